@@ -0,0 +1,16 @@
+#!/bin/sh
+# Lints and tests godot_data with every JSON and RON flavour, with and without minname.
+# Each build enables the matching readers, so the tests check the output reads back.
+set -e
+cd "$(dirname "$0")"
+
+for json in "" serjson serjsonpretty serjsonnatural; do
+    for ron in "" serron serronpretty; do
+        for minname in "" minname; do
+            features="dejson deron serbin debin serde msgpack cbor yaml toml $json $ron $minname"
+            echo "== $features"
+            cargo clippy -q -p godot_data --no-deps --all-targets --features "$features" -- -D warnings
+            cargo test -q -p godot_data --features "$features"
+        done
+    done
+done
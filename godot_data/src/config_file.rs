@@ -0,0 +1,34 @@
+use crate::project_file::GodotFileParameters;
+use std::collections::HashMap;
+
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+#[cfg(any(feature = "serjson", feature = "serjsonpretty", feature = "serjsonnatural"))]
+use nanoserde::SerJson;
+#[cfg(feature = "dejson")]
+use nanoserde::DeJson;
+#[cfg(any(feature = "serron", feature = "serronpretty"))]
+use nanoserde::SerRon;
+#[cfg(feature = "deron")]
+use nanoserde::DeRon;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A Godot `ConfigFile` (`project.godot`, `.import`, `export_presets.cfg`, `.gdextension`, ...).
+/// Keys written before the first `[section]` are stored under the empty section name.
+#[godot_macros::schema]
+#[cfg_attr(feature = "serjson", derive(SerJson))]
+#[cfg_attr(feature = "serjsonpretty", derive(SerJson))]
+#[cfg_attr(feature = "serjsonnatural", derive(SerJson))]
+#[cfg_attr(feature = "dejson", derive(DeJson))]
+#[cfg_attr(feature = "serbin", derive(Encode))]
+#[cfg_attr(feature = "debin", derive(Decode))]
+#[cfg_attr(feature = "serron", derive(SerRon))]
+#[cfg_attr(feature = "serronpretty", derive(SerRon))]
+#[cfg_attr(feature = "deron", derive(DeRon))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ConfigFile {
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "s"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "s"))]
+    pub sections: HashMap<String, GodotFileParameters>
+}
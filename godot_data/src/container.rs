@@ -0,0 +1,250 @@
+//! Self-describing wrapper around the bincode encoding of the models.
+//!
+//! Layout (little endian): magic `GDPB`, schema version `u16`, payload kind `u8`, flags `u8`,
+//! payload length `u64`, CRC-32 of the payload as `u32` if [`FLAG_CHECKSUM`] is set, payload.
+//! Files without the magic are headerless bincode from before the container and read as
+//! schema version 0.
+
+use std::fmt;
+
+#[cfg(feature = "debin")]
+use bincode::Decode;
+#[cfg(feature = "serbin")]
+use bincode::Encode;
+
+pub const MAGIC: &[u8; 4] = b"GDPB";
+/// Bump whenever the bincode layout of a model changes, and add the matching entry to
+/// `MIGRATIONS`.
+pub const SCHEMA_VERSION: u16 = 1;
+pub const FLAG_CHECKSUM: u8 = 1;
+
+const HEADER_LEN: usize = 16;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PayloadKind {
+    Project = 1,
+    Scene = 2,
+    Resource = 3,
+    /// A `.pck` directory listing.
+    Pack = 4,
+}
+
+impl PayloadKind {
+    pub fn from_u8(value: u8) -> Option<PayloadKind> {
+        match value {
+            1 => Some(PayloadKind::Project),
+            2 => Some(PayloadKind::Scene),
+            3 => Some(PayloadKind::Resource),
+            4 => Some(PayloadKind::Pack),
+            _ => None,
+        }
+    }
+}
+
+impl fmt::Display for PayloadKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PayloadKind::Project => "project",
+            PayloadKind::Scene => "scene",
+            PayloadKind::Resource => "resource",
+            PayloadKind::Pack => "pack",
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ContainerHeader {
+    pub schema_version: u16,
+    /// `None` for headerless files, which don't record what they hold.
+    pub kind: Option<PayloadKind>,
+    pub checksum: Option<u32>,
+}
+
+#[derive(Debug)]
+pub enum ContainerError {
+    Truncated,
+    UnknownKind(u8),
+    /// Written by a newer version of the tools.
+    UnsupportedVersion(u16),
+    KindMismatch { expected: PayloadKind, found: PayloadKind },
+    ChecksumMismatch { expected: u32, found: u32 },
+    #[cfg(feature = "serbin")]
+    Encode(bincode::error::EncodeError),
+    #[cfg(feature = "debin")]
+    Decode(bincode::error::DecodeError),
+}
+
+impl fmt::Display for ContainerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ContainerError::Truncated => write!(f, "the file is truncated"),
+            ContainerError::UnknownKind(kind) => write!(f, "unknown payload kind {}", kind),
+            ContainerError::UnsupportedVersion(version) => write!(
+                f,
+                "schema version {} is newer than the supported version {}",
+                version, SCHEMA_VERSION
+            ),
+            ContainerError::KindMismatch { expected, found } => {
+                write!(f, "expected a {} but the file holds a {}", expected, found)
+            }
+            ContainerError::ChecksumMismatch { expected, found } => {
+                write!(f, "checksum mismatch: expected {:08x}, found {:08x}", expected, found)
+            }
+            #[cfg(feature = "serbin")]
+            ContainerError::Encode(error) => write!(f, "{}", error),
+            #[cfg(feature = "debin")]
+            ContainerError::Decode(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for ContainerError {}
+
+/// CRC-32 (IEEE), as used by zip and PNG.
+pub fn crc32(data: &[u8]) -> u32 {
+    let mut crc = !0u32;
+    for byte in data {
+        crc ^= *byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 != 0 { (crc >> 1) ^ 0xEDB88320 } else { crc >> 1 };
+        }
+    }
+    !crc
+}
+
+/// Upgrades a payload of schema version `v` to `v + 1`.
+#[cfg(feature = "debin")]
+type Migration = fn(PayloadKind, Vec<u8>) -> Result<Vec<u8>, ContainerError>;
+
+/// `MIGRATIONS[v]` upgrades schema version `v`, so there is one entry per past version.
+#[cfg(feature = "debin")]
+const MIGRATIONS: [Migration; SCHEMA_VERSION as usize] = [migrate_headerless];
+
+/// Version 0 is headerless bincode. `GodotValue` variants have only been appended since, which
+/// keeps their indices, so the payload decodes as is.
+#[cfg(feature = "debin")]
+fn migrate_headerless(_kind: PayloadKind, payload: Vec<u8>) -> Result<Vec<u8>, ContainerError> {
+    Ok(payload)
+}
+
+/// Wraps an already encoded payload.
+pub fn write_container(kind: PayloadKind, payload: &[u8], checksum: bool) -> Vec<u8> {
+    let mut out = Vec::with_capacity(HEADER_LEN + 4 + payload.len());
+    out.extend(MAGIC);
+    out.extend(SCHEMA_VERSION.to_le_bytes());
+    out.push(kind as u8);
+    out.push(if checksum { FLAG_CHECKSUM } else { 0 });
+    out.extend((payload.len() as u64).to_le_bytes());
+    if checksum {
+        out.extend(crc32(payload).to_le_bytes());
+    }
+    out.extend(payload);
+    out
+}
+
+/// Splits a container into its header and payload, verifying the checksum.
+pub fn read_container(bytes: &[u8]) -> Result<(ContainerHeader, &[u8]), ContainerError> {
+    if !bytes.starts_with(MAGIC) {
+        return Ok((ContainerHeader { schema_version: 0, kind: None, checksum: None }, bytes));
+    }
+    if bytes.len() < HEADER_LEN {
+        return Err(ContainerError::Truncated);
+    }
+    let schema_version = u16::from_le_bytes([bytes[4], bytes[5]]);
+    if schema_version > SCHEMA_VERSION {
+        return Err(ContainerError::UnsupportedVersion(schema_version));
+    }
+    let kind = PayloadKind::from_u8(bytes[6]).ok_or(ContainerError::UnknownKind(bytes[6]))?;
+    let flags = bytes[7];
+    let len = u64::from_le_bytes(bytes[8..16].try_into().unwrap());
+    let mut rest = &bytes[HEADER_LEN..];
+
+    let checksum = if flags & FLAG_CHECKSUM != 0 {
+        let (checksum, payload) = rest.split_first_chunk::<4>().ok_or(ContainerError::Truncated)?;
+        rest = payload;
+        Some(u32::from_le_bytes(*checksum))
+    } else {
+        None
+    };
+    let payload = usize::try_from(len)
+        .ok()
+        .and_then(|len| rest.get(..len))
+        .ok_or(ContainerError::Truncated)?;
+    if let Some(expected) = checksum {
+        let found = crc32(payload);
+        if found != expected {
+            return Err(ContainerError::ChecksumMismatch { expected, found });
+        }
+    }
+    Ok((ContainerHeader { schema_version, kind: Some(kind), checksum }, payload))
+}
+
+#[cfg(feature = "serbin")]
+pub fn encode_container<T: Encode>(value: &T, kind: PayloadKind, checksum: bool) -> Result<Vec<u8>, ContainerError> {
+    let payload = bincode::encode_to_vec(value, bincode::config::standard()).map_err(ContainerError::Encode)?;
+    Ok(write_container(kind, &payload, checksum))
+}
+
+/// Decodes a container holding `expected`, migrating payloads of older schema versions.
+#[cfg(feature = "debin")]
+pub fn decode_container<T: Decode<()>>(bytes: &[u8], expected: PayloadKind) -> Result<T, ContainerError> {
+    let (header, payload) = read_container(bytes)?;
+    if let Some(found) = header.kind.filter(|found| *found != expected) {
+        return Err(ContainerError::KindMismatch { expected, found });
+    }
+    let mut payload = payload.to_vec();
+    for migration in &MIGRATIONS[header.schema_version as usize..] {
+        payload = migration(expected, payload)?;
+    }
+    let (value, _) =
+        bincode::decode_from_slice(&payload, bincode::config::standard()).map_err(ContainerError::Decode)?;
+    Ok(value)
+}
+
+#[cfg(all(test, feature = "serbin", feature = "debin"))]
+mod tests {
+    use super::*;
+    use crate::values::GodotValue;
+
+    #[test]
+    fn test_crc32() {
+        assert_eq!(crc32(b"123456789"), 0xCBF43926);
+    }
+
+    #[test]
+    fn test_container_round_trip() {
+        let value = GodotValue::Array(vec![GodotValue::Integer(3), GodotValue::String("a".to_string())]);
+        for checksum in [false, true] {
+            let bytes = encode_container(&value, PayloadKind::Resource, checksum).unwrap();
+            let (header, _) = read_container(&bytes).unwrap();
+            assert_eq!(header.schema_version, SCHEMA_VERSION);
+            assert_eq!(header.checksum.is_some(), checksum);
+            let decoded: GodotValue = decode_container(&bytes, PayloadKind::Resource).unwrap();
+            assert_eq!(decoded, value);
+        }
+    }
+
+    #[test]
+    fn test_container_errors() {
+        let value = GodotValue::Boolean(true);
+        let mut bytes = encode_container(&value, PayloadKind::Project, true).unwrap();
+        assert!(matches!(
+            decode_container::<GodotValue>(&bytes, PayloadKind::Scene),
+            Err(ContainerError::KindMismatch { expected: PayloadKind::Scene, found: PayloadKind::Project })
+        ));
+        *bytes.last_mut().unwrap() ^= 1;
+        assert!(matches!(read_container(&bytes), Err(ContainerError::ChecksumMismatch { .. })));
+        bytes[4] = 0xFF;
+        assert!(matches!(read_container(&bytes), Err(ContainerError::UnsupportedVersion(_))));
+        assert!(matches!(read_container(&bytes[..10]), Err(ContainerError::Truncated)));
+        assert!(matches!(read_container(&MAGIC[..]), Err(ContainerError::Truncated)));
+    }
+
+    #[test]
+    fn test_headerless_payload() {
+        let value = GodotValue::Vector2((1.0, 2.0));
+        let legacy = bincode::encode_to_vec(&value, bincode::config::standard()).unwrap();
+        let decoded: GodotValue = decode_container(&legacy, PayloadKind::Scene).unwrap();
+        assert_eq!(decoded, value);
+    }
+}
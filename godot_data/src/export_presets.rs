@@ -0,0 +1,86 @@
+use crate::project_file::GodotFileParameters;
+
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+#[cfg(any(feature = "serjson", feature = "serjsonpretty", feature = "serjsonnatural"))]
+use nanoserde::SerJson;
+#[cfg(feature = "dejson")]
+use nanoserde::DeJson;
+#[cfg(any(feature = "serron", feature = "serronpretty"))]
+use nanoserde::SerRon;
+#[cfg(feature = "deron")]
+use nanoserde::DeRon;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// One `[preset.N]` section of `export_presets.cfg` together with its `[preset.N.options]`.
+#[godot_macros::schema]
+#[cfg_attr(feature = "serjson", derive(SerJson))]
+#[cfg_attr(feature = "serjsonpretty", derive(SerJson))]
+#[cfg_attr(feature = "serjsonnatural", derive(SerJson))]
+#[cfg_attr(feature = "dejson", derive(DeJson))]
+#[cfg_attr(feature = "serbin", derive(Encode))]
+#[cfg_attr(feature = "debin", derive(Decode))]
+#[cfg_attr(feature = "serron", derive(SerRon))]
+#[cfg_attr(feature = "serronpretty", derive(SerRon))]
+#[cfg_attr(feature = "deron", derive(DeRon))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ExportPreset {
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "i"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "i"))]
+    pub index: u32,
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "n"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "n"))]
+    pub name: String,
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "pl"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "pl"))]
+    pub platform: String,
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "r"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "r"))]
+    pub runnable: bool,
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "ds"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "ds"))]
+    pub dedicated_server: bool,
+    /// `custom_features`, split on commas.
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "cf"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "cf"))]
+    pub custom_features: Vec<String>,
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "ef"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "ef"))]
+    pub export_filter: String,
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "if"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "if"))]
+    pub include_filter: String,
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "xf"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "xf"))]
+    pub exclude_filter: String,
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "ep"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "ep"))]
+    pub export_path: String,
+    /// Remaining keys of `[preset.N]`.
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "s"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "s"))]
+    pub settings: GodotFileParameters,
+    /// Platform-specific keys of `[preset.N.options]`.
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "o"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "o"))]
+    pub options: GodotFileParameters,
+}
+
+/// Contents of `export_presets.cfg`, ordered by preset index.
+#[godot_macros::schema]
+#[cfg_attr(feature = "serjson", derive(SerJson))]
+#[cfg_attr(feature = "serjsonpretty", derive(SerJson))]
+#[cfg_attr(feature = "serjsonnatural", derive(SerJson))]
+#[cfg_attr(feature = "dejson", derive(DeJson))]
+#[cfg_attr(feature = "serbin", derive(Encode))]
+#[cfg_attr(feature = "debin", derive(Decode))]
+#[cfg_attr(feature = "serron", derive(SerRon))]
+#[cfg_attr(feature = "serronpretty", derive(SerRon))]
+#[cfg_attr(feature = "deron", derive(DeRon))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ExportPresets {
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "p"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "p"))]
+    pub presets: Vec<ExportPreset>,
+}
@@ -0,0 +1,57 @@
+// The nanoserde `DeJson`/`DeRon` derives expand `Option` fields into code that trips this lint.
+#![allow(clippy::question_mark)]
+
+use std::collections::HashMap;
+
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+#[cfg(any(feature = "serjson", feature = "serjsonpretty", feature = "serjsonnatural"))]
+use nanoserde::SerJson;
+#[cfg(feature = "dejson")]
+use nanoserde::DeJson;
+#[cfg(any(feature = "serron", feature = "serronpretty"))]
+use nanoserde::SerRon;
+#[cfg(feature = "deron")]
+use nanoserde::DeRon;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Contents of a `.gdextension` manifest.
+#[godot_macros::schema]
+#[cfg_attr(feature = "serjson", derive(SerJson))]
+#[cfg_attr(feature = "serjsonpretty", derive(SerJson))]
+#[cfg_attr(feature = "serjsonnatural", derive(SerJson))]
+#[cfg_attr(feature = "dejson", derive(DeJson))]
+#[cfg_attr(feature = "serbin", derive(Encode))]
+#[cfg_attr(feature = "debin", derive(Decode))]
+#[cfg_attr(feature = "serron", derive(SerRon))]
+#[cfg_attr(feature = "serronpretty", derive(SerRon))]
+#[cfg_attr(feature = "deron", derive(DeRon))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct GDExtension {
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "es"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "es"))]
+    pub entry_symbol: String,
+    /// `compatibility_minimum`, written either as a number (`4.1`) or a string (`"4.1"`).
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "cmin"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "cmin"))]
+    pub compatibility_minimum: Option<String>,
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "cmax"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "cmax"))]
+    pub compatibility_maximum: Option<String>,
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "r"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "r"))]
+    pub reloadable: bool,
+    /// `[libraries]`, keyed by dot-separated feature tags such as `linux.debug.x86_64`.
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "l"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "l"))]
+    pub libraries: HashMap<String, String>,
+    /// `[icons]`, class name to icon path.
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "ic"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "ic"))]
+    pub icons: HashMap<String, String>,
+    /// `[dependencies]`, feature tags to `(library path, target directory)` pairs.
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "d"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "d"))]
+    pub dependencies: HashMap<String, Vec<(String, String)>>,
+}
@@ -0,0 +1,60 @@
+// The nanoserde `DeJson`/`DeRon` derives expand `Option` fields into code that trips this lint.
+#![allow(clippy::question_mark)]
+
+use crate::project_file::GodotFileParameters;
+use std::collections::HashMap;
+
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+#[cfg(any(feature = "serjson", feature = "serjsonpretty", feature = "serjsonnatural"))]
+use nanoserde::SerJson;
+#[cfg(feature = "dejson")]
+use nanoserde::DeJson;
+#[cfg(any(feature = "serron", feature = "serronpretty"))]
+use nanoserde::SerRon;
+#[cfg(feature = "deron")]
+use nanoserde::DeRon;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Contents of a `.import` sidecar file.
+#[godot_macros::schema]
+#[cfg_attr(feature = "serjson", derive(SerJson))]
+#[cfg_attr(feature = "serjsonpretty", derive(SerJson))]
+#[cfg_attr(feature = "serjsonnatural", derive(SerJson))]
+#[cfg_attr(feature = "dejson", derive(DeJson))]
+#[cfg_attr(feature = "serbin", derive(Encode))]
+#[cfg_attr(feature = "debin", derive(Decode))]
+#[cfg_attr(feature = "serron", derive(SerRon))]
+#[cfg_attr(feature = "serronpretty", derive(SerRon))]
+#[cfg_attr(feature = "deron", derive(DeRon))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ImportFile {
+    /// `[remap] importer`, e.g. `texture` or `scene`.
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "i"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "i"))]
+    pub importer: String,
+    /// `[remap] type`, the resource type produced by the importer.
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "t"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "t"))]
+    pub _type: Option<String>,
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "u"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "u"))]
+    pub uid: Option<String>,
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "p"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "p"))]
+    pub path: Option<String>,
+    /// Feature-specific imported paths such as `path.s3tc` or `path.etc2`, keyed by feature.
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "pp"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "pp"))]
+    pub platform_paths: HashMap<String, String>,
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "sf"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "sf"))]
+    pub source_file: Option<String>,
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "df"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "df"))]
+    pub dest_files: Vec<String>,
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "pr"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "pr"))]
+    pub params: GodotFileParameters,
+}
@@ -1,8 +1,115 @@
 pub mod project_file;
 pub mod values;
 pub mod tscn_file;
+pub mod import_file;
+pub mod config_file;
+pub mod export_presets;
+pub mod gdextension;
+pub mod project_settings;
+pub mod pck_file;
+pub mod scene_records;
+pub mod schema;
+#[cfg(feature = "bincode")]
+pub mod container;
+#[cfg(feature = "serde")]
+pub mod serde_formats;
+#[cfg(feature = "serjsonnatural")]
+mod natural_json;
 
 #[cfg(feature = "nanoserde")]
 pub use nanoserde;
 #[cfg(feature = "bincode")]
-pub use bincode;
\ No newline at end of file
+pub use bincode;
+#[cfg(feature = "serde")]
+pub use serde;
+
+#[cfg(any(
+    all(feature = "serjson", feature = "serjsonpretty"),
+    all(feature = "serjson", feature = "serjsonnatural"),
+    all(feature = "serjsonpretty", feature = "serjsonnatural")
+))]
+compile_error!("only one of `serjson`, `serjsonpretty` and `serjsonnatural` can be enabled");
+#[cfg(all(feature = "serron", feature = "serronpretty"))]
+compile_error!("only one of `serron` and `serronpretty` can be enabled");
+
+/// Every format enabled in this build must read back what it writes, whatever the other
+/// features are; `feature-matrix.sh` runs this over the feature combinations.
+#[cfg(all(
+    test,
+    any(
+        all(any(feature = "serjson", feature = "serjsonpretty", feature = "serjsonnatural"), feature = "dejson"),
+        all(any(feature = "serron", feature = "serronpretty"), feature = "deron"),
+        all(feature = "serbin", feature = "debin"),
+        feature = "serde"
+    )
+))]
+mod tests {
+    use crate::values::GodotValue;
+
+    fn sample() -> GodotValue {
+        GodotValue::Dictionary(vec![
+            ("null".to_string(), GodotValue::Null),
+            ("string".to_string(), GodotValue::String("a \"quoted\" \\ string".to_string())),
+            ("name".to_string(), GodotValue::StringName("name".to_string())),
+            ("integer".to_string(), GodotValue::Integer(-3)),
+            ("float".to_string(), GodotValue::Float(1.0)),
+            ("boolean".to_string(), GodotValue::Boolean(true)),
+            ("strings".to_string(), GodotValue::PackedStringArray(vec!["a".to_string(), String::new()])),
+            ("path".to_string(), GodotValue::NodePath("../Player".to_string())),
+            ("vector".to_string(), GodotValue::Vector2((1.0, 2.5))),
+            ("rect".to_string(), GodotValue::Rect2i((0, 0, 64, 32))),
+            ("color".to_string(), GodotValue::Color((1.0, 0.5, 0.0, 1.0))),
+            ("ext".to_string(), GodotValue::ExtResourceLink("1_abc".to_string())),
+            ("sub".to_string(), GodotValue::SubResourceLink("2_def".to_string())),
+            ("array".to_string(), GodotValue::Array(vec![GodotValue::Null, GodotValue::Vector3i((1, 2, 3))])),
+            ("type".to_string(), GodotValue::Dictionary(vec![("t".to_string(), GodotValue::Integer(1))])),
+            (
+                "event".to_string(),
+                GodotValue::Object(("InputEventKey".to_string(), vec![("keycode".to_string(), GodotValue::Integer(4194320))])),
+            ),
+            ("transform".to_string(), GodotValue::Transform2D([1.0, 0.0, 0.0, 1.0, 0.0, 0.0])),
+            ("basis".to_string(), GodotValue::Basis([1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0])),
+            ("bytes".to_string(), GodotValue::PackedByteArray(vec![0, 255])),
+            ("floats".to_string(), GodotValue::PackedFloat32Array(vec![0.5, -1.0])),
+            ("colors".to_string(), GodotValue::PackedColorArray(vec![(1.0, 0.0, 0.0, 1.0)])),
+        ])
+    }
+
+    #[cfg(all(any(feature = "serjson", feature = "serjsonpretty", feature = "serjsonnatural"), feature = "dejson"))]
+    #[test]
+    fn test_json_round_trip() {
+        use nanoserde::{DeJson, SerJson};
+        let json = sample().serialize_json();
+        assert_eq!(GodotValue::deserialize_json(&json).unwrap(), sample(), "{}", json);
+    }
+
+    #[cfg(all(any(feature = "serron", feature = "serronpretty"), feature = "deron"))]
+    #[test]
+    fn test_ron_round_trip() {
+        use nanoserde::{DeRon, SerRon};
+        let ron = sample().serialize_ron();
+        assert_eq!(GodotValue::deserialize_ron(&ron).unwrap(), sample(), "{}", ron);
+    }
+
+    #[cfg(all(feature = "serbin", feature = "debin"))]
+    #[test]
+    fn test_bin_round_trip() {
+        let config = bincode::config::standard();
+        let bytes = bincode::encode_to_vec(sample(), config).unwrap();
+        let (value, _): (GodotValue, usize) = bincode::decode_from_slice(&bytes, config).unwrap();
+        assert_eq!(value, sample());
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let json = serde_json::to_string(&sample()).unwrap();
+        assert_eq!(serde_json::from_str::<GodotValue>(&json).unwrap(), sample());
+        #[cfg(feature = "msgpack")]
+        assert_eq!(crate::serde_formats::from_msgpack::<GodotValue>(&crate::serde_formats::to_msgpack(&sample()).unwrap()).unwrap(), sample());
+        #[cfg(feature = "cbor")]
+        assert_eq!(crate::serde_formats::from_cbor::<GodotValue>(&crate::serde_formats::to_cbor(&sample()).unwrap()).unwrap(), sample());
+        #[cfg(feature = "yaml")]
+        assert_eq!(crate::serde_formats::from_yaml::<GodotValue>(&crate::serde_formats::to_yaml(&sample()).unwrap()).unwrap(), sample());
+    }
+}
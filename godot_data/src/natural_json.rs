@@ -0,0 +1,335 @@
+//! The "natural" JSON mapping of [`GodotValue`], used by the `serjsonnatural` feature.
+//!
+//! Null, strings, integers, floats, booleans, arrays and dictionaries are written as the
+//! matching JSON values. Every other variant is an object `{"type": "Vector2", "value": ...}`,
+//! as are floats JSON can't hold and dictionaries whose first key is `type`, so reading the
+//! output back gives the exact same value. Floats always have a `.` or an exponent, which
+//! keeps them apart from integers. With `minname` the object is `{"t": "V2", "v": ...}`, the
+//! same keys `serjsonpretty` uses.
+
+#[cfg(feature = "dejson")]
+use std::str::Chars;
+
+#[cfg(feature = "dejson")]
+use nanoserde::{DeJson, DeJsonErr, DeJsonState, DeJsonTok};
+use nanoserde::{SerJson, SerJsonState};
+
+use crate::values::GodotValue;
+
+/// The keys of a tagged object, the `tag` and `content` of [`GodotValue`].
+const TYPE: &str = if cfg!(feature = "minname") { "t" } else { "type" };
+const VALUE: &str = if cfg!(feature = "minname") { "v" } else { "value" };
+
+/// Full and `minname` names of the variants written with a `type`.
+const TAGS: &[(&str, &str)] = &[
+    ("StringName", "SN"),
+    ("Float", "F"),
+    ("PackedStringArray", "PSA"),
+    ("NodePath", "NP"),
+    ("Vector2", "V2"),
+    ("Rect2", "R2"),
+    ("Color", "C"),
+    ("ExtResourceLink", "ERL"),
+    ("SubResourceLink", "SRL"),
+    ("Dictionary", "D"),
+    ("Object", "O"),
+    ("Vector2i", "V2I"),
+    ("Rect2i", "R2I"),
+    ("Vector3", "V3"),
+    ("Vector3i", "V3I"),
+    ("Vector4", "V4"),
+    ("Vector4i", "V4I"),
+    ("Transform2D", "T2"),
+    ("Plane", "PL"),
+    ("Quaternion", "Q"),
+    ("AABB", "AB"),
+    ("Basis", "BS"),
+    ("Transform3D", "T3"),
+    ("Projection", "PJ"),
+    ("PackedByteArray", "PBA"),
+    ("PackedInt32Array", "PI32"),
+    ("PackedInt64Array", "PI64"),
+    ("PackedFloat32Array", "PF32"),
+    ("PackedFloat64Array", "PF64"),
+    ("PackedVector2Array", "PV2"),
+    ("PackedVector3Array", "PV3"),
+    ("PackedColorArray", "PC"),
+    ("PackedVector4Array", "PV4"),
+];
+
+/// The name written for the variant `name`.
+fn tag(name: &str) -> &'static str {
+    let (full, short) = TAGS.iter().find(|(full, _)| *full == name).expect("untagged variant");
+    if cfg!(feature = "minname") { short } else { full }
+}
+
+/// The variant written as `tag`, by its full name.
+#[cfg(feature = "dejson")]
+fn variant(tag: &str) -> Option<&'static str> {
+    TAGS.iter()
+        .find(|(full, short)| if cfg!(feature = "minname") { *short == tag } else { *full == tag })
+        .map(|(full, _)| *full)
+}
+
+/// Dictionary entries as a JSON object, in order.
+struct Entries<'a>(&'a [(String, GodotValue)]);
+
+impl SerJson for Entries<'_> {
+    fn ser_json(&self, d: usize, s: &mut SerJsonState) {
+        s.out.push('{');
+        for (index, (key, value)) in self.0.iter().enumerate() {
+            if index != 0 {
+                s.conl();
+            }
+            key.ser_json(d + 1, s);
+            s.out.push(':');
+            value.ser_json(d + 1, s);
+        }
+        s.out.push('}');
+    }
+}
+
+/// `Object` as `["Class", {"property": value, ...}]`.
+struct Object<'a>(&'a str, Entries<'a>);
+
+impl SerJson for Object<'_> {
+    fn ser_json(&self, d: usize, s: &mut SerJsonState) {
+        s.out.push('[');
+        self.0.ser_json(d, s);
+        s.out.push(',');
+        self.1.ser_json(d, s);
+        s.out.push(']');
+    }
+}
+
+fn tagged<T: SerJson + ?Sized>(s: &mut SerJsonState, d: usize, name: &str, value: &T) {
+    s.out.push('{');
+    s.label(TYPE);
+    s.out.push(':');
+    s.label(tag(name));
+    s.out.push(',');
+    s.label(VALUE);
+    s.out.push(':');
+    value.ser_json(d, s);
+    s.out.push('}');
+}
+
+impl SerJson for GodotValue {
+    fn ser_json(&self, d: usize, s: &mut SerJsonState) {
+        match self {
+            GodotValue::Null => s.out.push_str("null"),
+            GodotValue::String(value) => value.ser_json(d, s),
+            GodotValue::Integer(value) => value.ser_json(d, s),
+            GodotValue::Float(value) if value.is_finite() => value.ser_json(d, s),
+            GodotValue::Float(value) => tagged(s, d, "Float", &value.to_string()),
+            GodotValue::Boolean(value) => value.ser_json(d, s),
+            GodotValue::Array(value) => value.ser_json(d, s),
+            GodotValue::Dictionary(value) if value.first().is_none_or(|(key, _)| key != TYPE) => {
+                Entries(value).ser_json(d, s)
+            }
+            GodotValue::Dictionary(value) => tagged(s, d, "Dictionary", &Entries(value)),
+            GodotValue::Object((class, properties)) => {
+                tagged(s, d, "Object", &Object(class, Entries(properties)))
+            }
+            GodotValue::StringName(value) => tagged(s, d, "StringName", value),
+            GodotValue::PackedStringArray(value) => tagged(s, d, "PackedStringArray", value),
+            GodotValue::NodePath(value) => tagged(s, d, "NodePath", value),
+            GodotValue::Vector2(value) => tagged(s, d, "Vector2", value),
+            GodotValue::Rect2(value) => tagged(s, d, "Rect2", value),
+            GodotValue::Color(value) => tagged(s, d, "Color", value),
+            GodotValue::ExtResourceLink(value) => tagged(s, d, "ExtResourceLink", value),
+            GodotValue::SubResourceLink(value) => tagged(s, d, "SubResourceLink", value),
+            GodotValue::Vector2i(value) => tagged(s, d, "Vector2i", value),
+            GodotValue::Rect2i(value) => tagged(s, d, "Rect2i", value),
+            GodotValue::Vector3(value) => tagged(s, d, "Vector3", value),
+            GodotValue::Vector3i(value) => tagged(s, d, "Vector3i", value),
+            GodotValue::Vector4(value) => tagged(s, d, "Vector4", value),
+            GodotValue::Vector4i(value) => tagged(s, d, "Vector4i", value),
+            GodotValue::Transform2D(value) => tagged(s, d, "Transform2D", value),
+            GodotValue::Plane(value) => tagged(s, d, "Plane", value),
+            GodotValue::Quaternion(value) => tagged(s, d, "Quaternion", value),
+            GodotValue::AABB(value) => tagged(s, d, "AABB", value),
+            GodotValue::Basis(value) => tagged(s, d, "Basis", value),
+            GodotValue::Transform3D(value) => tagged(s, d, "Transform3D", value),
+            GodotValue::Projection(value) => tagged(s, d, "Projection", value),
+            GodotValue::PackedByteArray(value) => tagged(s, d, "PackedByteArray", value),
+            GodotValue::PackedInt32Array(value) => tagged(s, d, "PackedInt32Array", value),
+            GodotValue::PackedInt64Array(value) => tagged(s, d, "PackedInt64Array", value),
+            GodotValue::PackedFloat32Array(value) => tagged(s, d, "PackedFloat32Array", value),
+            GodotValue::PackedFloat64Array(value) => tagged(s, d, "PackedFloat64Array", value),
+            GodotValue::PackedVector2Array(value) => tagged(s, d, "PackedVector2Array", value),
+            GodotValue::PackedVector3Array(value) => tagged(s, d, "PackedVector3Array", value),
+            GodotValue::PackedColorArray(value) => tagged(s, d, "PackedColorArray", value),
+            GodotValue::PackedVector4Array(value) => tagged(s, d, "PackedVector4Array", value),
+        }
+    }
+}
+
+/// Reads the entries of an object whose `{` has already been read.
+#[cfg(feature = "dejson")]
+fn de_entries(s: &mut DeJsonState, i: &mut Chars) -> Result<Vec<(String, GodotValue)>, DeJsonErr> {
+    let mut entries = Vec::new();
+    while s.next_str().is_some() {
+        let key = std::mem::take(&mut s.strbuf);
+        s.next_colon(i)?;
+        entries.push((key, GodotValue::de_json(s, i)?));
+        s.eat_comma_curly(i)?;
+    }
+    s.curly_close(i)?;
+    Ok(entries)
+}
+
+/// Reads the value of a tagged object, its type being `name`.
+#[cfg(feature = "dejson")]
+fn de_tagged(s: &mut DeJsonState, i: &mut Chars, name: &str) -> Result<GodotValue, DeJsonErr> {
+    Ok(match variant(name) {
+        Some("Float") => match s.tok {
+            DeJsonTok::Str => {
+                let value = String::de_json(s, i)?;
+                GodotValue::Float(value.parse().map_err(|_| s.err_parse(&value))?)
+            }
+            _ => GodotValue::Float(f64::de_json(s, i)?),
+        },
+        Some("Dictionary") => {
+            s.curly_open(i)?;
+            GodotValue::Dictionary(de_entries(s, i)?)
+        }
+        Some("Object") => {
+            s.block_open(i)?;
+            let class = String::de_json(s, i)?;
+            s.eat_comma_block(i)?;
+            s.curly_open(i)?;
+            let properties = de_entries(s, i)?;
+            s.eat_comma_block(i)?;
+            s.block_close(i)?;
+            GodotValue::Object((class, properties))
+        }
+        Some("StringName") => GodotValue::StringName(DeJson::de_json(s, i)?),
+        Some("PackedStringArray") => GodotValue::PackedStringArray(DeJson::de_json(s, i)?),
+        Some("NodePath") => GodotValue::NodePath(DeJson::de_json(s, i)?),
+        Some("Vector2") => GodotValue::Vector2(DeJson::de_json(s, i)?),
+        Some("Rect2") => GodotValue::Rect2(DeJson::de_json(s, i)?),
+        Some("Color") => GodotValue::Color(DeJson::de_json(s, i)?),
+        Some("ExtResourceLink") => GodotValue::ExtResourceLink(DeJson::de_json(s, i)?),
+        Some("SubResourceLink") => GodotValue::SubResourceLink(DeJson::de_json(s, i)?),
+        Some("Vector2i") => GodotValue::Vector2i(DeJson::de_json(s, i)?),
+        Some("Rect2i") => GodotValue::Rect2i(DeJson::de_json(s, i)?),
+        Some("Vector3") => GodotValue::Vector3(DeJson::de_json(s, i)?),
+        Some("Vector3i") => GodotValue::Vector3i(DeJson::de_json(s, i)?),
+        Some("Vector4") => GodotValue::Vector4(DeJson::de_json(s, i)?),
+        Some("Vector4i") => GodotValue::Vector4i(DeJson::de_json(s, i)?),
+        Some("Transform2D") => GodotValue::Transform2D(DeJson::de_json(s, i)?),
+        Some("Plane") => GodotValue::Plane(DeJson::de_json(s, i)?),
+        Some("Quaternion") => GodotValue::Quaternion(DeJson::de_json(s, i)?),
+        Some("AABB") => GodotValue::AABB(DeJson::de_json(s, i)?),
+        Some("Basis") => GodotValue::Basis(DeJson::de_json(s, i)?),
+        Some("Transform3D") => GodotValue::Transform3D(DeJson::de_json(s, i)?),
+        Some("Projection") => GodotValue::Projection(DeJson::de_json(s, i)?),
+        Some("PackedByteArray") => GodotValue::PackedByteArray(DeJson::de_json(s, i)?),
+        Some("PackedInt32Array") => GodotValue::PackedInt32Array(DeJson::de_json(s, i)?),
+        Some("PackedInt64Array") => GodotValue::PackedInt64Array(DeJson::de_json(s, i)?),
+        Some("PackedFloat32Array") => GodotValue::PackedFloat32Array(DeJson::de_json(s, i)?),
+        Some("PackedFloat64Array") => GodotValue::PackedFloat64Array(DeJson::de_json(s, i)?),
+        Some("PackedVector2Array") => GodotValue::PackedVector2Array(DeJson::de_json(s, i)?),
+        Some("PackedVector3Array") => GodotValue::PackedVector3Array(DeJson::de_json(s, i)?),
+        Some("PackedColorArray") => GodotValue::PackedColorArray(DeJson::de_json(s, i)?),
+        Some("PackedVector4Array") => GodotValue::PackedVector4Array(DeJson::de_json(s, i)?),
+        _ => return Err(s.err_enum(name)),
+    })
+}
+
+#[cfg(feature = "dejson")]
+impl DeJson for GodotValue {
+    fn de_json(s: &mut DeJsonState, i: &mut Chars) -> Result<Self, DeJsonErr> {
+        match s.tok {
+            DeJsonTok::Null => {
+                s.next_tok(i)?;
+                Ok(GodotValue::Null)
+            }
+            DeJsonTok::Str => Ok(GodotValue::String(DeJson::de_json(s, i)?)),
+            DeJsonTok::U64(_) | DeJsonTok::I64(_) => Ok(GodotValue::Integer(DeJson::de_json(s, i)?)),
+            DeJsonTok::F64(_) => Ok(GodotValue::Float(DeJson::de_json(s, i)?)),
+            DeJsonTok::Bool(_) => Ok(GodotValue::Boolean(DeJson::de_json(s, i)?)),
+            DeJsonTok::BlockOpen => Ok(GodotValue::Array(DeJson::de_json(s, i)?)),
+            DeJsonTok::CurlyOpen => {
+                s.curly_open(i)?;
+                if s.next_str().is_none() || s.strbuf != TYPE {
+                    return Ok(GodotValue::Dictionary(de_entries(s, i)?));
+                }
+                s.next_colon(i)?;
+                let name = String::de_json(s, i)?;
+                s.eat_comma_curly(i)?;
+                if s.next_str().is_none() || s.strbuf != VALUE {
+                    return Err(s.err_exp(VALUE));
+                }
+                s.next_colon(i)?;
+                let value = de_tagged(s, i, &name)?;
+                s.eat_comma_curly(i)?;
+                s.curly_close(i)?;
+                Ok(value)
+            }
+            _ => Err(s.err_token("value")),
+        }
+    }
+}
+
+#[cfg(all(test, feature = "dejson"))]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_natural_json() {
+        let value = GodotValue::Dictionary(vec![
+            ("name".to_string(), GodotValue::String("Player".to_string())),
+            ("speed".to_string(), GodotValue::Float(2.0)),
+            ("lives".to_string(), GodotValue::Integer(3)),
+            ("items".to_string(), GodotValue::Array(vec![GodotValue::Null, GodotValue::Boolean(true)])),
+            ("position".to_string(), GodotValue::Vector2((1.0, 2.5))),
+        ]);
+        let json = value.serialize_json();
+        #[cfg(not(feature = "minname"))]
+        assert_eq!(
+            json,
+            r#"{"name":"Player","speed":2.0,"lives":3,"items":[null,true],"position":{"type":"Vector2","value":[1.0,2.5]}}"#
+        );
+        #[cfg(feature = "minname")]
+        assert_eq!(json, r#"{"name":"Player","speed":2.0,"lives":3,"items":[null,true],"position":{"t":"V2","v":[1.0,2.5]}}"#);
+        assert_eq!(GodotValue::deserialize_json(&json).unwrap(), value);
+    }
+
+    #[test]
+    fn test_natural_json_exact() {
+        for value in [
+            GodotValue::Float(1.0),
+            GodotValue::Float(1e300),
+            GodotValue::Float(f64::INFINITY),
+            GodotValue::Float(f64::NEG_INFINITY),
+            GodotValue::Integer(i64::MIN),
+            GodotValue::String("a \"quoted\" \\ string".to_string()),
+            GodotValue::StringName("name".to_string()),
+            GodotValue::NodePath("../Player".to_string()),
+            GodotValue::Dictionary(vec![]),
+            GodotValue::Dictionary(vec![
+                (TYPE.to_string(), GodotValue::String("Vector2".to_string())),
+                (VALUE.to_string(), GodotValue::Array(vec![])),
+            ]),
+            GodotValue::Dictionary(vec![
+                ("a".to_string(), GodotValue::Integer(1)),
+                ("a".to_string(), GodotValue::Integer(2)),
+            ]),
+            GodotValue::Object((
+                "InputEventKey".to_string(),
+                vec![("keycode".to_string(), GodotValue::Integer(4194320))],
+            )),
+            GodotValue::Transform2D([1.0, 0.0, 0.0, 1.0, 0.0, 0.0]),
+            GodotValue::PackedByteArray(vec![0, 255]),
+            GodotValue::PackedColorArray(vec![(1.0, 0.0, 0.0, 1.0)]),
+        ] {
+            let json = value.serialize_json();
+            assert_eq!(GodotValue::deserialize_json(&json).unwrap(), value, "{}", json);
+        }
+        let json = GodotValue::Float(f64::NAN).serialize_json();
+        assert!(matches!(GodotValue::deserialize_json(&json).unwrap(), GodotValue::Float(value) if value.is_nan()));
+        assert!(GodotValue::deserialize_json(&format!(r#"{{"{TYPE}":"Nope","{VALUE}":1}}"#)).is_err());
+    }
+}
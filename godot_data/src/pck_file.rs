@@ -0,0 +1,86 @@
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+#[cfg(any(feature = "serjson", feature = "serjsonpretty", feature = "serjsonnatural"))]
+use nanoserde::SerJson;
+#[cfg(feature = "dejson")]
+use nanoserde::DeJson;
+#[cfg(any(feature = "serron", feature = "serronpretty"))]
+use nanoserde::SerRon;
+#[cfg(feature = "deron")]
+use nanoserde::DeRon;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A file stored in a `.pck` archive.
+#[godot_macros::schema]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serjson", derive(SerJson))]
+#[cfg_attr(feature = "serjsonpretty", derive(SerJson))]
+#[cfg_attr(feature = "serjsonnatural", derive(SerJson))]
+#[cfg_attr(feature = "dejson", derive(DeJson))]
+#[cfg_attr(feature = "serbin", derive(Encode))]
+#[cfg_attr(feature = "debin", derive(Decode))]
+#[cfg_attr(feature = "serron", derive(SerRon))]
+#[cfg_attr(feature = "serronpretty", derive(SerRon))]
+#[cfg_attr(feature = "deron", derive(DeRon))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PckEntry {
+    /// `res://` path of the file.
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "p"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "p"))]
+    pub path: String,
+    /// Offset of the file data from the start of the buffer the pack was read from.
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "o"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "o"))]
+    pub offset: u64,
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "s"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "s"))]
+    pub size: u64,
+    /// Lowercase hex MD5 of the file data.
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "m"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "m"))]
+    pub md5: String,
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "e"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "e"))]
+    pub encrypted: bool,
+    /// Set in patch packs for files the patch deletes.
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "r"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "r"))]
+    pub removal: bool,
+}
+
+/// Header and directory of a `.pck` archive.
+#[godot_macros::schema]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serjson", derive(SerJson))]
+#[cfg_attr(feature = "serjsonpretty", derive(SerJson))]
+#[cfg_attr(feature = "serjsonnatural", derive(SerJson))]
+#[cfg_attr(feature = "dejson", derive(DeJson))]
+#[cfg_attr(feature = "serbin", derive(Encode))]
+#[cfg_attr(feature = "debin", derive(Decode))]
+#[cfg_attr(feature = "serron", derive(SerRon))]
+#[cfg_attr(feature = "serronpretty", derive(SerRon))]
+#[cfg_attr(feature = "deron", derive(DeRon))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct PckFile {
+    /// Pack format version: 2 for Godot 4.0 - 4.3, 3 since 4.4.
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "fv"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "fv"))]
+    pub format_version: u32,
+    /// Major, minor and patch version of the engine that wrote the pack.
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "ev"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "ev"))]
+    pub engine_version: (u32, u32, u32),
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "f"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "f"))]
+    pub flags: u32,
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "e"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "e"))]
+    pub entries: Vec<PckEntry>,
+}
+
+impl PckFile {
+    pub fn get(&self, path: &str) -> Option<&PckEntry> {
+        self.entries.iter().find(|entry| entry.path == path)
+    }
+}
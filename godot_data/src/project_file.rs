@@ -3,24 +3,36 @@ use std::collections::HashMap;
 
 #[cfg(feature = "bincode")]
 use bincode::{Decode, Encode};
-#[cfg(any(feature = "serjson", feature = "serjsonpretty", feature = "dejson"))]
-use nanoserde::{SerJson, DeJson};
-#[cfg(any(feature = "serron", feature = "deron"))]
-use nanoserde::{SerRon, DeRon};
+#[cfg(any(feature = "serjson", feature = "serjsonpretty", feature = "serjsonnatural"))]
+use nanoserde::SerJson;
+#[cfg(feature = "dejson")]
+use nanoserde::DeJson;
+#[cfg(any(feature = "serron", feature = "serronpretty"))]
+use nanoserde::SerRon;
+#[cfg(feature = "deron")]
+use nanoserde::DeRon;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
 pub type GodotFileParameters = HashMap<String, GodotValue>;
 
 
+#[godot_macros::schema]
 #[cfg_attr(feature = "serjson", derive(SerJson))]
 #[cfg_attr(feature = "serjsonpretty", derive(SerJson))]
+#[cfg_attr(feature = "serjsonnatural", derive(SerJson))]
 #[cfg_attr(feature = "dejson", derive(DeJson))]
 #[cfg_attr(feature = "serbin", derive(Encode))]
 #[cfg_attr(feature = "debin", derive(Decode))]
 #[cfg_attr(feature = "serron", derive(SerRon))]
+#[cfg_attr(feature = "serronpretty", derive(SerRon))]
 #[cfg_attr(feature = "deron", derive(DeRon))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct ProjectFile {
-    #[cfg_attr(feature = "minname", nserde(rename = "cv"))]
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "cv"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "cv"))]
     pub config_version: u32,
-    #[cfg_attr(feature = "minname", nserde(rename = "s"))]
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "s"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "s"))]
     pub sections: HashMap<String, GodotFileParameters>
 }
\ No newline at end of file
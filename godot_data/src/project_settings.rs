@@ -0,0 +1,256 @@
+use crate::project_file::{GodotFileParameters, ProjectFile};
+use crate::values::GodotValue;
+use std::collections::HashMap;
+
+/// Default `display/window/size/viewport_width` and `viewport_height`.
+pub const DEFAULT_VIEWPORT_SIZE: (i64, i64) = (1152, 648);
+
+/// An `[autoload]` entry. A leading `*` in the path marks the autoload as a global singleton.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Autoload<'a> {
+    pub name: &'a str,
+    pub path: &'a str,
+    pub singleton: bool,
+}
+
+/// An `Object(InputEvent...)` entry of an input action.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputEvent<'a> {
+    pub class: &'a str,
+    pub properties: &'a [(String, GodotValue)],
+}
+
+impl<'a> InputEvent<'a> {
+    pub fn get(&self, property: &str) -> Option<&'a GodotValue> {
+        self.properties.iter().find(|(k, _)| k == property).map(|(_, v)| v)
+    }
+}
+
+/// An `[input]` action with its deadzone and events.
+#[derive(Debug, Clone, PartialEq)]
+pub struct InputAction<'a> {
+    pub name: &'a str,
+    pub deadzone: f64,
+    pub events: Vec<InputEvent<'a>>,
+}
+
+/// Typed accessors for common settings of a [`ProjectFile`].
+#[derive(Clone, Copy)]
+pub struct ProjectSettings<'a> {
+    file: &'a ProjectFile,
+}
+
+/// Splits a setting path such as `application/config/name` into its section and key.
+pub fn split_setting_path(path: &str) -> Option<(&str, &str)> {
+    path.split_once('/')
+}
+
+impl ProjectFile {
+    pub fn settings(&self) -> ProjectSettings<'_> {
+        ProjectSettings { file: self }
+    }
+
+    /// Looks a setting up by its full path, e.g. `application/run/main_scene`.
+    /// Feature overrides are plain keys: `rendering/renderer/rendering_method.mobile`.
+    pub fn get(&self, path: &str) -> Option<&GodotValue> {
+        let (section, key) = split_setting_path(path)?;
+        self.sections.get(section)?.get(key)
+    }
+
+    /// Looks up the override of `path` for a single feature tag, e.g. `("…/rendering_method", "mobile")`.
+    pub fn get_override(&self, path: &str, feature: &str) -> Option<&GodotValue> {
+        self.get(&format!("{}.{}", path, feature))
+    }
+}
+
+/// Splits an override key such as `rendering_method.mobile` into the base key and its feature tags.
+pub fn split_feature_override(key: &str) -> (&str, Vec<&str>) {
+    let mut parts = key.split('.');
+    let base = parts.next().unwrap_or(key);
+    (base, parts.map(str::trim).filter(|f| !f.is_empty()).collect())
+}
+
+fn resolve_section(parameters: &GodotFileParameters, features: &[&str]) -> GodotFileParameters {
+    // Like Godot, an override applies when any of its tags is active, and the first one
+    // in file (sorted) order wins.
+    let mut keys = parameters.keys().collect::<Vec<_>>();
+    keys.sort();
+    let mut resolved: GodotFileParameters = HashMap::new();
+    let mut overridden = Vec::new();
+    for key in keys {
+        let (base, tags) = split_feature_override(key);
+        if tags.is_empty() {
+            resolved.entry(key.clone()).or_insert_with(|| parameters[key].clone());
+        } else if !overridden.contains(&base) && tags.iter().any(|tag| features.contains(tag)) {
+            overridden.push(base);
+            resolved.insert(base.to_string(), parameters[key].clone());
+        }
+    }
+    resolved
+}
+
+impl ProjectFile {
+    /// Returns the settings as seen with the given feature tags active (platform, `debug`/`release`,
+    /// custom export features, ...): applicable `key.feature` overrides replace their base key and
+    /// all override keys are dropped.
+    pub fn resolve_features(&self, features: &[&str]) -> ProjectFile {
+        ProjectFile {
+            config_version: self.config_version,
+            sections: self
+                .sections
+                .iter()
+                .map(|(name, parameters)| (name.clone(), resolve_section(parameters, features)))
+                .collect(),
+        }
+    }
+
+    /// Effective value of a single setting with the given feature tags active.
+    pub fn get_with_features(&self, path: &str, features: &[&str]) -> Option<&GodotValue> {
+        let (section, key) = split_setting_path(path)?;
+        let parameters = self.sections.get(section)?;
+        let mut overrides = parameters
+            .iter()
+            .filter(|(k, _)| {
+                let (base, tags) = split_feature_override(k);
+                base == key && tags.iter().any(|tag| features.contains(tag))
+            })
+            .collect::<Vec<_>>();
+        overrides.sort_by_key(|(k, _)| *k);
+        overrides.first().map(|(_, v)| *v).or_else(|| parameters.get(key))
+    }
+}
+
+fn as_str(value: &GodotValue) -> Option<&str> {
+    match value {
+        GodotValue::String(s) | GodotValue::StringName(s) => Some(s),
+        _ => None,
+    }
+}
+
+fn as_i64(value: &GodotValue) -> Option<i64> {
+    match value {
+        GodotValue::Integer(i) => Some(*i),
+        GodotValue::Float(f) => Some(*f as i64),
+        _ => None,
+    }
+}
+
+fn as_f64(value: &GodotValue) -> Option<f64> {
+    match value {
+        GodotValue::Integer(i) => Some(*i as f64),
+        GodotValue::Float(f) => Some(*f),
+        _ => None,
+    }
+}
+
+impl<'a> ProjectSettings<'a> {
+    pub fn get(&self, path: &str) -> Option<&'a GodotValue> {
+        self.file.get(path)
+    }
+
+    pub fn get_str(&self, path: &str) -> Option<&'a str> {
+        self.get(path).and_then(as_str)
+    }
+
+    pub fn name(&self) -> Option<&'a str> {
+        self.get_str("application/config/name")
+    }
+
+    pub fn version(&self) -> Option<&'a str> {
+        self.get_str("application/config/version")
+    }
+
+    pub fn icon(&self) -> Option<&'a str> {
+        self.get_str("application/config/icon")
+    }
+
+    pub fn main_scene(&self) -> Option<&'a str> {
+        self.get_str("application/run/main_scene")
+    }
+
+    /// `application/config/features`, e.g. `["4.3", "Mobile"]`.
+    pub fn features(&self) -> &'a [String] {
+        match self.get("application/config/features") {
+            Some(GodotValue::PackedStringArray(features)) => features,
+            _ => &[],
+        }
+    }
+
+    /// `display/window/size/viewport_width` and `viewport_height`, falling back to Godot's defaults.
+    pub fn viewport_size(&self) -> (i64, i64) {
+        (
+            self.get("display/window/size/viewport_width").and_then(as_i64).unwrap_or(DEFAULT_VIEWPORT_SIZE.0),
+            self.get("display/window/size/viewport_height").and_then(as_i64).unwrap_or(DEFAULT_VIEWPORT_SIZE.1),
+        )
+    }
+
+    /// Autoloads sorted by name.
+    pub fn autoloads(&self) -> Vec<Autoload<'a>> {
+        let Some(section) = self.file.sections.get("autoload") else {
+            return Vec::new();
+        };
+        let mut autoloads = section
+            .iter()
+            .filter_map(|(name, value)| {
+                let path = as_str(value)?;
+                Some(match path.strip_prefix('*') {
+                    Some(path) => Autoload { name, path, singleton: true },
+                    None => Autoload { name, path, singleton: false },
+                })
+            })
+            .collect::<Vec<_>>();
+        autoloads.sort_by_key(|a| a.name);
+        autoloads
+    }
+
+    /// Input actions sorted by name.
+    pub fn input_actions(&self) -> Vec<InputAction<'a>> {
+        let Some(section) = self.file.sections.get("input") else {
+            return Vec::new();
+        };
+        let mut actions = section
+            .iter()
+            .filter_map(|(name, value)| {
+                let GodotValue::Dictionary(entries) = value else {
+                    return None;
+                };
+                let mut action = InputAction { name, deadzone: 0.5, events: Vec::new() };
+                for (key, value) in entries {
+                    match (key.as_str(), value) {
+                        ("deadzone", v) => action.deadzone = as_f64(v).unwrap_or(action.deadzone),
+                        ("events", GodotValue::Array(events)) => {
+                            action.events = events
+                                .iter()
+                                .filter_map(|event| match event {
+                                    GodotValue::Object((class, properties)) => Some(InputEvent { class, properties }),
+                                    _ => None,
+                                })
+                                .collect();
+                        }
+                        _ => {}
+                    }
+                }
+                Some(action)
+            })
+            .collect::<Vec<_>>();
+        actions.sort_by_key(|a| a.name);
+        actions
+    }
+
+    /// Named layers of `kind` (`2d_physics`, `3d_render`, `avoidance`, ...), as `(layer number, name)`.
+    pub fn layer_names(&self, kind: &str) -> Vec<(u32, &'a str)> {
+        let Some(section) = self.file.sections.get("layer_names") else {
+            return Vec::new();
+        };
+        let prefix = format!("{}/layer_", kind);
+        let mut layers = section
+            .iter()
+            .filter_map(|(key, value)| {
+                let layer = key.strip_prefix(&prefix)?.parse().ok()?;
+                Some((layer, as_str(value)?))
+            })
+            .collect::<Vec<_>>();
+        layers.sort();
+        layers
+    }
+}
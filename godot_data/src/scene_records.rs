@@ -0,0 +1,157 @@
+// The nanoserde `DeJson`/`DeRon` derives expand `Option` fields into code that trips this lint.
+#![allow(clippy::question_mark)]
+
+use crate::tscn_file::{TSCNFile, Tag, TagType};
+use crate::values::GodotValue;
+use std::collections::HashMap;
+
+#[cfg(feature = "bincode")]
+use bincode::{Decode, Encode};
+#[cfg(any(feature = "serjson", feature = "serjsonpretty", feature = "serjsonnatural"))]
+use nanoserde::SerJson;
+#[cfg(feature = "dejson")]
+use nanoserde::DeJson;
+#[cfg(any(feature = "serron", feature = "serronpretty"))]
+use nanoserde::SerRon;
+#[cfg(feature = "deron")]
+use nanoserde::DeRon;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// One tag of a scene or resource on its own, a line of the JSON Lines export.
+#[godot_macros::schema]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serjson", derive(SerJson))]
+#[cfg_attr(feature = "serjsonpretty", derive(SerJson))]
+#[cfg_attr(feature = "serjsonnatural", derive(SerJson))]
+#[cfg_attr(feature = "dejson", derive(DeJson))]
+#[cfg_attr(feature = "serbin", derive(Encode))]
+#[cfg_attr(feature = "debin", derive(Decode))]
+#[cfg_attr(feature = "serron", derive(SerRon))]
+#[cfg_attr(feature = "serronpretty", derive(SerRon))]
+#[cfg_attr(feature = "deron", derive(DeRon))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SceneRecord {
+    /// The file the tag comes from, as given to [`scene_records`].
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "f"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "f"))]
+    pub file: String,
+    /// The path of a node from the scene root, `.` for the root itself. Connections have the
+    /// path of the node emitting the signal, other tags have none.
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "np"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "np"))]
+    pub node_path: Option<String>,
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "t"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "t"))]
+    pub _type: TagType,
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "a"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "a"))]
+    pub attrs: HashMap<String, GodotValue>,
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "p"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "p"))]
+    pub props: HashMap<String, GodotValue>,
+}
+
+fn attr<'a>(tag: &'a Tag, name: &str) -> Option<&'a str> {
+    match tag.attrs.get(name) {
+        Some(GodotValue::String(value) | GodotValue::NodePath(value)) => Some(value),
+        _ => None,
+    }
+}
+
+/// The path of a `node` tag from the scene root: `.` for the root, which has no `parent`,
+/// and `parent/name` for the others.
+pub fn node_path(tag: &Tag) -> Option<String> {
+    let name = attr(tag, "name")?;
+    Some(match attr(tag, "parent") {
+        None => ".".to_string(),
+        Some(".") => name.to_string(),
+        Some(parent) => format!("{}/{}", parent, name),
+    })
+}
+
+fn record(file: &str, node_path: Option<String>, tag: &Tag) -> SceneRecord {
+    SceneRecord {
+        file: file.to_string(),
+        node_path,
+        _type: tag._type,
+        attrs: tag.attrs.clone(),
+        props: tag.props.clone(),
+    }
+}
+
+/// The tags of `scene` one by one, each with the `file` it comes from: the header, then the
+/// external and sub-resources by id, then the nodes and connections in file order.
+pub fn scene_records(file: &str, scene: &TSCNFile) -> Vec<SceneRecord> {
+    let mut records = vec![record(file, None, &scene.header)];
+    for resources in [&scene.ext_resources, &scene.sub_resources] {
+        let mut ids: Vec<&String> = resources.keys().collect();
+        ids.sort();
+        records.extend(ids.into_iter().map(|id| record(file, None, &resources[id])));
+    }
+    for tag in &scene.nodes {
+        let path = if tag._type == TagType::Node { node_path(tag) } else { None };
+        records.push(record(file, path, tag));
+    }
+    for tag in &scene.connections {
+        records.push(record(file, attr(tag, "from").map(str::to_string), tag));
+    }
+    records
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn tag(_type: TagType, attrs: &[(&str, &str)]) -> Tag {
+        Tag {
+            _type,
+            attrs: attrs.iter().map(|(key, value)| (key.to_string(), GodotValue::String(value.to_string()))).collect(),
+            props: HashMap::new(),
+        }
+    }
+
+    #[test]
+    fn test_scene_records() {
+        let mut ext_resources = HashMap::new();
+        ext_resources.insert("2_b".to_string(), tag(TagType::ExtResource, &[("id", "2_b")]));
+        ext_resources.insert("1_a".to_string(), tag(TagType::ExtResource, &[("id", "1_a")]));
+        let scene = TSCNFile {
+            header: tag(TagType::GdScene, &[]),
+            ext_resources,
+            sub_resources: HashMap::new(),
+            nodes: vec![
+                tag(TagType::Node, &[("name", "Main")]),
+                tag(TagType::Node, &[("name", "Player"), ("parent", ".")]),
+                tag(TagType::Node, &[("name", "Sprite"), ("parent", "Player")]),
+                tag(TagType::Node, &[("name", "Shape"), ("parent", "Player/Sprite")]),
+            ],
+            connections: vec![tag(TagType::Connection, &[("signal", "hit"), ("from", "Player"), ("to", ".")])],
+        };
+        let records = scene_records("res://main.tscn", &scene);
+        let summary: Vec<(TagType, Option<&str>, Option<&str>)> = records
+            .iter()
+            .map(|record| {
+                let id = match record.attrs.get("id") {
+                    Some(GodotValue::String(id)) => Some(id.as_str()),
+                    _ => None,
+                };
+                (record._type, record.node_path.as_deref(), id)
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            vec![
+                (TagType::GdScene, None, None),
+                (TagType::ExtResource, None, Some("1_a")),
+                (TagType::ExtResource, None, Some("2_b")),
+                (TagType::Node, Some("."), None),
+                (TagType::Node, Some("Player"), None),
+                (TagType::Node, Some("Player/Sprite"), None),
+                (TagType::Node, Some("Player/Sprite/Shape"), None),
+                (TagType::Connection, Some("Player"), None),
+            ]
+        );
+        assert!(records.iter().all(|record| record.file == "res://main.tscn"));
+    }
+}
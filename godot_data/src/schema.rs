@@ -0,0 +1,804 @@
+//! JSON Schema and TypeScript definitions of the JSON written by the nanoserde derives.
+//!
+//! The shape depends on the `minname`, `serjsonpretty` and `serjsonnatural` features, so they
+//! are options here instead of being read from the build: a single binary can describe every
+//! combination. The definitions come from `#[godot_macros::schema]` on the models and are
+//! checked against real `serialize_json` output by the tests of every JSON feature.
+
+use std::collections::HashMap;
+
+use crate::config_file::ConfigFile;
+use crate::export_presets::ExportPresets;
+use crate::gdextension::GDExtension;
+use crate::import_file::ImportFile;
+use crate::pck_file::PckFile;
+use crate::project_file::{GodotFileParameters, ProjectFile};
+use crate::scene_records::SceneRecord;
+use crate::tscn_file::TSCNFile;
+use crate::values::GodotValue;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SchemaOptions {
+    /// The short field and variant names of `minname`.
+    pub minname: bool,
+    /// `{"type": "Vector2", "value": [...]}` values of `serjsonpretty`.
+    pub pretty: bool,
+    /// The values of `serjsonnatural`: plain JSON for nulls, strings, numbers, booleans, arrays
+    /// and dictionaries, `{"type": "Vector2", "value": [1.0, 2.0]}` for the rest. Takes
+    /// precedence over `pretty`.
+    pub natural: bool,
+}
+
+/// The models [`json_schema`] describes, all of them at once when no root is given.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Root {
+    ProjectFile,
+    TSCNFile,
+    PckFile,
+    ImportFile,
+    ConfigFile,
+    ExportPresets,
+    GDExtension,
+    SceneRecord,
+}
+
+impl Root {
+    pub const ALL: [Root; 8] = [
+        Root::ProjectFile,
+        Root::TSCNFile,
+        Root::PckFile,
+        Root::ImportFile,
+        Root::ConfigFile,
+        Root::ExportPresets,
+        Root::GDExtension,
+        Root::SceneRecord,
+    ];
+
+    /// The name of the type, also the title of its schema.
+    pub fn name(self) -> &'static str {
+        match self {
+            Root::ProjectFile => "ProjectFile",
+            Root::TSCNFile => "TSCNFile",
+            Root::PckFile => "PckFile",
+            Root::ImportFile => "ImportFile",
+            Root::ConfigFile => "ConfigFile",
+            Root::ExportPresets => "ExportPresets",
+            Root::GDExtension => "GDExtension",
+            Root::SceneRecord => "SceneRecord",
+        }
+    }
+
+    fn define(self, defs: &mut Definitions) {
+        match self {
+            Root::ProjectFile => ProjectFile::define(defs),
+            Root::TSCNFile => TSCNFile::define(defs),
+            Root::PckFile => PckFile::define(defs),
+            Root::ImportFile => ImportFile::define(defs),
+            Root::ConfigFile => ConfigFile::define(defs),
+            Root::ExportPresets => ExportPresets::define(defs),
+            Root::GDExtension => GDExtension::define(defs),
+            Root::SceneRecord => SceneRecord::define(defs),
+        }
+    }
+}
+
+/// The JSON shape of a Rust type.
+#[derive(Debug, Clone)]
+pub(crate) enum Ty {
+    Bool,
+    U8,
+    I32,
+    U32,
+    U64,
+    I64,
+    F32,
+    F64,
+    String,
+    Vec(Box<Ty>),
+    /// A `HashMap<String, _>`.
+    Map(Box<Ty>),
+    Tuple(Vec<Ty>),
+    Array(Box<Ty>, usize),
+    Ref(&'static str),
+}
+
+pub(crate) struct Field {
+    name: &'static str,
+    minname: &'static str,
+    ty: Ty,
+    /// `Option` fields are left out when `None`.
+    optional: bool,
+}
+
+impl Field {
+    pub(crate) fn of<T: Schema>(name: &'static str, minname: &'static str) -> Field {
+        Field { name, minname, ty: T::ty(), optional: T::OPTIONAL }
+    }
+}
+
+pub(crate) struct Variant {
+    name: &'static str,
+    minname: &'static str,
+    /// Empty for unit variants.
+    fields: Vec<Ty>,
+}
+
+impl Variant {
+    pub(crate) fn new(name: &'static str, minname: &'static str, fields: Vec<Ty>) -> Variant {
+        Variant { name, minname, fields }
+    }
+}
+
+pub(crate) enum Def {
+    Struct(Vec<Field>),
+    /// `pretty` marks enums deriving `SerJsonEnumPretty` under `serjsonpretty`.
+    Enum { variants: Vec<Variant>, pretty: bool },
+    /// `GodotValue`, which `serjsonnatural` writes by hand.
+    Value(Vec<Variant>),
+}
+
+/// The named types of the models, in the order they are first reached.
+#[derive(Default)]
+pub(crate) struct Definitions(Vec<(&'static str, Def)>);
+
+impl Definitions {
+    /// Adds `def` unless `name` is already defined; returns whether it was added, so a type
+    /// only defines the types it refers to once, recursive ones included.
+    pub(crate) fn add(&mut self, name: &'static str, def: Def) -> bool {
+        if self.0.iter().any(|(defined, _)| *defined == name) {
+            return false;
+        }
+        self.0.push((name, def));
+        true
+    }
+}
+
+/// Describes how a type is written by the nanoserde derives. `#[godot_macros::schema]`
+/// implements it on the models from their fields and variants, so the definitions follow
+/// every added variant or renamed field.
+pub(crate) trait Schema {
+    const OPTIONAL: bool = false;
+
+    /// The type where it appears as a field or variant value.
+    fn ty() -> Ty;
+
+    /// Adds the definitions of the named types it uses, its own included.
+    fn define(_defs: &mut Definitions) {}
+}
+
+macro_rules! primitives {
+    ($($rust:ty => $ty:ident),*) => {
+        $(impl Schema for $rust {
+            fn ty() -> Ty {
+                Ty::$ty
+            }
+        })*
+    };
+}
+
+primitives!(bool => Bool, u8 => U8, i32 => I32, u32 => U32, u64 => U64, i64 => I64, f32 => F32, f64 => F64, String => String);
+
+impl<T: Schema> Schema for Vec<T> {
+    fn ty() -> Ty {
+        Ty::Vec(Box::new(T::ty()))
+    }
+
+    fn define(defs: &mut Definitions) {
+        T::define(defs)
+    }
+}
+
+impl<T: Schema> Schema for HashMap<String, T> {
+    fn ty() -> Ty {
+        Ty::Map(Box::new(T::ty()))
+    }
+
+    fn define(defs: &mut Definitions) {
+        T::define(defs)
+    }
+}
+
+impl<T: Schema> Schema for Option<T> {
+    const OPTIONAL: bool = true;
+
+    fn ty() -> Ty {
+        T::ty()
+    }
+
+    fn define(defs: &mut Definitions) {
+        T::define(defs)
+    }
+}
+
+impl<T: Schema, const N: usize> Schema for [T; N] {
+    fn ty() -> Ty {
+        Ty::Array(Box::new(T::ty()), N)
+    }
+
+    fn define(defs: &mut Definitions) {
+        T::define(defs)
+    }
+}
+
+macro_rules! tuples {
+    ($(($($item:ident),+)),*) => {
+        $(impl<$($item: Schema),+> Schema for ($($item,)+) {
+            fn ty() -> Ty {
+                Ty::Tuple(vec![$($item::ty()),+])
+            }
+
+            fn define(defs: &mut Definitions) {
+                $($item::define(defs);)+
+            }
+        })*
+    };
+}
+
+tuples!((A, B), (A, B, C), (A, B, C, D));
+
+fn definitions() -> Definitions {
+    let mut defs = Definitions::default();
+    for root in Root::ALL {
+        root.define(&mut defs);
+    }
+    defs
+}
+
+fn parameters() -> Ty {
+    GodotFileParameters::ty()
+}
+
+impl Field {
+    fn key(&self, options: &SchemaOptions) -> &'static str {
+        if options.minname { self.minname } else { self.name }
+    }
+}
+
+/// The keys of `{"type": ..., "value": ...}`, shortened like the `tag` and `content` of
+/// `GodotValue` under `minname`.
+fn wrapper_keys(options: &SchemaOptions) -> (&'static str, &'static str) {
+    if options.minname { ("t", "v") } else { ("type", "value") }
+}
+
+impl Variant {
+    fn key(&self, options: &SchemaOptions) -> &'static str {
+        if options.minname { self.minname } else { self.name }
+    }
+
+    /// The type of the `value` of a tagged `serjsonnatural` object, `None` for the variants
+    /// written as plain JSON.
+    fn natural(&self) -> Option<Ty> {
+        match self.name {
+            "Null" | "String" | "Integer" | "Boolean" | "Array" => None,
+            // Only non-finite floats are tagged, as "inf", "-inf" or "NaN".
+            "Float" => Some(Ty::String),
+            "Dictionary" => Some(parameters()),
+            "Object" => Some(Ty::Tuple(vec![Ty::String, parameters()])),
+            _ => Some(self.fields[0].clone()),
+        }
+    }
+}
+
+/// Just enough JSON to write a schema.
+enum Json {
+    Str(String),
+    Num(i64),
+    Bool(bool),
+    Arr(Vec<Json>),
+    Obj(Vec<(String, Json)>),
+}
+
+fn obj<const N: usize>(entries: [(&str, Json); N]) -> Json {
+    Json::Obj(entries.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+}
+
+fn str(value: &str) -> Json {
+    Json::Str(value.to_string())
+}
+
+fn quote(value: &str) -> String {
+    let mut out = String::from('"');
+    for c in value.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+impl Json {
+    fn write(&self, out: &mut String, indent: usize) {
+        match self {
+            Json::Str(value) => out.push_str(&quote(value)),
+            Json::Num(value) => out.push_str(&value.to_string()),
+            Json::Bool(value) => out.push_str(if *value { "true" } else { "false" }),
+            Json::Arr(items) if items.is_empty() => out.push_str("[]"),
+            Json::Obj(entries) if entries.is_empty() => out.push_str("{}"),
+            Json::Arr(items) => {
+                out.push('[');
+                for (index, item) in items.iter().enumerate() {
+                    out.push_str(if index == 0 { "\n" } else { ",\n" });
+                    out.push_str(&"  ".repeat(indent + 1));
+                    item.write(out, indent + 1);
+                }
+                out.push('\n');
+                out.push_str(&"  ".repeat(indent));
+                out.push(']');
+            }
+            Json::Obj(entries) => {
+                out.push('{');
+                for (index, (key, value)) in entries.iter().enumerate() {
+                    out.push_str(if index == 0 { "\n" } else { ",\n" });
+                    out.push_str(&"  ".repeat(indent + 1));
+                    out.push_str(&quote(key));
+                    out.push_str(": ");
+                    value.write(out, indent + 1);
+                }
+                out.push('\n');
+                out.push_str(&"  ".repeat(indent));
+                out.push('}');
+            }
+        }
+    }
+}
+
+fn integer(minimum: Option<i64>, maximum: Option<i64>) -> Json {
+    let mut entries = vec![("type".to_string(), str("integer"))];
+    if let Some(minimum) = minimum {
+        entries.push(("minimum".to_string(), Json::Num(minimum)));
+    }
+    if let Some(maximum) = maximum {
+        entries.push(("maximum".to_string(), Json::Num(maximum)));
+    }
+    Json::Obj(entries)
+}
+
+fn tuple_schema(items: &[Ty]) -> Json {
+    obj([
+        ("type", str("array")),
+        ("prefixItems", Json::Arr(items.iter().map(ty_schema).collect())),
+        ("items", Json::Bool(false)),
+    ])
+}
+
+fn ty_schema(ty: &Ty) -> Json {
+    match ty {
+        Ty::Bool => obj([("type", str("boolean"))]),
+        Ty::U8 => integer(Some(0), Some(u8::MAX as i64)),
+        Ty::I32 => integer(Some(i32::MIN as i64), Some(i32::MAX as i64)),
+        Ty::U32 => integer(Some(0), Some(u32::MAX as i64)),
+        Ty::U64 => integer(Some(0), None),
+        Ty::I64 => integer(None, None),
+        Ty::F32 | Ty::F64 => obj([("type", str("number"))]),
+        Ty::String => obj([("type", str("string"))]),
+        Ty::Vec(item) => obj([("type", str("array")), ("items", ty_schema(item))]),
+        Ty::Map(value) => obj([("type", str("object")), ("additionalProperties", ty_schema(value))]),
+        Ty::Tuple(items) => tuple_schema(items),
+        Ty::Array(item, len) => obj([
+            ("type", str("array")),
+            ("items", ty_schema(item)),
+            ("minItems", Json::Num(*len as i64)),
+            ("maxItems", Json::Num(*len as i64)),
+        ]),
+        Ty::Ref(name) => obj([("$ref", Json::Str(format!("#/$defs/{}", name)))]),
+    }
+}
+
+fn closed_object(properties: Vec<(String, Json)>, required: Vec<Json>) -> Json {
+    obj([
+        ("type", str("object")),
+        ("properties", Json::Obj(properties)),
+        ("required", Json::Arr(required)),
+        ("additionalProperties", Json::Bool(false)),
+    ])
+}
+
+/// `GodotValue` under `serjsonnatural`. A plain object also matches the tagged ones, so this
+/// is an `anyOf`; the writer tags dictionaries whose first key is `type`.
+fn natural_schema(variants: &[Variant], options: &SchemaOptions) -> Json {
+    let (tag, content) = wrapper_keys(options);
+    let mut schemas = vec![
+        obj([("type", str("null"))]),
+        ty_schema(&Ty::String),
+        ty_schema(&Ty::I64),
+        ty_schema(&Ty::F64),
+        ty_schema(&Ty::Bool),
+        ty_schema(&Vec::<GodotValue>::ty()),
+        ty_schema(&parameters()),
+    ];
+    schemas.extend(variants.iter().filter_map(|variant| {
+        let ty = variant.natural()?;
+        Some(closed_object(
+            vec![
+                (tag.to_string(), obj([("const", str(variant.key(options)))])),
+                (content.to_string(), ty_schema(&ty)),
+            ],
+            vec![str(tag), str(content)],
+        ))
+    }));
+    obj([("anyOf", Json::Arr(schemas))])
+}
+
+fn def_schema(def: &Def, options: &SchemaOptions) -> Json {
+    match def {
+        Def::Value(variants) if options.natural => natural_schema(variants, options),
+        Def::Value(variants) => enum_schema(variants, true, options),
+        Def::Enum { variants, pretty } => enum_schema(variants, *pretty, options),
+        Def::Struct(fields) => closed_object(
+            fields.iter().map(|field| (field.key(options).to_string(), ty_schema(&field.ty))).collect(),
+            fields.iter().filter(|field| !field.optional).map(|field| str(field.key(options))).collect(),
+        ),
+    }
+}
+
+fn enum_schema(variants: &[Variant], pretty: bool, options: &SchemaOptions) -> Json {
+    Json::Obj(vec![(
+        "oneOf".to_string(),
+        Json::Arr(
+            variants
+                .iter()
+                .map(|variant| {
+                    let name = variant.key(options);
+                    if variant.fields.is_empty() {
+                        obj([("const", str(name))])
+                    } else if pretty && options.pretty {
+                        let (tag, content) = wrapper_keys(options);
+                        closed_object(
+                            vec![
+                                (tag.to_string(), obj([("const", str(name))])),
+                                (content.to_string(), tuple_schema(&variant.fields)),
+                            ],
+                            vec![str(tag), str(content)],
+                        )
+                    } else {
+                        closed_object(vec![(name.to_string(), tuple_schema(&variant.fields))], vec![str(name)])
+                    }
+                })
+                .collect(),
+        ),
+    )])
+}
+
+/// A JSON Schema (draft 2020-12) for `root`, or for any of the [`Root`]s if `None`.
+pub fn json_schema(root: Option<Root>, options: &SchemaOptions) -> String {
+    let mut entries = vec![("$schema".to_string(), str("https://json-schema.org/draft/2020-12/schema"))];
+    match root {
+        Some(root) => {
+            entries.push(("title".to_string(), str(root.name())));
+            entries.push(("$ref".to_string(), Json::Str(format!("#/$defs/{}", root.name()))));
+        }
+        None => entries.push((
+            "anyOf".to_string(),
+            Json::Arr(Root::ALL.iter().map(|root| ty_schema(&Ty::Ref(root.name()))).collect()),
+        )),
+    }
+    entries.push((
+        "$defs".to_string(),
+        Json::Obj(definitions().0.iter().map(|(name, def)| (name.to_string(), def_schema(def, options))).collect()),
+    ));
+
+    let mut out = String::new();
+    Json::Obj(entries).write(&mut out, 0);
+    out.push('\n');
+    out
+}
+
+fn ts_key(key: &str) -> String {
+    let mut chars = key.chars();
+    let identifier = chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_' || c == '$')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '$');
+    if identifier { key.to_string() } else { quote(key) }
+}
+
+fn ts_tuple(items: &[Ty]) -> String {
+    format!("[{}]", items.iter().map(ts_type).collect::<Vec<_>>().join(", "))
+}
+
+fn ts_type(ty: &Ty) -> String {
+    match ty {
+        Ty::Bool => "boolean".to_string(),
+        Ty::U8 | Ty::I32 | Ty::U32 | Ty::U64 | Ty::I64 | Ty::F32 | Ty::F64 => "number".to_string(),
+        Ty::String => "string".to_string(),
+        Ty::Vec(item) => format!("{}[]", ts_type(item)),
+        Ty::Map(value) => format!("Record<string, {}>", ts_type(value)),
+        Ty::Tuple(items) => ts_tuple(items),
+        Ty::Array(item, len) => format!("[{}]", vec![ts_type(item); *len].join(", ")),
+        Ty::Ref(name) => name.to_string(),
+    }
+}
+
+fn ts_variant(variant: &Variant, pretty: bool, options: &SchemaOptions) -> String {
+    let name = variant.key(options);
+    if variant.fields.is_empty() {
+        quote(name)
+    } else if pretty && options.pretty {
+        let (tag, content) = wrapper_keys(options);
+        format!("{{ {}: {}; {}: {} }}", tag, quote(name), content, ts_tuple(&variant.fields))
+    } else {
+        format!("{{ {}: {} }}", ts_key(name), ts_tuple(&variant.fields))
+    }
+}
+
+fn ts_union(out: &mut String, name: &str, members: Vec<String>) {
+    out.push_str(&format!("export type {} =\n", name));
+    for member in members {
+        out.push_str(&format!("  | {}\n", member));
+    }
+    out.pop();
+    out.push_str(";\n");
+}
+
+/// TypeScript declarations (`.d.ts`) of all the models.
+pub fn typescript(options: &SchemaOptions) -> String {
+    let mut out = format!(
+        "// JSON written by godot_data with minname: {}, serjsonpretty: {}, serjsonnatural: {}.\n",
+        options.minname, options.pretty, options.natural
+    );
+    for (name, def) in &definitions().0 {
+        out.push('\n');
+        match def {
+            Def::Struct(fields) => {
+                out.push_str(&format!("export interface {} {{\n", name));
+                for field in fields.iter() {
+                    let optional = if field.optional { "?" } else { "" };
+                    out.push_str(&format!("  {}{}: {};\n", ts_key(field.key(options)), optional, ts_type(&field.ty)));
+                }
+                out.push_str("}\n");
+            }
+            Def::Enum { variants, pretty } => {
+                ts_union(&mut out, name, variants.iter().map(|variant| ts_variant(variant, *pretty, options)).collect())
+            }
+            Def::Value(variants) if options.natural => {
+                let (tag, content) = wrapper_keys(options);
+                let mut members: Vec<String> = ["null", "string", "number", "boolean", "GodotValue[]", "{ [key: string]: GodotValue }"]
+                    .map(str::to_string)
+                    .to_vec();
+                members.extend(variants.iter().filter_map(|variant| {
+                    let ty = variant.natural()?;
+                    Some(format!("{{ {}: {}; {}: {} }}", tag, quote(variant.key(options)), content, ts_type(&ty)))
+                }));
+                ts_union(&mut out, name, members)
+            }
+            Def::Value(variants) => {
+                ts_union(&mut out, name, variants.iter().map(|variant| ts_variant(variant, true, options)).collect())
+            }
+        }
+    }
+    out
+}
+
+#[cfg(all(test, any(feature = "serjson", feature = "serjsonpretty", feature = "serjsonnatural")))]
+mod tests {
+    use super::*;
+    use crate::export_presets::ExportPreset;
+    use crate::pck_file::PckEntry;
+    use crate::scene_records::scene_records;
+    use crate::tscn_file::{Tag, TagType};
+    use nanoserde::SerJson;
+    use serde_json::Value;
+
+    /// Validates the subset of JSON Schema that [`json_schema`] writes.
+    fn validate(schema: &Value, value: &Value, root: &Value) -> bool {
+        let schema = schema.as_object().unwrap();
+        if let Some(reference) = schema.get("$ref") {
+            let name = reference.as_str().unwrap().trim_start_matches("#/$defs/");
+            return validate(&root["$defs"][name], value, root);
+        }
+        if let Some(constant) = schema.get("const") {
+            return constant == value;
+        }
+        if let Some(variants) = schema.get("oneOf") {
+            return variants.as_array().unwrap().iter().filter(|variant| validate(variant, value, root)).count() == 1;
+        }
+        if let Some(variants) = schema.get("anyOf") {
+            return variants.as_array().unwrap().iter().any(|variant| validate(variant, value, root));
+        }
+        match schema["type"].as_str().unwrap() {
+            "null" => value.is_null(),
+            "boolean" => value.is_boolean(),
+            "number" => value.is_number(),
+            "string" => value.is_string(),
+            "integer" => {
+                let Some(number) = value.as_i64().map(i128::from).or(value.as_u64().map(i128::from)) else {
+                    return false;
+                };
+                schema.get("minimum").is_none_or(|minimum| number >= minimum.as_i64().unwrap() as i128)
+                    && schema.get("maximum").is_none_or(|maximum| number <= maximum.as_i64().unwrap() as i128)
+            }
+            "array" => {
+                let Some(items) = value.as_array() else { return false };
+                let prefix = schema.get("prefixItems").map_or(&[][..], |prefix| prefix.as_array().unwrap());
+                if let Some(len) = schema.get("minItems") {
+                    if items.len() != len.as_u64().unwrap() as usize {
+                        return false;
+                    }
+                }
+                items.iter().enumerate().all(|(index, item)| match prefix.get(index) {
+                    Some(item_schema) => validate(item_schema, item, root),
+                    None => match &schema["items"] {
+                        Value::Bool(allowed) => *allowed,
+                        item_schema => validate(item_schema, item, root),
+                    },
+                }) && items.len() >= prefix.len()
+            }
+            "object" => {
+                let Some(entries) = value.as_object() else { return false };
+                let properties = schema.get("properties").and_then(Value::as_object);
+                let required = schema.get("required").map_or(&[][..], |required| required.as_array().unwrap());
+                required.iter().all(|key| entries.contains_key(key.as_str().unwrap()))
+                    && entries.iter().all(|(key, entry)| match properties.and_then(|properties| properties.get(key)) {
+                        Some(property) => validate(property, entry, root),
+                        None => match &schema["additionalProperties"] {
+                            Value::Bool(allowed) => *allowed,
+                            additional => validate(additional, entry, root),
+                        },
+                    })
+            }
+            other => panic!("unexpected type {}", other),
+        }
+    }
+
+    fn options() -> SchemaOptions {
+        SchemaOptions {
+            minname: cfg!(feature = "minname"),
+            pretty: cfg!(feature = "serjsonpretty"),
+            natural: cfg!(feature = "serjsonnatural"),
+        }
+    }
+
+    fn assert_valid(root: Root, json: String) {
+        let schema: Value = serde_json::from_str(&json_schema(Some(root), &options())).unwrap();
+        let value: Value = serde_json::from_str(&json).unwrap();
+        assert!(validate(&schema, &value, &schema), "{} does not match the {} schema", json, root.name());
+    }
+
+    #[test]
+    fn test_schema_matches_serialization() {
+        let mut props = HashMap::from([
+            ("null".to_string(), GodotValue::Null),
+            ("name".to_string(), GodotValue::String("a".to_string())),
+            ("position".to_string(), GodotValue::Vector2((1.0, 2.0))),
+            ("transform".to_string(), GodotValue::Transform2D([1.0, 0.0, 0.0, 1.0, 0.0, 0.0])),
+            ("bytes".to_string(), GodotValue::PackedByteArray(vec![0, 255])),
+            ("points".to_string(), GodotValue::PackedVector2Array(vec![(0.0, 1.0)])),
+            ("list".to_string(), GodotValue::Array(vec![GodotValue::Integer(-3), GodotValue::Boolean(true)])),
+            (
+                "event".to_string(),
+                GodotValue::Object(("InputEventKey".to_string(), vec![("keycode".to_string(), GodotValue::Integer(4194320))])),
+            ),
+            ("dict".to_string(), GodotValue::Dictionary(vec![("k".to_string(), GodotValue::Float(0.5))])),
+            (
+                "tagged_dict".to_string(),
+                GodotValue::Dictionary(vec![("type".to_string(), GodotValue::String("Vector2".to_string()))]),
+            ),
+            ("action".to_string(), GodotValue::StringName("jump".to_string())),
+            ("target".to_string(), GodotValue::NodePath("../Player".to_string())),
+            ("tint".to_string(), GodotValue::Color((1.0, 0.5, 0.0, 1.0))),
+            ("texture".to_string(), GodotValue::ExtResourceLink("1_a".to_string())),
+        ]);
+        // Only the natural mapping tags infinite floats, nanoserde writes a bare `inf`.
+        if cfg!(feature = "serjsonnatural") {
+            props.insert("limit".to_string(), GodotValue::Float(f64::INFINITY));
+        }
+        let tag = |_type| Tag { _type, attrs: HashMap::new(), props: HashMap::new() };
+        let scene = TSCNFile {
+            header: tag(TagType::GdScene),
+            ext_resources: HashMap::from([("1".to_string(), tag(TagType::ExtResource))]),
+            sub_resources: HashMap::new(),
+            nodes: vec![Tag { _type: TagType::Node, attrs: HashMap::new(), props }],
+            connections: vec![tag(TagType::Connection)],
+        };
+        assert_valid(Root::TSCNFile, scene.serialize_json());
+        for record in scene_records("res://main.tscn", &scene) {
+            assert_valid(Root::SceneRecord, record.serialize_json());
+        }
+
+        let project = ProjectFile {
+            config_version: 5,
+            sections: HashMap::from([(
+                "application".to_string(),
+                HashMap::from([("config/name".to_string(), GodotValue::String("Demo".to_string()))]),
+            )]),
+        };
+        assert_valid(Root::ProjectFile, project.serialize_json());
+
+        let pck = PckFile {
+            format_version: 2,
+            engine_version: (4, 3, 0),
+            flags: 0,
+            entries: vec![PckEntry {
+                path: "res://a.tscn".to_string(),
+                offset: 96,
+                size: 10,
+                md5: "00".repeat(16),
+                encrypted: false,
+                removal: false,
+            }],
+        };
+        assert_valid(Root::PckFile, pck.serialize_json());
+
+        let import = ImportFile {
+            importer: "texture".to_string(),
+            _type: Some("CompressedTexture2D".to_string()),
+            uid: Some("uid://cvn4ybpyjxbwo".to_string()),
+            path: Some("res://.godot/imported/icon.svg-218a8f2b3041327d8a5756f3a245f83b.ctex".to_string()),
+            platform_paths: HashMap::from([("s3tc".to_string(), "res://.godot/imported/icon.s3tc.ctex".to_string())]),
+            source_file: Some("res://icon.svg".to_string()),
+            dest_files: vec!["res://.godot/imported/icon.s3tc.ctex".to_string()],
+            params: HashMap::from([("compress/mode".to_string(), GodotValue::Integer(0))]),
+        };
+        assert_valid(Root::ImportFile, import.serialize_json());
+
+        let config = ConfigFile {
+            sections: HashMap::from([(
+                "player".to_string(),
+                HashMap::from([("spawn".to_string(), GodotValue::Vector3((0.0, 1.0, 0.0)))]),
+            )]),
+        };
+        assert_valid(Root::ConfigFile, config.serialize_json());
+
+        let presets = ExportPresets {
+            presets: vec![ExportPreset {
+                index: 0,
+                name: "Web".to_string(),
+                platform: "Web".to_string(),
+                runnable: true,
+                dedicated_server: false,
+                custom_features: vec!["demo".to_string()],
+                export_filter: "all_resources".to_string(),
+                include_filter: "*.json".to_string(),
+                exclude_filter: String::new(),
+                export_path: "build/index.html".to_string(),
+                settings: HashMap::from([("encrypt_pck".to_string(), GodotValue::Boolean(false))]),
+                options: HashMap::from([("html/canvas_resize_policy".to_string(), GodotValue::Integer(2))]),
+            }],
+        };
+        assert_valid(Root::ExportPresets, presets.serialize_json());
+
+        let extension = GDExtension {
+            entry_symbol: "gdext_rust_init".to_string(),
+            compatibility_minimum: Some("4.1".to_string()),
+            compatibility_maximum: Some("4.3".to_string()),
+            reloadable: true,
+            libraries: HashMap::from([("linux.x86_64".to_string(), "res://bin/libgame.so".to_string())]),
+            icons: HashMap::from([("Player".to_string(), "res://icons/player.svg".to_string())]),
+            dependencies: HashMap::from([(
+                "linux.x86_64".to_string(),
+                vec![("res://bin/libdep.so".to_string(), String::new())],
+            )]),
+        };
+        assert_valid(Root::GDExtension, extension.serialize_json());
+    }
+
+    #[test]
+    fn test_schema_rejects_other_shapes() {
+        let schema: Value = serde_json::from_str(&json_schema(Some(Root::ProjectFile), &options())).unwrap();
+        let other = SchemaOptions { minname: !options().minname, ..options() };
+        let other_schema: Value = serde_json::from_str(&json_schema(Some(Root::ProjectFile), &other)).unwrap();
+        let project = ProjectFile { config_version: 5, sections: HashMap::new() };
+        let value: Value = serde_json::from_str(&project.serialize_json()).unwrap();
+        assert!(validate(&schema, &value, &schema));
+        assert!(!validate(&other_schema, &value, &other_schema));
+    }
+
+    #[test]
+    fn test_typescript() {
+        let ts = typescript(&SchemaOptions { minname: false, pretty: false, natural: false });
+        assert!(ts.contains("export interface ProjectFile {\n  config_version: number;\n  sections: Record<string, Record<string, GodotValue>>;\n}\n"));
+        assert!(ts.contains("  | \"Null\"\n  | { String: [string] }\n"));
+        assert!(ts.contains("  _type?: string;\n"));
+        let ts = typescript(&SchemaOptions { minname: true, pretty: true, natural: false });
+        assert!(ts.contains("  | { t: \"V2\"; v: [[number, number]] }\n"));
+        assert!(ts.contains("export type TagType =\n  | \"GS\"\n"));
+        let ts = typescript(&SchemaOptions { minname: false, pretty: true, natural: false });
+        assert!(ts.contains("  | { type: \"Vector2\"; value: [[number, number]] }\n"));
+        let ts = typescript(&SchemaOptions { minname: false, pretty: false, natural: true });
+        assert!(ts.contains("export type GodotValue =\n  | null\n  | string\n  | number\n  | boolean\n"));
+        assert!(ts.contains("  | { type: \"Vector2\"; value: [number, number] }\n"));
+        assert!(ts.contains("  | { type: \"Dictionary\"; value: Record<string, GodotValue> }\n"));
+        assert!(!ts.contains("type: \"String\""));
+    }
+}
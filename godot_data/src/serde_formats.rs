@@ -0,0 +1,161 @@
+//! Encodings built on the `serde` derives.
+
+#[cfg(feature = "toml")]
+use crate::project_file::ProjectFile;
+#[cfg(feature = "toml")]
+use crate::values::GodotValue;
+#[cfg(feature = "toml")]
+use serde::Deserialize;
+#[cfg(feature = "toml")]
+use std::collections::HashMap;
+#[cfg(any(feature = "msgpack", feature = "cbor", feature = "yaml"))]
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Encodes structs as maps, so the keys follow the `minname` scheme like the JSON output.
+#[cfg(feature = "msgpack")]
+pub fn to_msgpack<T: Serialize>(value: &T) -> Result<Vec<u8>, rmp_serde::encode::Error> {
+    rmp_serde::to_vec_named(value)
+}
+
+#[cfg(feature = "msgpack")]
+pub fn from_msgpack<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, rmp_serde::decode::Error> {
+    rmp_serde::from_slice(bytes)
+}
+
+#[cfg(feature = "cbor")]
+pub fn to_cbor<T: Serialize>(value: &T) -> Result<Vec<u8>, ciborium::ser::Error<std::io::Error>> {
+    let mut out = Vec::new();
+    ciborium::into_writer(value, &mut out)?;
+    Ok(out)
+}
+
+#[cfg(feature = "cbor")]
+pub fn from_cbor<T: DeserializeOwned>(bytes: &[u8]) -> Result<T, ciborium::de::Error<std::io::Error>> {
+    ciborium::from_reader(bytes)
+}
+
+/// `GodotValue` variants are written as YAML tags, e.g. `!Vector2 [1, 2]`.
+#[cfg(feature = "yaml")]
+pub fn to_yaml<T: Serialize>(value: &T) -> Result<String, serde_norway::Error> {
+    serde_norway::to_string(value)
+}
+
+#[cfg(feature = "yaml")]
+pub fn from_yaml<T: DeserializeOwned>(text: &str) -> Result<T, serde_norway::Error> {
+    serde_norway::from_str(text)
+}
+
+/// Only project settings have a TOML mapping. Sections become tables next to `config_version`,
+/// with every value an inline table such as `"config/name" = { String = "Demo" }`.
+#[cfg(feature = "toml")]
+pub fn to_toml(file: &ProjectFile) -> Result<String, toml::ser::Error> {
+    let mut out = format!("config_version = {}\n", file.config_version);
+    let mut sections: Vec<_> = file.sections.iter().collect();
+    sections.sort_by_key(|(name, _)| *name);
+    for (name, parameters) in sections {
+        out.push_str(&format!("\n[{}]\n", toml_key(name)));
+        let mut parameters: Vec<_> = parameters.iter().collect();
+        parameters.sort_by_key(|(key, _)| *key);
+        for (key, value) in parameters {
+            out.push_str(&format!("{} = {}\n", toml_key(key), toml::Value::try_from(value)?));
+        }
+    }
+    Ok(out)
+}
+
+#[cfg(feature = "toml")]
+fn toml_key(key: &str) -> String {
+    if !key.is_empty() && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '-') {
+        key.to_string()
+    } else {
+        toml::Value::String(key.to_string()).to_string()
+    }
+}
+
+#[cfg(feature = "toml")]
+pub fn from_toml(text: &str) -> Result<ProjectFile, toml::de::Error> {
+    use serde::de::Error;
+
+    let mut table: toml::Table = toml::from_str(text)?;
+    let config_version = match table.remove("config_version") {
+        Some(toml::Value::Integer(version)) => u32::try_from(version).map_err(toml::de::Error::custom)?,
+        _ => return Err(toml::de::Error::custom("missing `config_version`")),
+    };
+    let mut sections = HashMap::new();
+    for (name, section) in table {
+        let toml::Value::Table(section) = section else {
+            return Err(toml::de::Error::custom(format!("`{}` is not a section", name)));
+        };
+        let mut parameters = HashMap::new();
+        for (key, value) in section {
+            parameters.insert(key, GodotValue::deserialize(value)?);
+        }
+        sections.insert(name, parameters);
+    }
+    Ok(ProjectFile { config_version, sections })
+}
+
+#[cfg(all(test, feature = "msgpack", feature = "cbor", feature = "yaml", feature = "toml"))]
+mod tests {
+    use super::*;
+    use crate::project_file::ProjectFile;
+    use crate::values::GodotValue;
+    use std::collections::HashMap;
+
+    fn project() -> ProjectFile {
+        let mut application = HashMap::new();
+        application.insert("config/name".to_string(), GodotValue::String("Demo".to_string()));
+        application.insert("config/features".to_string(), GodotValue::PackedStringArray(vec!["4.3".to_string()]));
+        let mut sections = HashMap::new();
+        sections.insert("application".to_string(), application);
+        ProjectFile { config_version: 5, sections }
+    }
+
+    fn assert_same(decoded: ProjectFile) {
+        let expected = project();
+        assert_eq!(decoded.config_version, expected.config_version);
+        assert_eq!(decoded.sections, expected.sections);
+    }
+
+    #[test]
+    fn test_msgpack_round_trip() {
+        let bytes = to_msgpack(&project()).unwrap();
+        // A map with named keys rather than rmp-serde's default positional array.
+        assert_eq!(bytes[0], 0x82);
+        assert_same(from_msgpack(&bytes).unwrap());
+    }
+
+    #[test]
+    fn test_cbor_round_trip() {
+        let bytes = to_cbor(&project()).unwrap();
+        assert_eq!(bytes[0], 0xA2);
+        assert_same(from_cbor(&bytes).unwrap());
+    }
+
+    #[test]
+    fn test_yaml_round_trip() {
+        #[cfg(not(feature = "minname"))]
+        let (strings, vector, dictionary, integer) = ("PackedStringArray", "Vector2", "Dictionary", "Integer");
+        #[cfg(feature = "minname")]
+        let (strings, vector, dictionary, integer) = ("PSA", "V2", "D", "I");
+
+        let text = to_yaml(&project()).unwrap();
+        assert!(text.contains(&format!("!{strings}")));
+        assert_same(from_yaml(&text).unwrap());
+
+        let value: GodotValue = from_yaml(&format!("!{vector} [1, 2.5]")).unwrap();
+        assert_eq!(value, GodotValue::Vector2((1.0, 2.5)));
+        let value: GodotValue = from_yaml(&format!("!{dictionary} [[a, !{integer} 1], [b, Null]]")).unwrap();
+        assert_eq!(
+            value,
+            GodotValue::Dictionary(vec![("a".to_string(), GodotValue::Integer(1)), ("b".to_string(), GodotValue::Null)])
+        );
+    }
+
+    #[test]
+    fn test_toml_round_trip() {
+        let text = to_toml(&project()).unwrap();
+        assert!(text.starts_with("config_version = 5\n\n[application]\n"));
+        assert_same(from_toml(&text).unwrap());
+    }
+}
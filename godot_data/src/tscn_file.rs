@@ -5,33 +5,50 @@ use std::str::FromStr;
 
 #[cfg(feature = "bincode")]
 use bincode::{Decode, Encode};
-#[cfg(any(feature = "serjson", feature = "serjsonpretty", feature = "dejson"))]
-use nanoserde::{SerJson, DeJson};
-#[cfg(any(feature = "serron", feature = "deron"))]
-use nanoserde::{SerRon, DeRon};
+#[cfg(any(feature = "serjson", feature = "serjsonpretty", feature = "serjsonnatural"))]
+use nanoserde::SerJson;
+#[cfg(feature = "dejson")]
+use nanoserde::DeJson;
+#[cfg(any(feature = "serron", feature = "serronpretty"))]
+use nanoserde::SerRon;
+#[cfg(feature = "deron")]
+use nanoserde::DeRon;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
-#[derive(Eq, PartialEq)]
+#[godot_macros::schema]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
 #[cfg_attr(feature = "serjson", derive(SerJson))]
 #[cfg_attr(feature = "serjsonpretty", derive(SerJson))]
+#[cfg_attr(feature = "serjsonnatural", derive(SerJson))]
 #[cfg_attr(feature = "dejson", derive(DeJson))]
 #[cfg_attr(feature = "serbin", derive(Encode))]
 #[cfg_attr(feature = "debin", derive(Decode))]
 #[cfg_attr(feature = "serron", derive(SerRon))]
+#[cfg_attr(feature = "serronpretty", derive(SerRon))]
 #[cfg_attr(feature = "deron", derive(DeRon))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum TagType {
-    #[cfg_attr(feature = "minname", nserde(rename = "GS"))]
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "GS"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "GS"))]
     GdScene,
-    #[cfg_attr(feature = "minname", nserde(rename = "GR"))]
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "GR"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "GR"))]
     GdResource,
-    #[cfg_attr(feature = "minname", nserde(rename = "ER"))]
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "ER"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "ER"))]
     ExtResource,
-    #[cfg_attr(feature = "minname", nserde(rename = "SR"))]
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "SR"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "SR"))]
     SubResource,
-    #[cfg_attr(feature = "minname", nserde(rename = "N"))]
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "N"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "N"))]
     Node,
-    #[cfg_attr(feature = "minname", nserde(rename = "R"))]
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "R"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "R"))]
     Resource,
-    #[cfg_attr(feature = "minname", nserde(rename = "C"))]
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "C"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "C"))]
     Connection,
 }
 
@@ -52,38 +69,54 @@ impl FromStr for TagType {
 }
 
 
+#[godot_macros::schema]
 #[cfg_attr(feature = "serjson", derive(SerJson))]
 #[cfg_attr(feature = "serjsonpretty", derive(SerJson))]
+#[cfg_attr(feature = "serjsonnatural", derive(SerJson))]
 #[cfg_attr(feature = "dejson", derive(DeJson))]
 #[cfg_attr(feature = "serbin", derive(Encode))]
 #[cfg_attr(feature = "debin", derive(Decode))]
 #[cfg_attr(feature = "serron", derive(SerRon))]
+#[cfg_attr(feature = "serronpretty", derive(SerRon))]
 #[cfg_attr(feature = "deron", derive(DeRon))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct Tag {
-    #[cfg_attr(feature = "minname", nserde(rename = "t"))]
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "t"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "t"))]
     pub _type: TagType,
-    #[cfg_attr(feature = "minname", nserde(rename = "a"))]
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "a"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "a"))]
     pub attrs: HashMap<String, GodotValue>,
-    #[cfg_attr(feature = "minname", nserde(rename = "p"))]
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "p"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "p"))]
     pub props: HashMap<String, GodotValue>,
 }
 
+#[godot_macros::schema]
 #[cfg_attr(feature = "serjson", derive(SerJson))]
 #[cfg_attr(feature = "serjsonpretty", derive(SerJson))]
+#[cfg_attr(feature = "serjsonnatural", derive(SerJson))]
 #[cfg_attr(feature = "dejson", derive(DeJson))]
 #[cfg_attr(feature = "serbin", derive(Encode))]
 #[cfg_attr(feature = "debin", derive(Decode))]
 #[cfg_attr(feature = "serron", derive(SerRon))]
+#[cfg_attr(feature = "serronpretty", derive(SerRon))]
 #[cfg_attr(feature = "deron", derive(DeRon))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub struct TSCNFile {
-    #[cfg_attr(feature = "minname", nserde(rename = "h"))]
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "h"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "h"))]
     pub header: Tag,
-    #[cfg_attr(feature = "minname", nserde(rename = "er"))]
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "er"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "er"))]
     pub ext_resources: HashMap<String, Tag>,
-    #[cfg_attr(feature = "minname", nserde(rename = "sr"))]
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "sr"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "sr"))]
     pub sub_resources: HashMap<String, Tag>,
-    #[cfg_attr(feature = "minname", nserde(rename = "n"))]
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "n"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "n"))]
     pub nodes: Vec<Tag>,
-    #[cfg_attr(feature = "minname", nserde(rename = "c"))]
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "c"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "c"))]
     pub connections: Vec<Tag>,
 }
@@ -1,47 +1,446 @@
+#[cfg(feature = "serjsonpretty")]
 use godot_macros::SerJsonEnumPretty;
+#[cfg(all(feature = "dejson", feature = "serjsonpretty"))]
+use godot_macros::DeJsonEnumPretty;
+#[cfg(all(feature = "serjsonnatural", feature = "minname"))]
+use godot_macros::NanoserdeAttributes;
+#[cfg(feature = "serronpretty")]
+use godot_macros::SerRonEnumPretty;
+#[cfg(all(feature = "deron", feature = "serronpretty"))]
+use godot_macros::DeRonEnumPretty;
 
 #[cfg(feature = "bincode")]
 use bincode::{Decode, Encode};
-#[cfg(any(feature = "serjson", feature = "serjsonpretty", feature = "dejson"))]
-use nanoserde::{SerJson, DeJson};
-#[cfg(any(feature = "serron", feature = "deron"))]
-use nanoserde::{SerRon, DeRon};
+#[cfg(feature = "serjson")]
+use nanoserde::SerJson;
+#[cfg(all(feature = "dejson", not(feature = "serjsonpretty"), not(feature = "serjsonnatural")))]
+use nanoserde::DeJson;
+#[cfg(feature = "serron")]
+use nanoserde::SerRon;
+#[cfg(all(feature = "deron", not(feature = "serronpretty")))]
+use nanoserde::DeRon;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
 
+#[godot_macros::schema(pretty, natural)]
+#[derive(Clone, Debug, PartialEq)]
 #[cfg_attr(feature = "serjson", derive(SerJson))]
 #[cfg_attr(feature = "serjsonpretty", derive(SerJsonEnumPretty))]
-#[cfg_attr(feature = "dejson", derive(DeJson))]
+#[cfg_attr(
+    all(feature = "dejson", not(feature = "serjsonpretty"), not(feature = "serjsonnatural")),
+    derive(DeJson)
+)]
+#[cfg_attr(all(feature = "dejson", feature = "serjsonpretty"), derive(DeJsonEnumPretty))]
+#[cfg_attr(all(feature = "serjsonnatural", feature = "minname"), derive(NanoserdeAttributes))]
 #[cfg_attr(feature = "serbin", derive(Encode))]
 #[cfg_attr(feature = "debin", derive(Decode))]
 #[cfg_attr(feature = "serron", derive(SerRon))]
-#[cfg_attr(feature = "deron", derive(DeRon))]
+#[cfg_attr(feature = "serronpretty", derive(SerRonEnumPretty))]
+#[cfg_attr(all(feature = "deron", not(feature = "serronpretty")), derive(DeRon))]
+#[cfg_attr(all(feature = "deron", feature = "serronpretty"), derive(DeRonEnumPretty))]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(tag = "t", content = "v"))]
 pub enum GodotValue {
     Null,
-    #[cfg_attr(feature = "minname", nserde(rename = "S"))]
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "S"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "S"))]
     String(String),
-    #[cfg_attr(feature = "minname", nserde(rename = "SN"))]
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "SN"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "SN"))]
     StringName(String),
-    #[cfg_attr(feature = "minname", nserde(rename = "I"))]
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "I"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "I"))]
     Integer(i64),
-    #[cfg_attr(feature = "minname", nserde(rename = "F"))]
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "F"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "F"))]
     Float(f64),
-    #[cfg_attr(feature = "minname", nserde(rename = "B"))]
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "B"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "B"))]
     Boolean(bool),
-    #[cfg_attr(feature = "minname", nserde(rename = "PSA"))]
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "PSA"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "PSA"))]
     PackedStringArray(Vec<String>),
-    #[cfg_attr(feature = "minname", nserde(rename = "NP"))]
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "NP"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "NP"))]
     NodePath(String),
-    #[cfg_attr(feature = "minname", nserde(rename = "V2"))]
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "V2"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "V2"))]
     Vector2((f64, f64)),
-    #[cfg_attr(feature = "minname", nserde(rename = "R2"))]
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "R2"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "R2"))]
     Rect2((f64, f64, f64, f64)),
-    #[cfg_attr(feature = "minname", nserde(rename = "C"))]
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "C"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "C"))]
     Color((f64, f64, f64, f64)),
-    #[cfg_attr(feature = "minname", nserde(rename = "ERL"))]
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "ERL"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "ERL"))]
     ExtResourceLink(String),
-    #[cfg_attr(feature = "minname", nserde(rename = "SRL"))]
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "SRL"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "SRL"))]
     SubResourceLink(String),
-    #[cfg_attr(feature = "minname", nserde(rename = "A"))]
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "A"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "A"))]
     Array(Vec<GodotValue>),
-    #[cfg_attr(feature = "minname", nserde(rename = "D"))]
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "D"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "D"))]
     Dictionary(Vec<(String, GodotValue)>),
-}
\ No newline at end of file
+    /// `Object(Class,"property":value,...)`, e.g. input events in `project.godot`.
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "O"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "O"))]
+    Object((String, Vec<(String, GodotValue)>)),
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "V2I"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "V2I"))]
+    Vector2i((i32, i32)),
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "R2I"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "R2I"))]
+    Rect2i((i32, i32, i32, i32)),
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "V3"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "V3"))]
+    Vector3((f64, f64, f64)),
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "V3I"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "V3I"))]
+    Vector3i((i32, i32, i32)),
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "V4"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "V4"))]
+    Vector4((f64, f64, f64, f64)),
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "V4I"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "V4I"))]
+    Vector4i((i32, i32, i32, i32)),
+    /// `x`, `y` and `origin` columns.
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "T2"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "T2"))]
+    Transform2D([f64; 6]),
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "PL"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "PL"))]
+    Plane((f64, f64, f64, f64)),
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "Q"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "Q"))]
+    Quaternion((f64, f64, f64, f64)),
+    /// Position followed by size.
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "AB"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "AB"))]
+    AABB([f64; 6]),
+    /// Rows in order.
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "BS"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "BS"))]
+    Basis([f64; 9]),
+    /// Basis rows followed by the origin.
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "T3"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "T3"))]
+    Transform3D([f64; 12]),
+    /// Columns in order.
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "PJ"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "PJ"))]
+    Projection([f64; 16]),
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "PBA"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "PBA"))]
+    PackedByteArray(Vec<u8>),
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "PI32"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "PI32"))]
+    PackedInt32Array(Vec<i32>),
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "PI64"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "PI64"))]
+    PackedInt64Array(Vec<i64>),
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "PF32"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "PF32"))]
+    PackedFloat32Array(Vec<f32>),
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "PF64"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "PF64"))]
+    PackedFloat64Array(Vec<f64>),
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "PV2"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "PV2"))]
+    PackedVector2Array(Vec<(f64, f64)>),
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "PV3"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "PV3"))]
+    PackedVector3Array(Vec<(f64, f64, f64)>),
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "PC"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "PC"))]
+    PackedColorArray(Vec<(f64, f64, f64, f64)>),
+    #[cfg_attr(all(feature = "nanoserde", feature = "minname"), nserde(rename = "PV4"))]
+    #[cfg_attr(all(feature = "serde", feature = "minname"), serde(rename = "PV4"))]
+    PackedVector4Array(Vec<(f64, f64, f64, f64)>),
+}
+#[cfg(all(
+    test,
+    any(
+        feature = "serde",
+        all(feature = "serjsonpretty", feature = "dejson"),
+        all(feature = "serronpretty", feature = "deron")
+    )
+))]
+mod tests {
+    use super::*;
+    #[cfg(all(feature = "serjsonpretty", feature = "dejson"))]
+    use nanoserde::{DeJson, SerJson};
+    #[cfg(all(feature = "serronpretty", feature = "deron"))]
+    use nanoserde::{DeRon, SerRon};
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let value = GodotValue::Dictionary(vec![
+            ("position".to_string(), GodotValue::Vector2((1.0, 2.5))),
+            ("items".to_string(), GodotValue::Array(vec![GodotValue::Null, GodotValue::Integer(3)])),
+        ]);
+        let json = serde_json::to_string(&value).unwrap();
+        #[cfg(not(feature = "minname"))]
+        assert_eq!(json, r#"{"Dictionary":[["position",{"Vector2":[1.0,2.5]}],["items",{"Array":["Null",{"Integer":3}]}]]}"#);
+        #[cfg(feature = "minname")]
+        assert_eq!(json, r#"{"D":[["position",{"V2":[1.0,2.5]}],["items",{"A":["Null",{"I":3}]}]]}"#);
+        assert_eq!(serde_json::from_str::<GodotValue>(&json).unwrap(), value);
+    }
+
+    #[cfg(all(feature = "serjsonpretty", feature = "dejson"))]
+    #[test]
+    fn test_pretty_json_round_trip() {
+        let value = GodotValue::Dictionary(vec![
+            ("position".to_string(), GodotValue::Vector2((1.0, 2.5))),
+            ("name".to_string(), GodotValue::String("a \\\"b\\\"".to_string())),
+            ("items".to_string(), GodotValue::Array(vec![GodotValue::Null, GodotValue::Integer(-3)])),
+            ("event".to_string(), GodotValue::Object(("InputEventKey".to_string(), vec![]))),
+        ]);
+        let json = value.serialize_json();
+        #[cfg(not(feature = "minname"))]
+        assert!(json.starts_with(r#"{"type":"Dictionary","value":[[["position",{"type":"Vector2","value":[[1.0,2.5]]}]"#));
+        #[cfg(feature = "minname")]
+        assert!(json.starts_with(r#"{"t":"D","v":[[["position",{"t":"V2","v":[[1.0,2.5]]}]"#), "{}", json);
+        assert_eq!(GodotValue::deserialize_json(&json).unwrap(), value);
+        #[cfg(not(feature = "minname"))]
+        assert_eq!(GodotValue::deserialize_json(r#"{"value":[1],"type":"Integer"}"#).unwrap(), GodotValue::Integer(1));
+        #[cfg(feature = "minname")]
+        assert_eq!(GodotValue::deserialize_json(r#"{"v":[1],"t":"I"}"#).unwrap(), GodotValue::Integer(1));
+    }
+
+    /// Struct and unit variants, which `GodotValue` doesn't have.
+    #[cfg(all(feature = "serjsonpretty", feature = "dejson"))]
+    #[test]
+    fn test_pretty_json_variants() {
+        #[derive(Debug, PartialEq, SerJsonEnumPretty, DeJsonEnumPretty)]
+        enum Shape {
+            Empty,
+            Circle { radius: f64, #[nserde(rename = "c")] center: (f64, f64), label: Option<String> },
+            Pair(i32, String),
+        }
+
+        for shape in [
+            Shape::Empty,
+            Shape::Circle { radius: 2.0, center: (0.5, 1.0), label: None },
+            Shape::Circle { radius: 1.0, center: (0.0, 0.0), label: Some("unit".to_string()) },
+            Shape::Pair(4, "four".to_string()),
+        ] {
+            let json = shape.serialize_json();
+            assert_eq!(Shape::deserialize_json(&json).unwrap(), shape, "{}", json);
+        }
+        assert_eq!(
+            Shape::deserialize_json(r#"{"type":"Circle","value":{"c":[1,2],"extra":[0],"radius":3}}"#).unwrap(),
+            Shape::Circle { radius: 3.0, center: (1.0, 2.0), label: None }
+        );
+        assert!(Shape::deserialize_json(r#"{"type":"Square","value":[]}"#).is_err());
+    }
+
+    #[cfg(all(feature = "serronpretty", feature = "deron"))]
+    #[test]
+    fn test_pretty_ron_round_trip() {
+        let value = GodotValue::Dictionary(vec![
+            ("position".to_string(), GodotValue::Vector2((1.0, 2.5))),
+            ("items".to_string(), GodotValue::Array(vec![GodotValue::Null, GodotValue::Integer(-3)])),
+        ]);
+        let ron = value.serialize_ron();
+        #[cfg(not(feature = "minname"))]
+        assert!(ron.starts_with("(\n    type:Dictionary,\n    value:("), "{}", ron);
+        #[cfg(feature = "minname")]
+        assert!(ron.starts_with("(\n    t:D,\n    v:("), "{}", ron);
+        assert_eq!(GodotValue::deserialize_ron(&ron).unwrap(), value);
+        assert_eq!(GodotValue::deserialize_ron("\"Null\"").unwrap(), GodotValue::Null);
+        #[cfg(not(feature = "minname"))]
+        assert_eq!(GodotValue::deserialize_ron("(value: (1), type: Integer)").unwrap(), GodotValue::Integer(1));
+        #[cfg(feature = "minname")]
+        assert_eq!(GodotValue::deserialize_ron("(v: (1), t: I)").unwrap(), GodotValue::Integer(1));
+    }
+
+    /// Struct and unit variants, which `GodotValue` doesn't have.
+    #[cfg(all(feature = "serronpretty", feature = "deron"))]
+    #[test]
+    fn test_pretty_ron_variants() {
+        #[derive(Debug, PartialEq, SerRonEnumPretty, DeRonEnumPretty)]
+        enum Shape {
+            Empty,
+            Circle { radius: f64, #[nserde(rename = "c")] center: (f64, f64), label: Option<String> },
+            Pair(i32, String),
+        }
+
+        for shape in [
+            Shape::Empty,
+            Shape::Circle { radius: 2.0, center: (0.5, 1.0), label: None },
+            Shape::Circle { radius: 1.0, center: (0.0, 0.0), label: Some("unit".to_string()) },
+            Shape::Pair(4, "four".to_string()),
+        ] {
+            let ron = shape.serialize_ron();
+            assert_eq!(Shape::deserialize_ron(&ron).unwrap(), shape, "{}", ron);
+        }
+        assert_eq!(
+            Shape::deserialize_ron("(type: \"Circle\", value: (c: (1.0, 2.0), radius: 3.0))").unwrap(),
+            Shape::Circle { radius: 3.0, center: (1.0, 2.0), label: None }
+        );
+        assert!(Shape::deserialize_ron("(type: Square, value: ())").is_err());
+    }
+
+    /// Structs through the pretty derives, with the field attributes the enums support.
+    #[cfg(all(feature = "serjsonpretty", feature = "dejson", feature = "serronpretty", feature = "deron"))]
+    #[test]
+    fn test_pretty_structs() {
+        #[derive(Debug, PartialEq)]
+        struct Degrees(f64);
+
+        impl From<&f64> for Degrees {
+            fn from(degrees: &f64) -> Degrees {
+                Degrees(*degrees)
+            }
+        }
+
+        impl From<&Degrees> for f64 {
+            fn from(degrees: &Degrees) -> f64 {
+                degrees.0
+            }
+        }
+
+        #[derive(Debug, PartialEq, SerJsonEnumPretty, DeJsonEnumPretty, SerRonEnumPretty, DeRonEnumPretty)]
+        struct Node {
+            #[nserde(rename = "n")]
+            name: String,
+            #[nserde(proxy = "f64")]
+            rotation: Degrees,
+            #[nserde(skip)]
+            cached: u32,
+            #[nserde(default = "root")]
+            parent: String,
+            value: GodotValue,
+            groups: Option<Vec<String>>,
+        }
+
+        #[derive(Debug, PartialEq, SerJsonEnumPretty, DeJsonEnumPretty, SerRonEnumPretty, DeRonEnumPretty)]
+        #[nserde(default)]
+        struct Settings {
+            width: u32,
+            title: String,
+        }
+
+        #[derive(Debug, PartialEq, SerJsonEnumPretty, DeJsonEnumPretty, SerRonEnumPretty, DeRonEnumPretty)]
+        struct Size(u32, u32);
+
+        let node = |cached| Node {
+            name: "Player".to_string(),
+            rotation: Degrees(90.0),
+            cached,
+            parent: ".".to_string(),
+            value: GodotValue::Vector2((1.0, 2.0)),
+            groups: None,
+        };
+        let (node, expected) = (node(7), node(0));
+        let json = node.serialize_json();
+        assert!(json.starts_with(r#"{"n":"Player","rotation":90.0,"parent":".""#), "{}", json);
+        assert_eq!(Node::deserialize_json(&json).unwrap(), expected);
+        let ron = node.serialize_ron();
+        assert_eq!(Node::deserialize_ron(&ron).unwrap(), expected, "{}", ron);
+
+        let parsed = Node::deserialize_json(r#"{"n":"A","rotation":0,"value":"Null","groups":["g"]}"#).unwrap();
+        assert_eq!(parsed.parent, "root");
+        assert_eq!(parsed.groups, Some(vec!["g".to_string()]));
+        assert!(Node::deserialize_json(r#"{"rotation":0,"value":"Null"}"#).is_err());
+
+        assert_eq!(Settings::deserialize_json("{}").unwrap(), Settings { width: 0, title: String::new() });
+        assert_eq!(Settings::deserialize_ron("(width: 3)").unwrap(), Settings { width: 3, title: String::new() });
+
+        assert_eq!(Size(1, 2).serialize_json(), "[1,2]");
+        assert_eq!(Size::deserialize_json("[1,2]").unwrap(), Size(1, 2));
+        assert_eq!(Size::deserialize_ron(&Size(1, 2).serialize_ron()).unwrap(), Size(1, 2));
+    }
+
+    /// The `tag`/`content` and `untagged` container attributes, in both pretty formats.
+    #[cfg(all(feature = "serjsonpretty", feature = "dejson", feature = "serronpretty", feature = "deron"))]
+    #[test]
+    fn test_pretty_tagging() {
+        #[derive(Debug, PartialEq, SerJsonEnumPretty, DeJsonEnumPretty, SerRonEnumPretty, DeRonEnumPretty)]
+        #[nserde(tag = "kind", content = "data")]
+        enum Adjacent {
+            Empty,
+            Color(f64, f64, f64, f64),
+            Circle { radius: f64 },
+        }
+
+        #[derive(Debug, PartialEq, SerJsonEnumPretty, DeJsonEnumPretty, SerRonEnumPretty, DeRonEnumPretty)]
+        #[nserde(tag = "kind")]
+        enum Internal {
+            Empty,
+            Circle { radius: f64, label: Option<String> },
+        }
+
+        #[derive(Debug, PartialEq, SerJsonEnumPretty, DeJsonEnumPretty, SerRonEnumPretty, DeRonEnumPretty)]
+        #[nserde(untagged)]
+        enum Untagged {
+            Empty,
+            Number(f64),
+            Pair(String, i64),
+            Circle { radius: f64 },
+        }
+
+        assert_eq!(Adjacent::Color(1.0, 0.5, 0.0, 1.0).serialize_json(), r#"{"kind":"Color","data":[1.0,0.5,0.0,1.0]}"#);
+        assert_eq!(Adjacent::Circle { radius: 2.0 }.serialize_json(), r#"{"kind":"Circle","data":{"radius":2.0}}"#);
+        assert_eq!(Internal::Circle { radius: 2.0, label: None }.serialize_json(), r#"{"kind":"Circle","radius":2.0}"#);
+        assert_eq!(Internal::Empty.serialize_json(), r#"{"kind":"Empty"}"#);
+        assert_eq!(Untagged::Empty.serialize_json(), "null");
+        assert_eq!(Untagged::Number(3.0).serialize_json(), "3.0");
+        assert_eq!(Untagged::Pair("a".to_string(), 1).serialize_json(), r#"["a",1]"#);
+        assert_eq!(Untagged::Circle { radius: 2.0 }.serialize_json(), r#"{"radius":2.0}"#);
+
+        for value in [Adjacent::Empty, Adjacent::Color(1.0, 0.5, 0.0, 1.0), Adjacent::Circle { radius: 2.0 }] {
+            assert_eq!(Adjacent::deserialize_json(&value.serialize_json()).unwrap(), value);
+            assert_eq!(Adjacent::deserialize_ron(&value.serialize_ron()).unwrap(), value);
+        }
+        for value in [
+            Internal::Empty,
+            Internal::Circle { radius: 2.0, label: Some("c".to_string()) },
+        ] {
+            assert_eq!(Internal::deserialize_json(&value.serialize_json()).unwrap(), value);
+            assert_eq!(Internal::deserialize_ron(&value.serialize_ron()).unwrap(), value);
+        }
+        for value in [
+            Untagged::Empty,
+            Untagged::Number(-1.5),
+            Untagged::Pair("a".to_string(), 1),
+            Untagged::Circle { radius: 2.0 },
+        ] {
+            assert_eq!(Untagged::deserialize_json(&value.serialize_json()).unwrap(), value);
+            assert_eq!(Untagged::deserialize_ron(&value.serialize_ron()).unwrap(), value);
+        }
+        assert!(Untagged::deserialize_json(r#"{"width":1}"#).is_err());
+
+        // Tags after the values, as written by consumers sorting their keys.
+        assert_eq!(
+            Adjacent::deserialize_json(r#"{"data":{"radius":2.0},"kind":"Circle"}"#).unwrap(),
+            Adjacent::Circle { radius: 2.0 }
+        );
+        assert_eq!(
+            Adjacent::deserialize_json(r#"{"data":[1,0.5,0,1],"kind":"Color"}"#).unwrap(),
+            Adjacent::Color(1.0, 0.5, 0.0, 1.0)
+        );
+        assert_eq!(
+            Internal::deserialize_json(r#"{"label":"c","radius":2.0,"kind":"Circle"}"#).unwrap(),
+            Internal::Circle { radius: 2.0, label: Some("c".to_string()) }
+        );
+        assert_eq!(Internal::deserialize_json(r#"{"other":[{}],"kind":"Empty"}"#).unwrap(), Internal::Empty);
+        assert_eq!(
+            Adjacent::deserialize_ron("(data: (radius: 2.0), kind: Circle)").unwrap(),
+            Adjacent::Circle { radius: 2.0 }
+        );
+        assert_eq!(
+            Internal::deserialize_ron(r#"(radius: 2.0, label: "c", kind: "Circle")"#).unwrap(),
+            Internal::Circle { radius: 2.0, label: Some("c".to_string()) }
+        );
+        assert_eq!(Internal::deserialize_ron("(kind: Empty)").unwrap(), Internal::Empty);
+        assert!(Adjacent::deserialize_json(r#"{"data":{"radius":2.0}}"#).is_err());
+        assert!(Adjacent::deserialize_json(r#"{"kind":"Circle"}"#).is_err());
+        assert!(Internal::deserialize_ron("(radius: 2.0)").is_err());
+        assert!(Internal::deserialize_ron("(kind: Empty, radius: 2.0)").is_err());
+    }
+}
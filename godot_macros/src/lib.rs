@@ -1,187 +1,79 @@
 extern crate alloc;
 extern crate proc_macro;
 
-use proc_macro::TokenStream;
-use crate::parse::{Category, Enum, Field, Type};
-
 #[macro_use]
 mod shared;
 
 mod parse;
+mod schema;
+mod serde_json;
+mod serde_ron;
+
+/// `SerJson` writing enum variants as `{"type": "Variant", "value": ...}`.
+#[proc_macro_derive(SerJsonEnumPretty, attributes(nserde))]
+pub fn derive_ser_json(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse::parse_data(input);
 
-fn ser_proxy_guard(fieldname: &str, field: &Field) -> String {
-    if let Some(proxy) = crate::shared::attrs_proxy(&field.attributes) {
-        if field.ty.base() == "Option" {
-            format!(
-                "{{{fieldname}.as_ref().map(|f| {{let proxy: {proxy} = Into::into(f);proxy}})}}"
-            )
-        } else {
-            format!("{{let proxy: {proxy} = Into::into(&{fieldname});proxy}}",)
-        }
-    } else {
-        format!("{fieldname}")
+    // ok we have an ident, its either a struct or a enum
+    match &input {
+        parse::Data::Struct(struct_) => serde_json::derive_ser_json_struct(struct_),
+        parse::Data::Enum(enum_) => serde_json::derive_ser_json_enum(enum_),
+        _ => unimplemented!("Only structs and enums are supported"),
     }
 }
 
-fn derive_ser_json_enum(enum_: &Enum) -> TokenStream {
-    let mut r = String::new();
+/// `DeJson` reading the output of [`SerJsonEnumPretty`](derive_ser_json).
+#[proc_macro_derive(DeJsonEnumPretty, attributes(nserde))]
+pub fn derive_de_json(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse::parse_data(input);
 
-    for variant in enum_.variants.iter() {
-        let field_name = variant.field_name.clone().unwrap();
-        let json_variant_name =
-            shared::attrs_rename(&variant.attributes).unwrap_or(field_name.clone());
+    match &input {
+        parse::Data::Struct(struct_) => serde_json::derive_de_json_struct(struct_),
+        parse::Data::Enum(enum_) => serde_json::derive_de_json_enum(enum_),
+        _ => unimplemented!("Only structs and enums are supported"),
+    }
+}
 
-        match &variant.ty {
-            Type {
-                wraps: None,
-                ident: Category::None,
-                ..
-            } => {
-                // unit variant
-                l!(
-                    r,
-                    "Self::{} => s.label(\"{}\"),",
-                    &field_name,
-                    json_variant_name
-                );
-            }
+/// `SerRon` writing enum variants as `(type: Variant, value: ...)`.
+#[proc_macro_derive(SerRonEnumPretty, attributes(nserde))]
+pub fn derive_ser_ron(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let input = parse::parse_data(input);
 
-            Type {
-                ident: Category::AnonymousStruct { contents },
-                ..
-            } => {
-                let mut items = String::new();
-                let mut field_names = vec![];
-                let last = contents.fields.len().saturating_sub(1);
-                for (index, field) in contents.fields.iter().enumerate() {
-                    if let Some(name) = &&field.field_name {
-                        let proxied_field = ser_proxy_guard(name, field);
-                        if index == last {
-                            if field.ty.base() == "Option" {
-                                l!(
-                                    items,
-                                    "if {}.is_some(){{s.field(d+1, \"{}\");{}.ser_json(d+1, s);}}",
-                                    name,
-                                    name,
-                                    proxied_field
-                                )
-                            } else {
-                                l!(
-                                    items,
-                                    "s.field(d+1, \"{}\");{}.ser_json(d+1, s);",
-                                    name,
-                                    proxied_field
-                                )
-                            }
-                        } else {
-                            if field.ty.base() == "Option" {
-                                l!(
-                                        items,
-                                        "if {}.is_some(){{s.field(d+1, \"{}\");{}.ser_json(d+1, s);s.conl();}}",
-                                        name,
-                                        name,
-                                        proxied_field
-                                    );
-                            } else {
-                                l!(
-                                    items,
-                                    "s.field(d+1, \"{}\");{}.ser_json(d+1, s);s.conl();",
-                                    name,
-                                    proxied_field
-                                );
-                            }
-                        }
-                        field_names.push(name.clone());
-                    }
-                }
-                l!(
-                    r,
-                    "Self::{} {{ {} }} => {{
-                                s.out.push('{{');
-                                s.label(\"type\");
-                                s.out.push(':');
-                                s.label(\"{}\");
-                                s.out.push(',');
-                                s.label(\"value\");
-                                s.out.push(':');
-                                s.st_pre();
-                                {}
-                                s.st_post(d);
-                                s.out.push('}}');
-                            }}",
-                    &field_name,
-                    field_names.join(","),
-                    json_variant_name,
-                    items
-                );
-            }
-            Type {
-                ident: Category::Tuple { contents },
-                ..
-            } => {
-                let mut names = Vec::new();
-                let mut inner = String::new();
-                let last = contents.len() - 1;
-                for (index, _) in contents.iter().enumerate() {
-                    let field_name = format!("f{}", index);
-                    names.push(field_name.clone());
-                    if index != last {
-                        l!(inner, "{}.ser_json(d, s); s.out.push(',');", field_name);
-                    } else {
-                        l!(inner, "{}.ser_json(d, s);", field_name);
-                    }
-                }
-                l!(
-                    r,
-                    "Self::{}  ({}) => {{
-                                s.out.push('{{');
-                                s.label(\"type\");
-                                s.out.push(':');
-                                s.label(\"{}\");
-                                s.out.push(',');
-                                s.label(\"value\");
-                                s.out.push(':');
-                                s.out.push('[');
-                                {}
-                                s.out.push(']');
-                                s.out.push('}}');
-                            }}",
-                    &field_name,
-                    names.join(","),
-                    json_variant_name,
-                    inner
-                );
-            }
-            v => {
-                unimplemented!("Unexpected type in enum: {:?}", v)
-            }
-        };
+    match &input {
+        parse::Data::Struct(struct_) => serde_ron::derive_ser_ron_struct(struct_),
+        parse::Data::Enum(enum_) => serde_ron::derive_ser_ron_enum(enum_),
+        _ => unimplemented!("Only structs and enums are supported"),
     }
-
-    format!(
-        "
-        impl SerJson for {} {{
-            fn ser_json(&self, d: usize, s: &mut nanoserde::SerJsonState) {{
-                match self {{
-                    {}
-                }}
-            }}
-        }}",
-        enum_.name, r
-    )
-        .parse()
-        .unwrap()
 }
 
-#[proc_macro_derive(SerJsonEnumPretty, attributes(nserde))]
-pub fn derive_ser_json(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+/// `DeRon` reading the output of [`SerRonEnumPretty`](derive_ser_ron).
+#[proc_macro_derive(DeRonEnumPretty, attributes(nserde))]
+pub fn derive_de_ron(input: proc_macro::TokenStream) -> proc_macro::TokenStream {
     let input = parse::parse_data(input);
 
-    // ok we have an ident, its either a struct or a enum
-    let ts = match &input {
-        parse::Data::Enum(enum_) => derive_ser_json_enum(enum_),
-        _ => unimplemented!(""),
-    };
+    match &input {
+        parse::Data::Struct(struct_) => serde_ron::derive_de_ron_struct(struct_),
+        parse::Data::Enum(enum_) => serde_ron::derive_de_ron_enum(enum_),
+        _ => unimplemented!("Only structs and enums are supported"),
+    }
+}
+
+/// Accepts `nserde` attributes without deriving anything, for types whose nanoserde impls are
+/// written by hand.
+#[proc_macro_derive(NanoserdeAttributes, attributes(nserde))]
+pub fn derive_nanoserde_attributes(_input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    proc_macro::TokenStream::new()
+}
 
-    ts
-}
\ No newline at end of file
+/// Implements `godot_data::schema::Schema` from the fields or variants of a model, for use
+/// inside `godot_data`. `#[schema(pretty)]` marks enums deriving [`SerJsonEnumPretty`](derive_ser_json)
+/// and `#[schema(natural)]` the value written by hand under `serjsonnatural`.
+///
+/// An attribute rather than a derive, placed before the other attributes of the item: derives
+/// only see the `minname` renames of `minname` builds, while the schema describes both.
+#[proc_macro_attribute]
+pub fn schema(args: proc_macro::TokenStream, input: proc_macro::TokenStream) -> proc_macro::TokenStream {
+    let mut output = input.clone();
+    output.extend(schema::impl_schema(args, input));
+    output
+}
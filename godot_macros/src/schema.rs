@@ -0,0 +1,130 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use proc_macro::{Delimiter, Group, TokenStream, TokenTree};
+
+use crate::parse::{self, Category, Data, Field, Type};
+use crate::shared;
+
+/// Applies the field and variant attributes of a `minname` build: `#[cfg_attr(<predicate naming
+/// minname>, nserde(...))]` becomes `#[nserde(...)]`, whatever the enabled features.
+fn apply_minname(input: TokenStream) -> TokenStream {
+    input
+        .into_iter()
+        .map(|token| match token {
+            TokenTree::Group(group) => {
+                let stream = minname_attribute(&group).unwrap_or_else(|| apply_minname(group.stream()));
+                let mut out = Group::new(group.delimiter(), stream);
+                out.set_span(group.span());
+                TokenTree::Group(out)
+            }
+            other => other,
+        })
+        .collect()
+}
+
+/// The attributes of `[cfg_attr(predicate, attributes)]` when the predicate names `minname`.
+fn minname_attribute(group: &Group) -> Option<TokenStream> {
+    if group.delimiter() != Delimiter::Bracket {
+        return None;
+    }
+    let mut tokens = group.stream().into_iter();
+    match tokens.next() {
+        Some(TokenTree::Ident(ident)) if ident.to_string() == "cfg_attr" => {}
+        _ => return None,
+    }
+    let Some(TokenTree::Group(args)) = tokens.next() else {
+        return None;
+    };
+    let mut args = args.stream().into_iter();
+    let predicate: TokenStream = args
+        .by_ref()
+        .take_while(|token| !matches!(token, TokenTree::Punct(punct) if punct.as_char() == ','))
+        .collect();
+    names_minname(predicate).then(|| args.collect())
+}
+
+fn names_minname(predicate: TokenStream) -> bool {
+    predicate.into_iter().any(|token| match token {
+        TokenTree::Literal(literal) => literal.to_string() == "\"minname\"",
+        TokenTree::Group(group) => names_minname(group.stream()),
+        _ => false,
+    })
+}
+
+fn key(field: &Field) -> String {
+    shared::attrs_rename(&field.attributes).unwrap_or_else(|| field.field_name.clone().unwrap())
+}
+
+fn ty(ty: &str) -> String {
+    format!("<{} as crate::schema::Schema>::ty()", ty)
+}
+
+/// The `godot_data::schema::Schema` impl of `#[schema]`, describing the fields or variants of
+/// `input` with their names in both builds.
+pub fn impl_schema(args: TokenStream, input: TokenStream) -> TokenStream {
+    let mut pretty = false;
+    let mut natural = false;
+    for arg in args {
+        match arg.to_string().as_str() {
+            "pretty" => pretty = true,
+            "natural" => natural = true,
+            "," => {}
+            other => panic!("Unknown schema option: {}", other),
+        }
+    }
+
+    let full = parse::parse_data(input.clone());
+    let min = parse::parse_data(apply_minname(input));
+    let mut types = Vec::new();
+    let mut r = String::new();
+    let def = match (&full, &min) {
+        (Data::Struct(full), Data::Struct(min)) => {
+            for (field, short) in full.fields.iter().zip(&min.fields) {
+                if shared::attrs_skip(&field.attributes) {
+                    continue;
+                }
+                let field_ty = field.ty.full();
+                l!(r, "crate::schema::Field::of::<{}>(\"{}\", \"{}\"),", field_ty, key(field), key(short));
+                types.push(field_ty);
+            }
+            format!("crate::schema::Def::Struct(vec![{}])", r)
+        }
+        (Data::Enum(full), Data::Enum(min)) => {
+            for (variant, short) in full.variants.iter().zip(&min.variants) {
+                let fields: Vec<String> = match &variant.ty.ident {
+                    Category::None => Vec::new(),
+                    Category::Tuple { contents } => contents.iter().map(Type::full).collect(),
+                    _ => unimplemented!("Only unit and tuple variants are supported"),
+                };
+                l!(
+                    r,
+                    "crate::schema::Variant::new(\"{}\", \"{}\", vec![{}]),",
+                    key(variant),
+                    key(short),
+                    fields.iter().map(|field| ty(field)).collect::<Vec<_>>().join(", ")
+                );
+                types.extend(fields);
+            }
+            if natural {
+                format!("crate::schema::Def::Value(vec![{}])", r)
+            } else {
+                format!("crate::schema::Def::Enum {{ variants: vec![{}], pretty: {} }}", r, pretty)
+            }
+        }
+        _ => unimplemented!("Only structs and enums are supported"),
+    };
+
+    let name = full.name();
+    let mut r = String::new();
+    l!(r, "impl crate::schema::Schema for {} {{", name);
+    l!(r, "fn ty() -> crate::schema::Ty {{ crate::schema::Ty::Ref(\"{}\") }}", name);
+    l!(r, "fn define(defs: &mut crate::schema::Definitions) {");
+    l!(r, "if defs.add(\"{}\", {}) {{", name, def);
+    for field_ty in types {
+        l!(r, "<{} as crate::schema::Schema>::define(defs);", field_ty);
+    }
+    l!(r, "}}}");
+    r.parse().unwrap()
+}
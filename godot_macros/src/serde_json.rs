@@ -0,0 +1,547 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::parse::{Category, Enum, Field, Struct, Type};
+use crate::shared::{self, Tagging};
+
+use proc_macro::TokenStream;
+
+/// Writes `fields` as `"key":value` pairs, reading each field through `access`. `leading` is
+/// true when the object already has an entry, so the first field needs a comma. `None`
+/// options and `skip` fields are left out.
+fn ser_json_named(fields: &[Field], access: impl Fn(&str) -> String, leading: bool) -> String {
+    let fields: Vec<&Field> = fields
+        .iter()
+        .filter(|field| !shared::attrs_skip(&field.attributes))
+        .collect();
+    let mut r = String::new();
+    if fields.is_empty() {
+        return r;
+    }
+    l!(r, "let mut needs_comma = {};", leading);
+    for field in fields {
+        let struct_fieldname = field.field_name.clone().unwrap();
+        let json_fieldname =
+            shared::attrs_rename(&field.attributes).unwrap_or_else(|| struct_fieldname.clone());
+        let proxied_field = shared::ser_proxy_guard(&access(&struct_fieldname), field);
+        let field_header = format!(
+            "if needs_comma {{ s.conl(); }} needs_comma = true; s.field(d+1, \"{}\");",
+            json_fieldname
+        );
+        if field.ty.base() == "Option" {
+            l!(r, "if let Some(t) = &{} {{ {} t.ser_json(d+1, s); }}", proxied_field, field_header);
+        } else {
+            l!(r, "{} {}.ser_json(d+1, s);", field_header, proxied_field);
+        }
+    }
+    r
+}
+
+/// Opens an object and writes `"tag":"variant"` into it.
+fn ser_json_tag(tag: &str, variant: &str) -> String {
+    format!(
+        "s.out.push('{{'); s.label(\"{}\"); s.out.push(':'); s.label(\"{}\");",
+        tag, variant
+    )
+}
+
+/// Writes the bound tuple fields `f0, f1, ...` as an array.
+fn ser_json_tuple(len: usize) -> String {
+    let mut r = String::new();
+    l!(r, "s.out.push('[');");
+    for index in 0..len {
+        if index != 0 {
+            l!(r, "s.out.push(',');");
+        }
+        l!(r, "f{}.ser_json(d, s);", index);
+    }
+    l!(r, "s.out.push(']');");
+    r
+}
+
+pub fn derive_ser_json_enum(enum_: &Enum) -> TokenStream {
+    let mut r = String::new();
+    let (generic_w_bounds, generic_no_bounds) = shared::enum_bounds_strings(enum_, "SerJson");
+    let tagging = shared::attrs_tagging(&enum_.attributes);
+    if let Some(error) = shared::internal_tuple_error(enum_, &tagging) {
+        return error;
+    }
+
+    for variant in enum_.variants.iter() {
+        let field_name = variant.field_name.clone().unwrap();
+        let json_variant_name =
+            shared::attrs_rename(&variant.attributes).unwrap_or(field_name.clone());
+
+        match &variant.ty {
+            Type {
+                wraps: None,
+                ident: Category::None,
+                ..
+            } => {
+                // unit variant
+                let body = match &tagging {
+                    Tagging::Adjacent { .. } => format!("s.label(\"{}\");", json_variant_name),
+                    Tagging::Internal { tag } => {
+                        format!("{} s.out.push('}}');", ser_json_tag(tag, &json_variant_name))
+                    }
+                    Tagging::Untagged => String::from("s.out.push_str(\"null\");"),
+                };
+                l!(r, "Self::{} => {{ {} }},", &field_name, body);
+            }
+
+            Type {
+                ident: Category::AnonymousStruct { contents },
+                ..
+            } => {
+                let field_names: Vec<String> = contents
+                    .fields
+                    .iter()
+                    .filter_map(|field| field.field_name.clone())
+                    .collect();
+                let body = match &tagging {
+                    Tagging::Adjacent { tag, content } => format!(
+                        "{} s.out.push(','); s.label(\"{}\"); s.out.push(':'); s.out.push('{{'); {} s.out.push('}}'); s.out.push('}}');",
+                        ser_json_tag(tag, &json_variant_name),
+                        content,
+                        ser_json_named(&contents.fields, |name| name.to_string(), false)
+                    ),
+                    Tagging::Internal { tag } => format!(
+                        "{} {} s.out.push('}}');",
+                        ser_json_tag(tag, &json_variant_name),
+                        ser_json_named(&contents.fields, |name| name.to_string(), true)
+                    ),
+                    Tagging::Untagged => format!(
+                        "s.out.push('{{'); {} s.out.push('}}');",
+                        ser_json_named(&contents.fields, |name| name.to_string(), false)
+                    ),
+                };
+                l!(r, "Self::{} {{ {} }} => {{ {} }},", &field_name, field_names.join(","), body);
+            }
+            Type {
+                ident: Category::Tuple { contents },
+                ..
+            } => {
+                let names: Vec<String> = (0..contents.len()).map(|index| format!("f{}", index)).collect();
+                let body = match &tagging {
+                    Tagging::Adjacent { tag, content } => format!(
+                        "{} s.out.push(','); s.label(\"{}\"); s.out.push(':'); {} s.out.push('}}');",
+                        ser_json_tag(tag, &json_variant_name),
+                        content,
+                        ser_json_tuple(contents.len())
+                    ),
+                    Tagging::Internal { .. } => unreachable!("rejected by internal_tuple_error"),
+                    Tagging::Untagged if contents.len() == 1 => String::from("f0.ser_json(d, s);"),
+                    Tagging::Untagged => ser_json_tuple(contents.len()),
+                };
+                l!(r, "Self::{}({}) => {{ {} }},", &field_name, names.join(","), body);
+            }
+            v => {
+                unimplemented!("Unexpected type in enum: {:?}", v)
+            }
+        };
+    }
+
+    format!(
+        "
+        impl{} nanoserde::SerJson for {}{} {{
+            fn ser_json(&self, d: usize, s: &mut nanoserde::SerJsonState) {{
+                match self {{
+                    {}
+                }}
+            }}
+        }}",
+        generic_w_bounds, enum_.name, generic_no_bounds, r
+    )
+        .parse()
+        .unwrap()
+}
+
+pub fn derive_ser_json_struct(struct_: &Struct) -> TokenStream {
+    let mut s = String::new();
+    let (generic_w_bounds, generic_no_bounds) = shared::struct_bounds_strings(struct_, "SerJson");
+
+    if struct_.named {
+        l!(s, "s.out.push('{');");
+        s.push_str(&ser_json_named(&struct_.fields, |name| format!("self.{}", name), false));
+        l!(s, "s.out.push('}');");
+    } else if struct_.fields.len() == 1 && shared::attrs_transparent(&struct_.attributes) {
+        l!(s, "self.0.ser_json(d, s);");
+    } else {
+        l!(s, "s.out.push('[');");
+        for index in 0..struct_.fields.len() {
+            if index != 0 {
+                l!(s, "s.out.push(',');");
+            }
+            l!(s, "self.{}.ser_json(d, s);", index);
+        }
+        l!(s, "s.out.push(']');");
+    }
+
+    format!(
+        "
+        impl{} nanoserde::SerJson for {}{} {{
+            fn ser_json(&self, d: usize, s: &mut nanoserde::SerJsonState) {{
+                {}
+            }}
+        }}",
+        generic_w_bounds,
+        struct_.name.as_ref().expect("Cannot implement for anonymous struct"),
+        generic_no_bounds,
+        s
+    )
+        .parse()
+        .unwrap()
+}
+
+pub fn derive_de_json_struct(struct_: &Struct) -> TokenStream {
+    let (generic_w_bounds, generic_no_bounds) = shared::struct_bounds_strings(struct_, "DeJson");
+
+    let body = if struct_.named {
+        derive_de_json_named(
+            "Self",
+            shared::attrs_default(&struct_.attributes).is_some(),
+            &struct_.fields,
+            false,
+        )
+    } else if struct_.fields.len() == 1 && shared::attrs_transparent(&struct_.attributes) {
+        String::from("Self(nanoserde::DeJson::de_json(s, i)?)")
+    } else {
+        derive_de_json_tuple("Self", struct_.fields.len())
+    };
+
+    format!(
+        "
+        impl{} nanoserde::DeJson for {}{} {{
+            fn de_json(s: &mut nanoserde::DeJsonState, i: &mut core::str::Chars) -> ::core::result::Result<Self, nanoserde::DeJsonErr> {{
+                ::core::result::Result::Ok({{ {} }})
+            }}
+        }}",
+        generic_w_bounds,
+        struct_.name.as_ref().expect("Cannot implement for anonymous struct"),
+        generic_no_bounds,
+        body
+    )
+        .parse()
+        .unwrap()
+}
+
+/// Reads `[a, b, ...]` into the tuple constructor `name`.
+fn derive_de_json_tuple(name: &str, len: usize) -> String {
+    let mut fields = String::new();
+    for _ in 0..len {
+        l!(fields, "{let r = nanoserde::DeJson::de_json(s, i)?;s.eat_comma_block(i)?;r},");
+    }
+    format!("{{s.block_open(i)?;let r = {}({}); s.block_close(i)?;r}}", name, fields)
+}
+
+/// Reads a `{...}` object into the fields of `name`, honouring `rename`, `default`,
+/// `default_with`, `proxy` and `skip`. Unknown keys are skipped. With `opened` the `{` and
+/// any entries before the fields have already been read.
+fn derive_de_json_named(name: &str, defaults: bool, fields: &[Field], opened: bool) -> String {
+    let mut local_vars = Vec::new();
+    let mut struct_field_names = Vec::new();
+    let mut matches = Vec::new();
+    let mut unwraps = Vec::new();
+
+    for field in fields {
+        let struct_fieldname = field.field_name.as_ref().unwrap().to_string();
+        let localvar = format!("_{}", struct_fieldname);
+        let default_val = if let Some(v) = shared::attrs_default(&field.attributes) {
+            if let Some(mut val) = v {
+                if field.ty.base() == "String"
+                    || field.ty.wraps.as_ref().is_some_and(|wrapped| {
+                        wrapped.iter().any(|ty| ty.base() == "String")
+                    })
+                {
+                    val = format!("\"{}\".to_string()", val)
+                }
+                if field.ty.base() == "Option" {
+                    val = format!("Some({})", val);
+                }
+                Some(val)
+            } else if field.ty.base() != "Option" {
+                Some(String::from("Default::default()"))
+            } else {
+                Some(String::from("None"))
+            }
+        } else {
+            shared::attrs_default_with(&field.attributes).map(|v| format!("{}()", v))
+        };
+        let json_fieldname =
+            shared::attrs_rename(&field.attributes).unwrap_or(struct_fieldname.clone());
+        let proxified_t = match shared::attrs_proxy(&field.attributes) {
+            Some(proxy) if field.ty.base() == "Option" => format!("Some(From::<&{proxy}>::from(&t))"),
+            Some(proxy) => format!("From::<&{proxy}>::from(&t)"),
+            None => "t".to_string(),
+        };
+
+        if shared::attrs_skip(&field.attributes) {
+            unwraps.push(default_val.unwrap_or_else(|| String::from("Default::default()")));
+        } else {
+            let missing = if field.ty.base() == "Option" {
+                default_val.unwrap_or_else(|| String::from("None"))
+            } else if defaults {
+                default_val.unwrap_or_else(|| String::from("Default::default()"))
+            } else {
+                default_val.unwrap_or_else(|| format!("return Err(s.err_nf(\"{}\"))", struct_fieldname))
+            };
+            unwraps.push(format!(
+                "match {} {{ Some(t) => {}, None => {} }}",
+                localvar, proxified_t, missing
+            ));
+            matches.push((json_fieldname, localvar.clone()));
+            local_vars.push(localvar);
+        }
+        struct_field_names.push(struct_fieldname);
+    }
+
+    let mut r = String::new();
+    for local_var in &local_vars {
+        l!(r, "let mut {} = None;", local_var);
+    }
+    if !opened {
+        l!(r, "s.curly_open(i)?;");
+    }
+    l!(r, "while s.next_str().is_some() {");
+    l!(r, "match AsRef::<str>::as_ref(&s.strbuf) {");
+    for (json_field_name, local_var) in matches.iter() {
+        l!(
+            r,
+            "\"{}\" => {{s.next_colon(i)?;{} = Some(nanoserde::DeJson::de_json(s, i)?)}},",
+            json_field_name,
+            local_var
+        );
+    }
+    l!(r, "_ => {s.next_colon(i)?; s.whole_field(i)?; }");
+    l!(r, "}");
+    l!(r, "s.eat_comma_curly(i)?");
+    l!(r, "}");
+    l!(r, "s.curly_close(i)?;");
+    l!(r, "{} {{", name);
+    for (field_name, unwrap) in struct_field_names.iter().zip(unwraps.iter()) {
+        l!(r, "{}: {},", field_name, unwrap);
+    }
+    l!(r, "}");
+    r
+}
+
+/// Reads what [`derive_ser_json_enum`] writes. Tags may come anywhere in their object, and
+/// untagged values are read by trying each variant in turn.
+pub fn derive_de_json_enum(enum_: &Enum) -> TokenStream {
+    let (generic_w_bounds, generic_no_bounds) = shared::enum_bounds_strings(enum_, "DeJson");
+    let tagging = shared::attrs_tagging(&enum_.attributes);
+    if let Some(error) = shared::internal_tuple_error(enum_, &tagging) {
+        return error;
+    }
+    let body = match tagging {
+        Tagging::Adjacent { tag, content } => derive_de_json_tagged(enum_, &tag, Some(&content)),
+        Tagging::Internal { tag } => derive_de_json_tagged(enum_, &tag, None),
+        Tagging::Untagged => derive_de_json_untagged(enum_),
+    };
+
+    format!(
+        "impl{} nanoserde::DeJson for {}{} {{
+            fn de_json(s: &mut nanoserde::DeJsonState, i: &mut core::str::Chars) -> ::core::result::Result<Self, nanoserde::DeJsonErr> {{
+                {}
+            }}
+        }}",
+        generic_w_bounds, enum_.name, generic_no_bounds, body
+    )
+        .parse()
+        .unwrap()
+}
+
+/// Reads the rest of an object whose `{` has been read, skipping every key but `content`,
+/// whose value `read` reads. Without `content` only skips the keys, ending on `done`.
+fn de_json_content(content: Option<(&str, &str)>, done: &str) -> String {
+    let Some((content, read)) = content else {
+        return format!(
+            "while s.next_str().is_some() {{ s.next_colon(i)?; s.whole_field(i)?; s.eat_comma_curly(i)?; }}
+            s.curly_close(i)?;
+            {done}"
+        );
+    };
+    format!(
+        "let mut r = None;
+        while s.next_str().is_some() {{
+            let is_content = s.strbuf == \"{content}\";
+            s.next_colon(i)?;
+            if is_content {{
+                r = Some({{ {read} }});
+            }} else {{
+                s.whole_field(i)?;
+            }}
+            s.eat_comma_curly(i)?;
+        }}
+        s.curly_close(i)?;
+        match r {{
+            Some(r) => r,
+            None => return ::core::result::Result::Err(s.err_nf(\"{content}\")),
+        }}"
+    )
+}
+
+/// Reads `{"tag": "Variant", ...}`. With `content` the values follow under it and unit
+/// variants are plain strings, without it the enum is internally tagged. Consumers sorting keys may write the tag after the values,
+/// so a first pass looks for the tag and the object is read again knowing the variant.
+fn derive_de_json_tagged(enum_: &Enum, tag: &str, content: Option<&str>) -> String {
+    let mut r_units = String::new();
+    let mut r_rest = String::new();
+
+    for variant in &enum_.variants {
+        let field_name = variant.field_name.clone().unwrap();
+        let json_variant_name =
+            shared::attrs_rename(&variant.attributes).unwrap_or(field_name.clone());
+        let name = format!("Self::{}", &field_name);
+
+        match &variant.ty {
+            Type {
+                wraps: None,
+                ident: Category::None,
+                ..
+            } => {
+                // unit variant
+                if content.is_some() {
+                    l!(r_units, "\"{}\" => {},", json_variant_name, &name);
+                } else {
+                    l!(r_rest, "\"{}\" => {{ {} }},", json_variant_name, de_json_content(None, &name));
+                }
+            }
+            Type {
+                ident: Category::AnonymousStruct { contents },
+                ..
+            } => {
+                let body = match content {
+                    Some(content) => de_json_content(
+                        Some((content, &derive_de_json_named(&name, false, &contents.fields, false))),
+                        "",
+                    ),
+                    None => derive_de_json_named(&name, false, &contents.fields, true),
+                };
+                l!(r_rest, "\"{}\" => {{ {} }},", json_variant_name, body);
+            }
+            Type {
+                ident: Category::Tuple { contents },
+                ..
+            } => {
+                l!(
+                    r_rest,
+                    "\"{}\" => {{ {} }},",
+                    json_variant_name,
+                    de_json_content(
+                        Some((content.expect("rejected by internal_tuple_error"), &derive_de_json_tuple(&name, contents.len()))),
+                        ""
+                    )
+                );
+            }
+            v => {
+                unimplemented!("Unexpected type in enum: {:?}", v)
+            }
+        };
+    }
+
+    let mut r = String::new();
+    l!(r, "match s.tok {");
+
+    if !r_rest.is_empty() {
+        l!(
+            r,
+            "nanoserde::DeJsonTok::CurlyOpen => {{
+                {0}
+                s.curly_open(i)?;
+                let mut variant = None;
+                while s.next_str().is_some() {{
+                    let is_tag = s.strbuf == \"{1}\";
+                    s.next_colon(i)?;
+                    if is_tag {{
+                        variant = Some(s.as_string()?);
+                        break;
+                    }}
+                    s.whole_field(i)?;
+                    s.eat_comma_curly(i)?;
+                }}
+                let Some(variant) = variant else {{
+                    return ::core::result::Result::Err(s.err_nf(\"{1}\"));
+                }};
+                {2}
+                s.curly_open(i)?;
+                ::core::result::Result::Ok(match variant.as_str() {{
+                    {3}
+                    _ => return ::core::result::Result::Err(s.err_enum(&variant)),
+                }})
+            }},",
+            shared::SAVE_POSITION,
+            tag,
+            shared::REWIND,
+            r_rest
+        );
+    }
+
+    if !r_units.is_empty() {
+        l!(
+            r,
+            "nanoserde::DeJsonTok::Str => {{
+                let _ = s.string(i)?;
+                ::core::result::Result::Ok(match s.strbuf.as_ref() {{
+                    {}
+                    _ => return ::core::result::Result::Err(s.err_enum(&s.strbuf)),
+                }})
+            }},",
+            r_units
+        );
+    }
+
+    l!(r, "_ => ::core::result::Result::Err(s.err_token(\"String or {\")),");
+    l!(r, "}");
+    r
+}
+
+/// Tries each variant in declaration order, rewinding the state after each failure.
+fn derive_de_json_untagged(enum_: &Enum) -> String {
+    let mut r = String::new();
+    l!(r, shared::SAVE_POSITION);
+
+    for (index, variant) in enum_.variants.iter().enumerate() {
+        let field_name = variant.field_name.clone().unwrap();
+        let name = format!("Self::{}", &field_name);
+        let body = match &variant.ty {
+            Type {
+                wraps: None,
+                ident: Category::None,
+                ..
+            } => format!("<() as nanoserde::DeJson>::de_json(s, i)?; {}", name),
+            Type {
+                ident: Category::AnonymousStruct { contents },
+                ..
+            } => derive_de_json_named(&name, false, &contents.fields, false),
+            Type {
+                ident: Category::Tuple { contents },
+                ..
+            } if contents.len() == 1 => format!("{}(nanoserde::DeJson::de_json(s, i)?)", name),
+            Type {
+                ident: Category::Tuple { contents },
+                ..
+            } => derive_de_json_tuple(&name, contents.len()),
+            v => {
+                unimplemented!("Unexpected type in enum: {:?}", v)
+            }
+        };
+        l!(
+            r,
+            "let variant_{0} = |s: &mut nanoserde::DeJsonState, i: &mut core::str::Chars| -> ::core::result::Result<Self, nanoserde::DeJsonErr> {{
+                ::core::result::Result::Ok({{ {1} }})
+            }};
+            if let ::core::result::Result::Ok(r) = variant_{0}(s, i) {{
+                return ::core::result::Result::Ok(r);
+            }}
+            {2}",
+            index,
+            body,
+            shared::REWIND
+        );
+    }
+
+    l!(r, "::core::result::Result::Err(s.err_parse(\"{}\"))", enum_.name);
+    r
+}
@@ -0,0 +1,583 @@
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+
+use crate::parse::{Category, Enum, Field, Struct, Type};
+use crate::shared::{self, Tagging};
+
+use proc_macro::TokenStream;
+
+/// Writes `fields` as `field: value,` lines at depth `d`, reading each field through
+/// `access`. `None` options and `skip` fields are left out.
+fn ser_ron_named(fields: &[Field], d: &str, access: impl Fn(&str) -> String) -> String {
+    let mut r = String::new();
+    for field in fields {
+        if shared::attrs_skip(&field.attributes) {
+            continue;
+        }
+        let struct_fieldname = field.field_name.clone().unwrap();
+        let ron_fieldname =
+            shared::attrs_rename(&field.attributes).unwrap_or_else(|| struct_fieldname.clone());
+        let proxied_field = shared::ser_proxy_guard(&access(&struct_fieldname), field);
+        if field.ty.base() == "Option" {
+            l!(
+                r,
+                "if let Some(t) = &{} {{ s.field({}, \"{}\"); t.ser_ron({}, s); s.conl(); }}",
+                proxied_field,
+                d,
+                ron_fieldname,
+                d
+            );
+        } else {
+            l!(
+                r,
+                "s.field({}, \"{}\"); {}.ser_ron({}, s); s.conl();",
+                d,
+                ron_fieldname,
+                proxied_field,
+                d
+            );
+        }
+    }
+    r
+}
+
+/// Opens a struct and writes `tag: Variant,` into it.
+fn ser_ron_tag(tag: &str, variant: &str) -> String {
+    format!(
+        "s.st_pre(); s.field(d+1, \"{}\"); s.out.push_str(\"{}\"); s.conl();",
+        tag, variant
+    )
+}
+
+/// Writes the bound tuple fields `f0, f1, ...` as `(f0, f1, ...)` at depth `d`.
+fn ser_ron_tuple(len: usize, d: &str) -> String {
+    let mut r = String::new();
+    l!(r, "s.out.push('(');");
+    for index in 0..len {
+        if index != 0 {
+            l!(r, "s.out.push_str(\", \");");
+        }
+        l!(r, "f{}.ser_ron({}, s);", index, d);
+    }
+    l!(r, "s.out.push(')');");
+    r
+}
+
+pub fn derive_ser_ron_enum(enum_: &Enum) -> TokenStream {
+    let mut r = String::new();
+    let (generic_w_bounds, generic_no_bounds) = shared::enum_bounds_strings(enum_, "SerRon");
+    let tagging = shared::attrs_tagging(&enum_.attributes);
+    if let Some(error) = shared::internal_tuple_error(enum_, &tagging) {
+        return error;
+    }
+
+    for variant in enum_.variants.iter() {
+        let field_name = variant.field_name.clone().unwrap();
+        let ron_variant_name =
+            shared::attrs_rename(&variant.attributes).unwrap_or(field_name.clone());
+
+        match &variant.ty {
+            Type {
+                wraps: None,
+                ident: Category::None,
+                ..
+            } => {
+                // unit variant
+                let body = match &tagging {
+                    Tagging::Adjacent { .. } => format!("s.out.push_str(\"{}\");", ron_variant_name),
+                    Tagging::Internal { tag } => {
+                        format!("{} s.st_post(d);", ser_ron_tag(tag, &ron_variant_name))
+                    }
+                    Tagging::Untagged => String::from("s.out.push_str(\"()\");"),
+                };
+                l!(r, "Self::{} => {{ {} }},", &field_name, body);
+            }
+            Type {
+                ident: Category::AnonymousStruct { contents },
+                ..
+            } => {
+                let field_names: Vec<String> = contents
+                    .fields
+                    .iter()
+                    .filter_map(|field| field.field_name.clone())
+                    .collect();
+                let body = match &tagging {
+                    Tagging::Adjacent { tag, content } => format!(
+                        "{} s.field(d+1, \"{}\"); s.st_pre(); {} s.st_post(d+1); s.conl(); s.st_post(d);",
+                        ser_ron_tag(tag, &ron_variant_name),
+                        content,
+                        ser_ron_named(&contents.fields, "d+2", |name| name.to_string())
+                    ),
+                    Tagging::Internal { tag } => format!(
+                        "{} {} s.st_post(d);",
+                        ser_ron_tag(tag, &ron_variant_name),
+                        ser_ron_named(&contents.fields, "d+1", |name| name.to_string())
+                    ),
+                    Tagging::Untagged => format!(
+                        "s.st_pre(); {} s.st_post(d);",
+                        ser_ron_named(&contents.fields, "d+1", |name| name.to_string())
+                    ),
+                };
+                l!(r, "Self::{} {{ {} }} => {{ {} }},", &field_name, field_names.join(","), body);
+            }
+            Type {
+                ident: Category::Tuple { contents },
+                ..
+            } => {
+                let names: Vec<String> = (0..contents.len()).map(|index| format!("f{}", index)).collect();
+                let body = match &tagging {
+                    Tagging::Adjacent { tag, content } => format!(
+                        "{} s.field(d+1, \"{}\"); {} s.conl(); s.st_post(d);",
+                        ser_ron_tag(tag, &ron_variant_name),
+                        content,
+                        ser_ron_tuple(contents.len(), "d+1")
+                    ),
+                    Tagging::Internal { .. } => unreachable!("rejected by internal_tuple_error"),
+                    Tagging::Untagged if contents.len() == 1 => String::from("f0.ser_ron(d, s);"),
+                    Tagging::Untagged => ser_ron_tuple(contents.len(), "d"),
+                };
+                l!(r, "Self::{}({}) => {{ {} }},", &field_name, names.join(","), body);
+            }
+            v => {
+                unimplemented!("Unexpected type in enum: {:?}", v)
+            }
+        };
+    }
+
+    format!(
+        "
+        impl{} nanoserde::SerRon for {}{} {{
+            fn ser_ron(&self, d: usize, s: &mut nanoserde::SerRonState) {{
+                match self {{
+                    {}
+                }}
+            }}
+        }}",
+        generic_w_bounds, enum_.name, generic_no_bounds, r
+    )
+        .parse()
+        .unwrap()
+}
+
+pub fn derive_ser_ron_struct(struct_: &Struct) -> TokenStream {
+    let mut s = String::new();
+    let (generic_w_bounds, generic_no_bounds) = shared::struct_bounds_strings(struct_, "SerRon");
+
+    if struct_.named {
+        l!(s, "s.st_pre();");
+        s.push_str(&ser_ron_named(&struct_.fields, "d+1", |name| format!("self.{}", name)));
+        l!(s, "s.st_post(d);");
+    } else if struct_.fields.len() == 1 && shared::attrs_transparent(&struct_.attributes) {
+        l!(s, "self.0.ser_ron(d, s);");
+    } else {
+        l!(s, "s.out.push('(');");
+        for index in 0..struct_.fields.len() {
+            if index != 0 {
+                l!(s, "s.out.push_str(\", \");");
+            }
+            l!(s, "self.{}.ser_ron(d, s);", index);
+        }
+        l!(s, "s.out.push(')');");
+    }
+
+    format!(
+        "
+        impl{} nanoserde::SerRon for {}{} {{
+            fn ser_ron(&self, d: usize, s: &mut nanoserde::SerRonState) {{
+                {}
+            }}
+        }}",
+        generic_w_bounds,
+        struct_.name.as_ref().expect("Cannot implement for anonymous struct"),
+        generic_no_bounds,
+        s
+    )
+        .parse()
+        .unwrap()
+}
+
+pub fn derive_de_ron_struct(struct_: &Struct) -> TokenStream {
+    let (generic_w_bounds, generic_no_bounds) = shared::struct_bounds_strings(struct_, "DeRon");
+
+    let body = if struct_.named {
+        derive_de_ron_named(
+            "Self",
+            shared::attrs_default(&struct_.attributes).is_some(),
+            &struct_.fields,
+            None,
+        )
+    } else if struct_.fields.len() == 1 && shared::attrs_transparent(&struct_.attributes) {
+        String::from("Self(nanoserde::DeRon::de_ron(s, i)?)")
+    } else {
+        derive_de_ron_tuple("Self", struct_.fields.len())
+    };
+
+    format!(
+        "
+        impl{} nanoserde::DeRon for {}{} {{
+            fn de_ron(s: &mut nanoserde::DeRonState, i: &mut core::str::Chars) -> ::core::result::Result<Self, nanoserde::DeRonErr> {{
+                ::core::result::Result::Ok({{ {} }})
+            }}
+        }}",
+        generic_w_bounds,
+        struct_.name.as_ref().expect("Cannot implement for anonymous struct"),
+        generic_no_bounds,
+        body
+    )
+        .parse()
+        .unwrap()
+}
+
+/// Reads `(a, b, ...)` into the tuple constructor `name`.
+fn derive_de_ron_tuple(name: &str, len: usize) -> String {
+    let mut fields = String::new();
+    for _ in 0..len {
+        l!(fields, "{let r = nanoserde::DeRon::de_ron(s, i)?;s.eat_comma_paren(i)?;r},");
+    }
+    format!("{{s.paren_open(i)?;let r = {}({}); s.paren_close(i)?;r}}", name, fields)
+}
+
+/// Reads a `(field: value, ...)` struct into the fields of `name`, honouring `rename`,
+/// `default`, `default_with`, `proxy` and `skip`. RON has no way to skip a value of unknown
+/// shape, so unknown fields are an error. With `tag` these are the fields of an internally
+/// tagged variant: the `(` has already been read and the tag is skipped wherever it is.
+fn derive_de_ron_named(name: &str, defaults: bool, fields: &[Field], tag: Option<&str>) -> String {
+    let mut local_vars = Vec::new();
+    let mut struct_field_names = Vec::new();
+    let mut matches = Vec::new();
+    let mut unwraps = Vec::new();
+
+    for field in fields {
+        let struct_fieldname = field.field_name.as_ref().unwrap().to_string();
+        let localvar = format!("_{}", struct_fieldname);
+        let default_val = if let Some(v) = shared::attrs_default(&field.attributes) {
+            if let Some(mut val) = v {
+                if field.ty.base() == "String"
+                    || field.ty.wraps.as_ref().is_some_and(|wrapped| {
+                        wrapped.iter().any(|ty| ty.base() == "String")
+                    })
+                {
+                    val = format!("\"{}\".to_string()", val)
+                }
+                if field.ty.base() == "Option" {
+                    val = format!("Some({})", val);
+                }
+                Some(val)
+            } else if field.ty.base() != "Option" {
+                Some(String::from("Default::default()"))
+            } else {
+                Some(String::from("None"))
+            }
+        } else {
+            shared::attrs_default_with(&field.attributes).map(|v| format!("{}()", v))
+        };
+        let ron_fieldname =
+            shared::attrs_rename(&field.attributes).unwrap_or(struct_fieldname.clone());
+        let proxified_t = match shared::attrs_proxy(&field.attributes) {
+            Some(proxy) if field.ty.base() == "Option" => format!("Some(From::<&{proxy}>::from(&t))"),
+            Some(proxy) => format!("From::<&{proxy}>::from(&t)"),
+            None => "t".to_string(),
+        };
+
+        if shared::attrs_skip(&field.attributes) {
+            unwraps.push(default_val.unwrap_or_else(|| String::from("Default::default()")));
+        } else {
+            let missing = if field.ty.base() == "Option" {
+                default_val.unwrap_or_else(|| String::from("None"))
+            } else if defaults {
+                default_val.unwrap_or_else(|| String::from("Default::default()"))
+            } else {
+                default_val.unwrap_or_else(|| format!("return Err(s.err_nf(\"{}\"))", struct_fieldname))
+            };
+            unwraps.push(format!(
+                "match {} {{ Some(t) => {}, None => {} }}",
+                localvar, proxified_t, missing
+            ));
+            matches.push((ron_fieldname, localvar.clone()));
+            local_vars.push(localvar);
+        }
+        struct_field_names.push(struct_fieldname);
+    }
+
+    let mut r = String::new();
+    for local_var in &local_vars {
+        l!(r, "let mut {} = None;", local_var);
+    }
+    if tag.is_none() {
+        l!(r, "s.paren_open(i)?;");
+    }
+    l!(r, "while s.next_ident().is_some() {");
+    l!(r, "match AsRef::<str>::as_ref(&s.identbuf) {");
+    if let Some(tag) = tag {
+        l!(r, "\"{}\" => {{s.next_colon(i)?; s.next_tok(i)?;}},", tag);
+    }
+    for (ron_field_name, local_var) in matches.iter() {
+        l!(
+            r,
+            "\"{}\" => {{s.next_colon(i)?;{} = Some(nanoserde::DeRon::de_ron(s, i)?)}},",
+            ron_field_name,
+            local_var
+        );
+    }
+    l!(r, "_ => return ::core::result::Result::Err(s.err_exp(&s.identbuf)),");
+    l!(r, "}");
+    l!(r, "s.eat_comma_paren(i)?");
+    l!(r, "}");
+    l!(r, "s.paren_close(i)?;");
+    l!(r, "{} {{", name);
+    for (field_name, unwrap) in struct_field_names.iter().zip(unwraps.iter()) {
+        l!(r, "{}: {},", field_name, unwrap);
+    }
+    l!(r, "}");
+    r
+}
+
+/// Reads what [`derive_ser_ron_enum`] writes. Tags may come anywhere in their struct, and
+/// untagged values are read by trying each variant in turn.
+pub fn derive_de_ron_enum(enum_: &Enum) -> TokenStream {
+    let (generic_w_bounds, generic_no_bounds) = shared::enum_bounds_strings(enum_, "DeRon");
+    let tagging = shared::attrs_tagging(&enum_.attributes);
+    if let Some(error) = shared::internal_tuple_error(enum_, &tagging) {
+        return error;
+    }
+    let body = match tagging {
+        Tagging::Adjacent { tag, content } => derive_de_ron_tagged(enum_, &tag, Some(&content)),
+        Tagging::Internal { tag } => derive_de_ron_tagged(enum_, &tag, None),
+        Tagging::Untagged => derive_de_ron_untagged(enum_),
+    };
+
+    format!(
+        "impl{} nanoserde::DeRon for {}{} {{
+            fn de_ron(s: &mut nanoserde::DeRonState, i: &mut core::str::Chars) -> ::core::result::Result<Self, nanoserde::DeRonErr> {{
+                {}
+            }}
+        }}",
+        generic_w_bounds, enum_.name, generic_no_bounds, body
+    )
+        .parse()
+        .unwrap()
+}
+
+/// Skips a value of any shape, stopping at the `,` or `)` after it.
+const SKIP_VALUE: &str = "let mut depth = 0usize;
+    loop {
+        match s.tok {
+            nanoserde::DeRonTok::ParenOpen | nanoserde::DeRonTok::BlockOpen | nanoserde::DeRonTok::CurlyOpen => depth += 1,
+            nanoserde::DeRonTok::Comma | nanoserde::DeRonTok::ParenClose if depth == 0 => break,
+            nanoserde::DeRonTok::ParenClose | nanoserde::DeRonTok::BlockClose | nanoserde::DeRonTok::CurlyClose => depth -= 1,
+            nanoserde::DeRonTok::Eof => return ::core::result::Result::Err(s.err_token(\", or )\")),
+            _ => {}
+        }
+        s.next_tok(i)?;
+    }";
+
+/// Reads the rest of a struct whose `(` has been read: `tag`, skipped, and `content`, whose
+/// value `read` reads. Without `content` only the tag may be there, ending on `done`.
+fn de_ron_content(tag: &str, content: Option<(&str, &str)>, done: &str) -> String {
+    let Some((content, read)) = content else {
+        return format!(
+            "while s.next_ident().is_some() {{
+                if s.identbuf != \"{tag}\" {{
+                    return ::core::result::Result::Err(s.err_exp(&s.identbuf));
+                }}
+                s.next_colon(i)?;
+                s.next_tok(i)?;
+                s.eat_comma_paren(i)?;
+            }}
+            s.paren_close(i)?;
+            {done}"
+        );
+    };
+    format!(
+        "let mut r = None;
+        while s.next_ident().is_some() {{
+            match AsRef::<str>::as_ref(&s.identbuf) {{
+                \"{content}\" => {{ s.next_colon(i)?; r = Some({{ {read} }}); }}
+                \"{tag}\" => {{ s.next_colon(i)?; s.next_tok(i)?; }}
+                _ => return ::core::result::Result::Err(s.err_exp(&s.identbuf)),
+            }}
+            s.eat_comma_paren(i)?;
+        }}
+        s.paren_close(i)?;
+        match r {{
+            Some(r) => r,
+            None => return ::core::result::Result::Err(s.err_nf(\"{content}\")),
+        }}"
+    )
+}
+
+/// Reads `(tag: Variant, ...)`, the variant name as an identifier or a string. With `content`
+/// the values follow under it and unit variants are bare identifiers, without it the enum is
+/// internally tagged. As in JSON, a first pass looks for the tag and the struct is read again knowing the variant.
+fn derive_de_ron_tagged(enum_: &Enum, tag: &str, content: Option<&str>) -> String {
+    let mut r_units = String::new();
+    let mut r_rest = String::new();
+
+    for variant in &enum_.variants {
+        let field_name = variant.field_name.clone().unwrap();
+        let ron_variant_name =
+            shared::attrs_rename(&variant.attributes).unwrap_or(field_name.clone());
+        let name = format!("Self::{}", &field_name);
+
+        match &variant.ty {
+            Type {
+                wraps: None,
+                ident: Category::None,
+                ..
+            } => {
+                // unit variant
+                if content.is_some() {
+                    l!(r_units, "\"{}\" => {},", ron_variant_name, &name);
+                } else {
+                    l!(r_rest, "\"{}\" => {{ {} }},", ron_variant_name, de_ron_content(tag, None, &name));
+                }
+            }
+            Type {
+                ident: Category::AnonymousStruct { contents },
+                ..
+            } => {
+                let body = match content {
+                    Some(content) => de_ron_content(
+                        tag,
+                        Some((content, &derive_de_ron_named(&name, false, &contents.fields, None))),
+                        "",
+                    ),
+                    None => derive_de_ron_named(&name, false, &contents.fields, Some(tag)),
+                };
+                l!(r_rest, "\"{}\" => {{ {} }},", ron_variant_name, body);
+            }
+            Type {
+                ident: Category::Tuple { contents },
+                ..
+            } => {
+                l!(
+                    r_rest,
+                    "\"{}\" => {{ {} }},",
+                    ron_variant_name,
+                    de_ron_content(
+                        tag,
+                        Some((content.expect("rejected by internal_tuple_error"), &derive_de_ron_tuple(&name, contents.len()))),
+                        ""
+                    )
+                );
+            }
+            v => {
+                unimplemented!("Unexpected type in enum: {:?}", v)
+            }
+        };
+    }
+
+    let mut r = String::new();
+    l!(r, "match s.tok {");
+
+    if !r_rest.is_empty() {
+        l!(
+            r,
+            "nanoserde::DeRonTok::ParenOpen => {{
+                {0}
+                s.paren_open(i)?;
+                let mut variant = None;
+                while s.next_ident().is_some() {{
+                    let is_tag = s.identbuf == \"{1}\";
+                    s.next_colon(i)?;
+                    if is_tag {{
+                        variant = Some(match s.tok {{
+                            nanoserde::DeRonTok::Ident => s.identbuf.clone(),
+                            _ => s.as_string()?,
+                        }});
+                        break;
+                    }}
+                    {2}
+                    s.eat_comma_paren(i)?;
+                }}
+                let Some(variant) = variant else {{
+                    return ::core::result::Result::Err(s.err_nf(\"{1}\"));
+                }};
+                {3}
+                s.paren_open(i)?;
+                ::core::result::Result::Ok(match variant.as_str() {{
+                    {4}
+                    _ => return ::core::result::Result::Err(s.err_enum(&variant)),
+                }})
+            }},",
+            shared::SAVE_POSITION,
+            tag,
+            SKIP_VALUE,
+            shared::REWIND,
+            r_rest
+        );
+    }
+
+    if !r_units.is_empty() {
+        l!(
+            r,
+            "nanoserde::DeRonTok::Ident | nanoserde::DeRonTok::Str => {{
+                let variant = match s.tok {{
+                    nanoserde::DeRonTok::Ident => s.identbuf.clone(),
+                    _ => s.as_string()?,
+                }};
+                let r = match variant.as_str() {{
+                    {}
+                    _ => return ::core::result::Result::Err(s.err_enum(&variant)),
+                }};
+                s.next_tok(i)?;
+                ::core::result::Result::Ok(r)
+            }},",
+            r_units
+        );
+    }
+
+    l!(r, "_ => ::core::result::Result::Err(s.err_token(\"Identifier or (\")),");
+    l!(r, "}");
+    r
+}
+
+/// Tries each variant in declaration order, rewinding the state after each failure.
+fn derive_de_ron_untagged(enum_: &Enum) -> String {
+    let mut r = String::new();
+    l!(r, shared::SAVE_POSITION);
+
+    for (index, variant) in enum_.variants.iter().enumerate() {
+        let field_name = variant.field_name.clone().unwrap();
+        let name = format!("Self::{}", &field_name);
+        let body = match &variant.ty {
+            Type {
+                wraps: None,
+                ident: Category::None,
+                ..
+            } => format!("<() as nanoserde::DeRon>::de_ron(s, i)?; {}", name),
+            Type {
+                ident: Category::AnonymousStruct { contents },
+                ..
+            } => derive_de_ron_named(&name, false, &contents.fields, None),
+            Type {
+                ident: Category::Tuple { contents },
+                ..
+            } if contents.len() == 1 => format!("{}(nanoserde::DeRon::de_ron(s, i)?)", name),
+            Type {
+                ident: Category::Tuple { contents },
+                ..
+            } => derive_de_ron_tuple(&name, contents.len()),
+            v => {
+                unimplemented!("Unexpected type in enum: {:?}", v)
+            }
+        };
+        l!(
+            r,
+            "let variant_{0} = |s: &mut nanoserde::DeRonState, i: &mut core::str::Chars| -> ::core::result::Result<Self, nanoserde::DeRonErr> {{
+                ::core::result::Result::Ok({{ {1} }})
+            }};
+            if let ::core::result::Result::Ok(r) = variant_{0}(s, i) {{
+                return ::core::result::Result::Ok(r);
+            }}
+            {2}",
+            index,
+            body,
+            shared::REWIND
+        );
+    }
+
+    l!(r, "::core::result::Result::Err(s.err_parse(\"{}\"))", enum_.name);
+    r
+}
@@ -66,6 +66,91 @@ pub fn attrs_skip(attributes: &[crate::parse::Attribute]) -> bool {
         .any(|attr| attr.tokens.len() == 1 && attr.tokens[0] == "skip")
 }
 
+/// How the pretty derives mark the variant of an enum, set by container attributes.
+pub enum Tagging {
+    /// `{"type": "Variant", "value": ...}`, the keys changed with `tag` and `content`.
+    Adjacent { tag: String, content: String },
+    /// `#[nserde(tag = "kind")]` alone: the tag sits next to the fields of struct variants.
+    /// Tuple variants have no key to put their values under and are rejected, as in serde.
+    Internal { tag: String },
+    /// `#[nserde(untagged)]`: only the value, read back by trying each variant in order.
+    Untagged,
+}
+
+fn attrs_string(attributes: &[crate::parse::Attribute], name: &str) -> Option<String> {
+    attributes.iter().find_map(|attr| {
+        if attr.tokens.len() == 2 && attr.tokens[0] == name {
+            Some(attr.tokens[1].clone())
+        } else {
+            None
+        }
+    })
+}
+
+pub fn attrs_tagging(attributes: &[crate::parse::Attribute]) -> Tagging {
+    if attributes
+        .iter()
+        .any(|attr| attr.tokens.len() == 1 && attr.tokens[0] == "untagged")
+    {
+        return Tagging::Untagged;
+    }
+    match (attrs_string(attributes, "tag"), attrs_string(attributes, "content")) {
+        (Some(tag), None) => Tagging::Internal { tag },
+        (tag, content) => Tagging::Adjacent {
+            tag: tag.unwrap_or_else(|| String::from("type")),
+            content: content.unwrap_or_else(|| String::from("value")),
+        },
+    }
+}
+
+/// A `compile_error!` for the first tuple variant of an internally tagged enum.
+pub fn internal_tuple_error(enum_: &Enum, tagging: &Tagging) -> Option<proc_macro::TokenStream> {
+    if !matches!(tagging, Tagging::Internal { .. }) {
+        return None;
+    }
+    let variant = enum_
+        .variants
+        .iter()
+        .find(|variant| matches!(variant.ty.ident, crate::parse::Category::Tuple { .. }))?;
+    Some(
+        format!(
+            "compile_error!(\"`{}::{}`: `#[nserde(tag = ...)]` cannot be used with tuple variants, add `content = ...`\");",
+            enum_.name,
+            variant.field_name.as_ref().unwrap()
+        )
+        .parse()
+        .unwrap(),
+    )
+}
+
+/// Generated code saving the position of a JSON or RON deserializer `s` reading `i`.
+pub const SAVE_POSITION: &str = "let start = (i.clone(), s.cur, s.tok.clone(), s.strbuf.clone(), s.numbuf.clone(), s.identbuf.clone(), s.line, s.col);";
+
+/// Generated code going back to the position saved by [`SAVE_POSITION`].
+pub const REWIND: &str = "*i = start.0.clone();
+    s.cur = start.1;
+    s.tok = start.2.clone();
+    s.strbuf = start.3.clone();
+    s.numbuf = start.4.clone();
+    s.identbuf = start.5.clone();
+    s.line = start.6;
+    s.col = start.7;";
+
+/// The expression serializing `fieldname`, converted through its `proxy` type if it has one.
+pub fn ser_proxy_guard(fieldname: &str, field: &crate::parse::Field) -> String {
+    if let Some(proxy) = attrs_proxy(&field.attributes) {
+        if field.ty.base() == "Option" {
+            ::alloc::format!(
+                "{{{fieldname}.as_ref().map(|f| {{let proxy: {proxy} = Into::into(f);proxy}})}}"
+            )
+        } else {
+            ::alloc::format!("{{let proxy: {proxy} = Into::into(&{fieldname});proxy}}")
+        }
+    } else {
+        fieldname.to_string()
+    }
+}
+
 pub(crate) fn struct_bounds_strings(struct_: &Struct, bound_name: &str) -> (String, String) {
     let generics: &Vec<_> = &struct_.generics;
 
@@ -87,7 +172,7 @@ pub(crate) fn struct_bounds_strings(struct_: &Struct, bound_name: &str) -> (Stri
         generic_no_bounds += ", ";
     }
     generic_no_bounds += ">";
-    return (generic_w_bounds, generic_no_bounds);
+    (generic_w_bounds, generic_no_bounds)
 }
 
 pub(crate) fn enum_bounds_strings(enum_: &Enum, bound_name: &str) -> (String, String) {
@@ -111,5 +196,5 @@ pub(crate) fn enum_bounds_strings(enum_: &Enum, bound_name: &str) -> (String, St
         generic_no_bounds += ", ";
     }
     generic_no_bounds += ">";
-    return (generic_w_bounds, generic_no_bounds);
+    (generic_w_bounds, generic_no_bounds)
 }
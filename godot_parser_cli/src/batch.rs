@@ -0,0 +1,175 @@
+use std::ffi::OsStr;
+use std::fs;
+use std::path::{Component, Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+
+use crate::error::CliError;
+
+/// The file Godot looks for to leave a directory out of the project.
+const GDIGNORE: &str = ".gdignore";
+
+fn is_pattern(text: &str) -> bool {
+    text.contains(['*', '?', '['])
+}
+
+/// Whether `path` names several inputs: a directory or a glob pattern.
+pub fn is_batch(path: &Path) -> bool {
+    path.is_dir() || is_pattern(&path.to_string_lossy())
+}
+
+/// The files under `dir` accepted by `accept`, in sorted order. Like the Godot editor, skips
+/// hidden directories such as `.godot/` and directories holding a `.gdignore` file.
+pub fn discover(dir: &Path, accept: &impl Fn(&Path) -> bool, files: &mut Vec<PathBuf>) -> Result<(), CliError> {
+    if dir.join(GDIGNORE).exists() {
+        return Ok(());
+    }
+    let mut entries = Vec::new();
+    for entry in fs::read_dir(dir).map_err(CliError::io(dir, "read the directory"))? {
+        entries.push(entry.map_err(CliError::io(dir, "read the directory"))?.path());
+    }
+    entries.sort();
+    for path in entries {
+        if path.is_dir() {
+            if !path.file_name().and_then(OsStr::to_str).is_some_and(|name| name.starts_with('.')) {
+                discover(&path, accept, files)?;
+            }
+        } else if accept(&path) {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+/// Matches a single path component against a pattern component with `*`, `?` and `[...]`.
+fn matches_name(pattern: &[char], name: &[char]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some(('*', rest)) => (0..=name.len()).any(|skip| matches_name(rest, &name[skip..])),
+        Some(('?', rest)) => !name.is_empty() && matches_name(rest, &name[1..]),
+        Some(('[', rest)) if rest.contains(&']') => {
+            let end = rest.iter().position(|c| *c == ']').unwrap();
+            let (negate, set) = match &rest[..end] {
+                ['!', set @ ..] => (true, set),
+                set => (false, set),
+            };
+            let Some((first, name_rest)) = name.split_first() else { return false };
+            let mut found = false;
+            let mut index = 0;
+            while index < set.len() {
+                if index + 2 < set.len() && set[index + 1] == '-' {
+                    found |= (set[index]..=set[index + 2]).contains(first);
+                    index += 3;
+                } else {
+                    found |= set[index] == *first;
+                    index += 1;
+                }
+            }
+            found != negate && matches_name(&rest[end + 1..], name_rest)
+        }
+        Some((c, rest)) => name.first() == Some(c) && matches_name(rest, &name[1..]),
+    }
+}
+
+/// Matches path components against pattern components, `**` standing for any number of
+/// directories.
+fn matches_path(pattern: &[Vec<char>], path: &[Vec<char>]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((component, rest)) if component.iter().collect::<String>() == "**" => {
+            (0..=path.len()).any(|skip| matches_path(rest, &path[skip..]))
+        }
+        Some((component, rest)) => {
+            path.split_first().is_some_and(|(name, path)| matches_name(component, name) && matches_path(rest, path))
+        }
+    }
+}
+
+fn chars(components: impl Iterator<Item = impl AsRef<OsStr>>) -> Vec<Vec<char>> {
+    components.map(|component| component.as_ref().to_string_lossy().chars().collect()).collect()
+}
+
+/// The inputs named by `path`, a file, a directory or a glob pattern, along with the
+/// directory the output tree mirrors.
+pub fn inputs(path: &Path, accept: &impl Fn(&Path) -> bool) -> Result<(PathBuf, Vec<PathBuf>), CliError> {
+    let mut files = Vec::new();
+    if path.is_dir() {
+        discover(path, accept, &mut files)?;
+        return Ok((path.to_path_buf(), files));
+    }
+    let components: Vec<Component> = path.components().collect();
+    let Some(first_pattern) = components.iter().position(|component| is_pattern(&component.as_os_str().to_string_lossy())) else {
+        return Ok((path.parent().unwrap_or(Path::new("")).to_path_buf(), vec![path.to_path_buf()]));
+    };
+    let base: PathBuf = components[..first_pattern].iter().collect();
+    let base = if base.as_os_str().is_empty() { PathBuf::from(".") } else { base };
+    let pattern = chars(components[first_pattern..].iter());
+    discover(&base, accept, &mut files)?;
+    files.retain(|file| file.strip_prefix(&base).is_ok_and(|relative| matches_path(&pattern, &chars(relative.iter()))));
+    Ok((base, files))
+}
+
+/// Runs `convert` on every item over all the cores, returning the results in order.
+pub fn parallel<T: Sync, R: Send>(items: &[T], convert: impl Fn(&T) -> R + Sync) -> Vec<R> {
+    let workers = thread::available_parallelism().map_or(1, |count| count.get()).min(items.len());
+    let next = AtomicUsize::new(0);
+    let results = Mutex::new(Vec::with_capacity(items.len()));
+    thread::scope(|scope| {
+        for _ in 0..workers {
+            scope.spawn(|| loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let Some(item) = items.get(index) else { break };
+                let result = convert(item);
+                results.lock().unwrap().push((index, result));
+            });
+        }
+    });
+    let mut results = results.into_inner().unwrap();
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, result)| result).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn matches(pattern: &str, path: &str) -> bool {
+        matches_path(&chars(Path::new(pattern).iter()), &chars(Path::new(path).iter()))
+    }
+
+    #[test]
+    fn test_glob() {
+        assert!(matches("*.tscn", "main.tscn"));
+        assert!(!matches("*.tscn", "scenes/main.tscn"));
+        assert!(matches("**/*.tscn", "main.tscn"));
+        assert!(matches("**/*.tscn", "scenes/levels/main.tscn"));
+        assert!(matches("scenes/level_?.tres", "scenes/level_1.tres"));
+        assert!(matches("scenes/level_[0-4].tres", "scenes/level_3.tres"));
+        assert!(!matches("scenes/level_[!0-4].tres", "scenes/level_3.tres"));
+        assert!(!matches("scenes/*.tres", "scenes/main.tscn"));
+    }
+
+    #[test]
+    fn test_discover() {
+        let root = std::env::temp_dir().join(format!("godot_parser_cli_discover_{}", std::process::id()));
+        for dir in ["scenes/levels", "addons/tool", ".godot/imported"] {
+            fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        for file in ["project.godot", "scenes/main.tscn", "scenes/levels/1.tscn", "addons/tool/x.tscn", ".godot/imported/y.tscn"] {
+            fs::write(root.join(file), "").unwrap();
+        }
+        fs::write(root.join("addons/.gdignore"), "").unwrap();
+
+        let accept = |path: &Path| path.extension().is_some_and(|extension| extension == "tscn");
+        let (base, files) = inputs(&root, &accept).unwrap();
+        assert_eq!(base, root);
+        assert_eq!(files, vec![root.join("scenes/levels/1.tscn"), root.join("scenes/main.tscn")]);
+        let (base, files) = inputs(&root.join("scenes/*.tscn"), &accept).unwrap();
+        assert_eq!(base, root.join("scenes"));
+        assert_eq!(files, vec![root.join("scenes/main.tscn")]);
+
+        assert_eq!(parallel(&[1, 2, 3, 4, 5], |n| n * 10), vec![10, 20, 30, 40, 50]);
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
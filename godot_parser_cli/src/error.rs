@@ -0,0 +1,229 @@
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use godot_data::nanoserde::{DeJsonErr, DeRonErr, SerJson};
+
+/// Exit code of errors reading or writing files.
+pub const EXIT_IO: i32 = 3;
+/// Exit code of inputs that aren't valid Godot files or valid files of the input format.
+pub const EXIT_PARSE: i32 = 4;
+/// Exit code of inputs and formats that don't go together, or data a format can't hold.
+pub const EXIT_FORMAT: i32 = 5;
+
+/// Where a parse error is, 1-based for text.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Location {
+    Text { line: usize, column: usize },
+    Byte(usize),
+}
+
+impl Location {
+    /// The location of `offset` bytes into `source`.
+    pub fn in_text(source: &str, offset: usize) -> Location {
+        let mut offset = offset.min(source.len());
+        while !source.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        let before = &source[..offset];
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+        Location::Text { line: before.matches('\n').count() + 1, column: before[line_start..].chars().count() + 1 }
+    }
+}
+
+impl fmt::Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Location::Text { line, column } => write!(f, "{}:{}", line, column),
+            Location::Byte(offset) => write!(f, "byte {}", offset),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum CliError {
+    Io { path: PathBuf, action: &'static str, error: io::Error },
+    Parse { path: PathBuf, message: String, location: Option<Location> },
+    Format { message: String },
+    /// Files of a batch that failed, each reported on its own already. Exits with the code of
+    /// the first failure.
+    Batch { failed: usize, code: i32 },
+}
+
+impl CliError {
+    pub fn io(path: &Path, action: &'static str) -> impl FnOnce(io::Error) -> CliError {
+        let path = path.to_path_buf();
+        move |error| CliError::Io { path, action, error }
+    }
+
+    pub fn parse(path: &Path, message: impl fmt::Display) -> CliError {
+        CliError::Parse { path: path.to_path_buf(), message: message.to_string(), location: None }
+    }
+
+    pub fn format(message: impl fmt::Display) -> CliError {
+        CliError::Format { message: message.to_string() }
+    }
+
+    /// A nom error of the text parsers, located in `source` when it points into it.
+    pub fn parse_text(path: &Path, source: &str, error: nom::Err<nom::error::Error<&str>>) -> CliError {
+        let (message, location) = match error {
+            nom::Err::Incomplete(_) => ("unexpected end of file".to_string(), Some(Location::in_text(source, source.len()))),
+            nom::Err::Error(error) | nom::Err::Failure(error) => (
+                format!("invalid syntax ({})", error.code.description()),
+                offset(source.as_bytes(), error.input.as_bytes()).map(|offset| Location::in_text(source, offset)),
+            ),
+        };
+        CliError::Parse { path: path.to_path_buf(), message, location }
+    }
+
+    /// A nom error of the binary parsers.
+    pub fn parse_binary(path: &Path, source: &[u8], error: nom::Err<nom::error::Error<&[u8]>>) -> CliError {
+        let (message, location) = match error {
+            nom::Err::Incomplete(_) => ("unexpected end of file".to_string(), Some(Location::Byte(source.len()))),
+            nom::Err::Error(error) | nom::Err::Failure(error) => (
+                format!("invalid data ({})", error.code.description()),
+                offset(source, error.input).map(Location::Byte),
+            ),
+        };
+        CliError::Parse { path: path.to_path_buf(), message, location }
+    }
+
+    /// Input the text parsers stopped at without reaching the end of the file.
+    pub fn trailing(path: &Path, source: &str, rest: &str) -> CliError {
+        CliError::Parse {
+            path: path.to_path_buf(),
+            message: "unexpected content".to_string(),
+            location: offset(source.as_bytes(), rest.trim_start().as_bytes()).map(|offset| Location::in_text(source, offset)),
+        }
+    }
+
+    pub fn json(path: &Path, error: DeJsonErr) -> CliError {
+        CliError::Parse {
+            path: path.to_path_buf(),
+            message: format!("{:?}", error.msg).trim_end().to_string(),
+            location: Some(Location::Text { line: error.line + 1, column: error.col + 1 }),
+        }
+    }
+
+    pub fn ron(path: &Path, error: DeRonErr) -> CliError {
+        CliError::Parse {
+            path: path.to_path_buf(),
+            message: format!("{:?}", error.msg).trim_end().to_string(),
+            location: Some(Location::Text { line: error.line + 1, column: error.col + 1 }),
+        }
+    }
+
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            CliError::Io { .. } => EXIT_IO,
+            CliError::Parse { .. } => EXIT_PARSE,
+            CliError::Format { .. } => EXIT_FORMAT,
+            CliError::Batch { code, .. } => *code,
+        }
+    }
+
+    fn kind(&self) -> &'static str {
+        match self {
+            CliError::Io { .. } => "io",
+            CliError::Parse { .. } => "parse",
+            CliError::Format { .. } => "format",
+            CliError::Batch { .. } => "batch",
+        }
+    }
+
+    fn path(&self) -> Option<&Path> {
+        match self {
+            CliError::Io { path, .. } | CliError::Parse { path, .. } => Some(path),
+            CliError::Format { .. } | CliError::Batch { .. } => None,
+        }
+    }
+
+    fn message(&self) -> String {
+        match self {
+            CliError::Io { action, error, .. } => format!("failed to {}: {}", action, error),
+            CliError::Parse { message, .. } | CliError::Format { message } => message.clone(),
+            CliError::Batch { failed, .. } => format!("{} files failed", failed),
+        }
+    }
+
+    /// The error as a single-line JSON object, for `--json-errors`.
+    pub fn to_json(&self) -> String {
+        let mut out = format!("{{\"kind\":\"{}\",\"code\":{},\"message\":{}", self.kind(), self.exit_code(), self.message().serialize_json());
+        if let Some(path) = self.path() {
+            out.push_str(&format!(",\"path\":{}", path.display().to_string().serialize_json()));
+        }
+        match self {
+            CliError::Parse { location: Some(Location::Text { line, column }), .. } => {
+                out.push_str(&format!(",\"line\":{},\"column\":{}", line, column))
+            }
+            CliError::Parse { location: Some(Location::Byte(offset)), .. } => out.push_str(&format!(",\"offset\":{}", offset)),
+            _ => {}
+        }
+        out.push('}');
+        out
+    }
+}
+
+/// `path:line:column: message`, like compilers do, so editors and CI can link to the spot.
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Parse { path, message, location: Some(location @ Location::Text { .. }) } => {
+                write!(f, "{}:{}: {}", path.display(), location, message)
+            }
+            CliError::Parse { path, message, location: Some(location) } => {
+                write!(f, "{}: {} at {}", path.display(), message, location)
+            }
+            _ => match self.path() {
+                Some(path) => write!(f, "{}: {}", path.display(), self.message()),
+                None => write!(f, "{}", self.message()),
+            },
+        }
+    }
+}
+
+/// How far into `source` the slice `rest` starts, if it is a part of it.
+fn offset(source: &[u8], rest: &[u8]) -> Option<usize> {
+    let start = source.as_ptr() as usize;
+    let position = rest.as_ptr() as usize;
+    (start..=start + source.len()).contains(&position).then(|| position - start)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_text_location() {
+        let source = "[gd_scene]\n\n[node name=\"A\"]\nbroken";
+        let rest = &source[source.find("broken").unwrap()..];
+        let error = CliError::trailing(Path::new("main.tscn"), source, rest);
+        assert_eq!(error.to_string(), "main.tscn:4:1: unexpected content");
+        assert_eq!(error.exit_code(), EXIT_PARSE);
+        assert_eq!(
+            error.to_json(),
+            r#"{"kind":"parse","code":4,"message":"unexpected content","path":"main.tscn","line":4,"column":1}"#
+        );
+        assert_eq!(Location::in_text("ab\ncd", 4), Location::Text { line: 2, column: 2 });
+    }
+
+    #[test]
+    fn test_foreign_input() {
+        // Errors built on a literal instead of the parsed input have no location.
+        let error = nom::Err::Error(nom::error::Error::new("invalid tag type", nom::error::ErrorKind::Fail));
+        match CliError::parse_text(Path::new("main.tscn"), "[gd_resource]", error) {
+            CliError::Parse { location, .. } => assert_eq!(location, None),
+            _ => unreachable!(),
+        }
+        let error = CliError::io(Path::new("missing.tscn"), "read")(io::Error::from(io::ErrorKind::NotFound));
+        assert_eq!(error.exit_code(), EXIT_IO);
+        assert!(error.to_string().starts_with("missing.tscn: failed to read: "));
+    }
+
+    #[test]
+    fn test_batch() {
+        let error = CliError::Batch { failed: 2, code: EXIT_IO };
+        assert_eq!(error.exit_code(), EXIT_IO);
+        assert_eq!(error.to_json(), r#"{"kind":"batch","code":3,"message":"2 files failed"}"#);
+    }
+}
@@ -1,46 +1,157 @@
+mod batch;
+mod error;
+mod watch;
+
 use std::any::Any;
+use std::collections::HashSet;
 use std::ffi::OsStr;
 use std::fs;
-use std::path::{PathBuf};
+use std::io::{BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::process;
+use std::sync::OnceLock;
+use std::thread;
+use std::time::{Duration, Instant};
 use base64::Engine;
 use base64::prelude::BASE64_STANDARD;
 use clap::{Parser, Subcommand, ValueEnum, ValueHint};
-use godot_data::bincode;
-use godot_data::bincode::config;
+use error::CliError;
+use godot_data::bincode::{Decode, Encode};
+use godot_data::container::{decode_container, encode_container, PayloadKind};
+use godot_data::pck_file::PckFile;
 use godot_data::project_file::ProjectFile;
 use godot_data::nanoserde::{DeJson, DeRon, SerJson, SerRon};
-use godot_data::tscn_file::TSCNFile;
-use godot_parser_library::project_parser::parse_project_file;
+use godot_data::scene_records::scene_records;
+use godot_data::schema::{json_schema, typescript, Root, SchemaOptions};
+use godot_data::serde::de::DeserializeOwned;
+use godot_data::serde::Serialize;
+use godot_data::serde_formats::{from_cbor, from_msgpack, from_toml, from_yaml, to_cbor, to_msgpack, to_toml, to_yaml};
+use godot_data::tscn_file::{TSCNFile, TagType};
+use godot_parser_library::pck_parser::{extract_pck, pack_directory, parse_pck_file, PckOptions};
+use godot_parser_library::project_parser::{parse_project_binary, parse_project_file, write_project_binary};
+use godot_parser_library::project_paths::ProjectPaths;
+use godot_parser_library::rsrc_parser::{parse_rsrc_file, write_rsrc_file};
 use godot_parser_library::tscn_tres_parser::{parse_tres_file, parse_tscn_file};
 
 #[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+#[allow(clippy::upper_case_acronyms)]
 enum Format {
     JSON,
     BIN,
-    RON
+    RON,
+    MSGPACK,
+    CBOR,
+    YAML,
+    /// Project settings only
+    TOML,
+    /// Godot's binary formats (`.scn`, `.res`, `project.binary`)
+    Godot,
+}
+
+impl Format {
+    /// The extension of converted files in a batch, `None` for Godot's own formats which keep
+    /// the extensions Godot gives them.
+    fn extension(self) -> Option<&'static str> {
+        match self {
+            Format::JSON => Some("json"),
+            Format::BIN => Some("bin"),
+            Format::RON => Some("ron"),
+            Format::MSGPACK => Some("msgpack"),
+            Format::CBOR => Some("cbor"),
+            Format::YAML => Some("yaml"),
+            Format::TOML => Some("toml"),
+            Format::Godot => None,
+        }
+    }
+}
+
+/// What stdin holds, as it has no extension to tell.
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum InputKind {
+    /// `project.godot` or `project.binary`
+    Project,
+    /// `.tscn` or `.scn`
+    Scene,
+    /// `.tres` or `.res`
+    Resource,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum SchemaLanguage {
+    JsonSchema,
+    Typescript,
+}
+
+#[derive(Copy, Clone, PartialEq, Eq, ValueEnum)]
+enum SchemaRoot {
+    Project,
+    Scene,
+    Pack,
+    Import,
+    Config,
+    ExportPresets,
+    Gdextension,
+    /// A line of the `jsonl` output
+    SceneRecord,
+}
+
+impl SchemaRoot {
+    fn root(self) -> Root {
+        match self {
+            SchemaRoot::Project => Root::ProjectFile,
+            SchemaRoot::Scene => Root::TSCNFile,
+            SchemaRoot::Pack => Root::PckFile,
+            SchemaRoot::Import => Root::ImportFile,
+            SchemaRoot::Config => Root::ConfigFile,
+            SchemaRoot::ExportPresets => Root::ExportPresets,
+            SchemaRoot::Gdextension => Root::GDExtension,
+            SchemaRoot::SceneRecord => Root::SceneRecord,
+        }
+    }
 }
 
 #[derive(Parser)]
-#[command(version, about, long_about = None)]
+#[command(version, about, long_about = None, after_help = EXIT_CODES)]
 struct Cli {
     #[command(subcommand)]
     command: Command,
 
-    #[arg(value_parser = clap::value_parser!(PathBuf), value_hint = ValueHint::FilePath)]
+    /// The input file, or `-` for stdin; `from-godot` and `jsonl` also take a directory or a
+    /// glob pattern
+    #[arg(value_parser = clap::value_parser!(PathBuf), value_hint = ValueHint::AnyPath)]
     path: PathBuf,
 
     #[arg(value_enum, default_value_t = Format::JSON)]
     format: Format,
 
-    /// Print the output to stdout
+    /// Print the output to stdout, the default when reading stdin
     #[arg(short, long)]
     stdout: bool,
 
-    /// The output file
+    /// Print binary output to stdout as is instead of in base64
+    #[arg(long)]
+    raw: bool,
+
+    /// What stdin holds when the path is `-`, text or binary
+    #[arg(long, value_enum)]
+    kind: Option<InputKind>,
+
+    /// The output file, or the directory the outputs of a directory or pattern go to, which
+    /// they need
     #[arg(short, long)]
     output: Option<PathBuf>,
+
+    /// Store a CRC-32 of the payload in BIN output
+    #[arg(long)]
+    checksum: bool,
+
+    /// Report errors on stderr as one JSON object per line
+    #[arg(long)]
+    json_errors: bool,
 }
 
+const EXIT_CODES: &str = "Exit codes: 3 for I/O errors, 4 for invalid input, 5 for unsupported formats.";
+
 
 #[derive(Subcommand)]
 enum Command {
@@ -50,171 +161,480 @@ enum Command {
 
         #[arg(value_enum, default_value_t = Format::JSON)]
         format_in: Format,
+    },
+    /// List the entries of a .pck, or of an executable with an embedded pack
+    PckList,
+    /// Extract the files of a .pck into the output directory
+    PckExtract,
+    /// Pack a directory into a .pck
+    PckPack {
+        /// Pack format version: 2 for Godot 4.0 - 4.3, 3 for Godot 4.4+
+        #[arg(long, default_value_t = PckOptions::default().format_version)]
+        pck_version: u32,
+
+        /// Alignment of the file data in bytes
+        #[arg(long, default_value_t = PckOptions::default().alignment)]
+        alignment: u64,
+    },
+    /// Write the JSON Schema or TypeScript definitions of the JSON output to the path
+    Schema {
+        #[arg(long, value_enum, default_value_t = SchemaLanguage::JsonSchema)]
+        lang: SchemaLanguage,
+
+        /// Describe a single type instead of all of them (JSON Schema only)
+        #[arg(long, value_enum)]
+        root: Option<SchemaRoot>,
+
+        /// Describe the short names of the `minname` feature
+        #[arg(long)]
+        minname: bool,
+
+        /// Describe the `{type, value}` values of the `serjsonpretty` feature
+        #[arg(long)]
+        pretty: bool,
+
+        /// Describe the plain JSON values of the `serjsonnatural` feature
+        #[arg(long, conflicts_with = "pretty")]
+        natural: bool,
+    },
+    /// Write every tag of a scene or resource, or of all of those a directory or pattern
+    /// names, as one JSON object per line (`.jsonl`). A directory or pattern needs --output
+    /// or --stdout
+    Jsonl,
+    /// Convert the project settings, scenes and resources of a directory into the --output
+    /// directory, then convert them again whenever they change, until interrupted
+    Watch {
+        /// Milliseconds between two scans of the directory
+        #[arg(long, default_value_t = 500)]
+        interval: u64,
+
+        /// Milliseconds a file must stay unchanged before it is converted, so a burst of saves
+        /// converts it once
+        #[arg(long, default_value_t = 300)]
+        debounce: u64,
+    },
+}
+
+/// Written data, printed as is or in base64 with `--stdout`.
+enum Output {
+    Text(String),
+    Binary(Vec<u8>),
+}
+
+fn is_stdio(path: &Path) -> bool {
+    path.as_os_str() == "-"
+}
+
+/// The contents of `path`, or of stdin for `-`. Stdin is read once and kept, so it can be
+/// looked at before being parsed.
+fn read(path: &Path) -> Result<Vec<u8>, CliError> {
+    static STDIN: OnceLock<Vec<u8>> = OnceLock::new();
+    if !is_stdio(path) {
+        return fs::read(path).map_err(CliError::io(path, "read"));
+    }
+    if let Some(bytes) = STDIN.get() {
+        return Ok(bytes.clone());
+    }
+    let mut bytes = Vec::new();
+    std::io::stdin().read_to_end(&mut bytes).map_err(CliError::io(path, "read"))?;
+    Ok(STDIN.get_or_init(|| bytes).clone())
+}
+
+fn read_text(path: &Path) -> Result<String, CliError> {
+    if is_stdio(path) {
+        return String::from_utf8(read(path)?).map_err(|error| CliError::parse(path, error));
+    }
+    fs::read_to_string(path).map_err(CliError::io(path, "read"))
+}
+
+/// The extension of the Godot file at `path`. Stdin gets the one matching `kind` and whether
+/// it starts like a binary file.
+fn godot_extension(path: &Path, kind: Option<InputKind>) -> Result<&str, CliError> {
+    if !is_stdio(path) {
+        return Ok(path.extension().and_then(OsStr::to_str).unwrap_or(""));
+    }
+    let kind = kind.ok_or_else(|| CliError::format("Reading stdin needs --kind project, scene or resource"))?;
+    let bytes = read(path)?;
+    Ok(match kind {
+        InputKind::Project if bytes.starts_with(b"ECFG") => "binary",
+        InputKind::Project => "godot",
+        InputKind::Scene if bytes.starts_with(b"RSRC") => "scn",
+        InputKind::Scene => "tscn",
+        InputKind::Resource if bytes.starts_with(b"RSRC") => "res",
+        InputKind::Resource => "tres",
+    })
+}
+
+/// Prints `data` to stdout, binary data in base64 unless `raw`.
+fn print_output(data: Output, raw: bool) -> Result<(), CliError> {
+    match data {
+        Output::Text(text) => println!("{}", text),
+        Output::Binary(bytes) if raw => {
+            let mut stdout = std::io::stdout().lock();
+            stdout.write_all(&bytes).and_then(|_| stdout.flush()).map_err(CliError::io(Path::new("-"), "write"))?;
+        }
+        Output::Binary(bytes) => println!("{}", BASE64_STANDARD.encode(bytes.as_slice())),
+    }
+    Ok(())
+}
+
+/// Runs a text parser over the whole of `source`.
+fn parse_text<'a, T>(
+    path: &Path,
+    source: &'a str,
+    parser: impl FnOnce(&'a str) -> nom::IResult<&'a str, T>,
+) -> Result<T, CliError> {
+    let (rest, value) = parser(source).map_err(|error| CliError::parse_text(path, source, error))?;
+    if !rest.trim().is_empty() {
+        return Err(CliError::trailing(path, source, rest));
+    }
+    Ok(value)
+}
+
+fn parse_binary<'a, T>(
+    path: &Path,
+    source: &'a [u8],
+    parser: impl FnOnce(&'a [u8]) -> nom::IResult<&'a [u8], T>,
+) -> Result<T, CliError> {
+    parser(source).map(|(_, value)| value).map_err(|error| CliError::parse_binary(path, source, error))
+}
+
+/// Reads a file written by Godot, along with the extension of its converted counterpart.
+fn from_godot(path: &Path, kind: Option<InputKind>) -> Result<(Box<dyn Any>, &'static str), CliError> {
+    Ok(match Some(godot_extension(path, kind)?) {
+        Some("scn") => (Box::from(parse_binary(path, &read(path)?, parse_rsrc_file)?), "scn"),
+        Some("res") => (Box::from(parse_binary(path, &read(path)?, parse_rsrc_file)?), "res"),
+        Some("binary") => (Box::from(parse_binary(path, &read(path)?, parse_project_binary)?), "bin"),
+        Some("godot") => (Box::from(parse_text(path, &read_text(path)?, parse_project_file)?), "bin"),
+        Some("tscn") => (Box::from(parse_text(path, &read_text(path)?, parse_tscn_file)?), "scn"),
+        Some("tres") => (Box::from(parse_text(path, &read_text(path)?, parse_tres_file)?), "res"),
+        _ => return Err(CliError::format(format!("Unsupported file extension of {}", path.display()))),
+    })
+}
+
+/// The text files a directory or pattern converts: the project settings, scenes and resources.
+fn is_godot_text(path: &Path) -> bool {
+    path.file_name() == Some(OsStr::new("project.godot"))
+        || matches!(path.extension().and_then(OsStr::to_str), Some("tscn" | "tres"))
+}
+
+fn read_scene(path: &Path, kind: Option<InputKind>) -> Result<TSCNFile, CliError> {
+    match Some(godot_extension(path, kind)?) {
+        Some("tscn") => parse_text(path, &read_text(path)?, parse_tscn_file),
+        Some("tres") => parse_text(path, &read_text(path)?, parse_tres_file),
+        Some("scn" | "res") => parse_binary(path, &read(path)?, parse_rsrc_file),
+        _ => Err(CliError::format(format!("{} is not a scene or resource", path.display()))),
+    }
+}
+
+/// Reads `path` written in `format`, other than TOML and Godot's binary formats which only
+/// some types have.
+fn deserialize<T>(path: &Path, format: Format, kind: PayloadKind) -> Result<T, CliError>
+where
+    T: DeJson + DeRon + Decode<()> + DeserializeOwned,
+{
+    match format {
+        Format::JSON => T::deserialize_json(&read_text(path)?).map_err(|error| CliError::json(path, error)),
+        Format::BIN => decode_container(&read(path)?, kind).map_err(|error| CliError::parse(path, error)),
+        Format::RON => T::deserialize_ron(&read_text(path)?).map_err(|error| CliError::ron(path, error)),
+        Format::MSGPACK => from_msgpack(&read(path)?).map_err(|error| CliError::parse(path, error)),
+        Format::CBOR => from_cbor(&read(path)?).map_err(|error| CliError::parse(path, error)),
+        Format::YAML => from_yaml(&read_text(path)?).map_err(|error| CliError::parse(path, error)),
+        Format::TOML => Err(CliError::format("TOML is only supported for project settings")),
+        Format::Godot => unreachable!("Godot's binary formats are read by the callers"),
+    }
+}
+
+/// Writes `value` in `format`, other than TOML and Godot's binary formats which only some
+/// types have.
+fn serialize<T>(value: &T, format: Format, kind: PayloadKind, checksum: bool) -> Result<Output, CliError>
+where
+    T: SerJson + SerRon + Encode + Serialize,
+{
+    Ok(match format {
+        Format::JSON => Output::Text(value.serialize_json()),
+        Format::BIN => Output::Binary(encode_container(value, kind, checksum).map_err(CliError::format)?),
+        Format::RON => Output::Text(value.serialize_ron()),
+        Format::MSGPACK => Output::Binary(to_msgpack(value).map_err(CliError::format)?),
+        Format::CBOR => Output::Binary(to_cbor(value).map_err(CliError::format)?),
+        Format::YAML => Output::Text(to_yaml(value).map_err(CliError::format)?),
+        Format::TOML => return Err(CliError::format("TOML is only supported for project settings")),
+        Format::Godot => return Err(CliError::format("Pack listings have no Godot binary format")),
+    })
+}
+
+fn report(error: &CliError, json_errors: bool) {
+    if json_errors {
+        eprintln!("{}", error.to_json());
+    } else {
+        eprintln!("error: {}", error);
     }
 }
 
 fn main() {
     let cli = Cli::parse();
+    let json_errors = cli.json_errors;
+    if let Err(error) = run(cli) {
+        // A batch reports each failure and a summary as it goes.
+        if !matches!(error, CliError::Batch { .. }) {
+            report(&error, json_errors);
+        }
+        process::exit(error.exit_code());
+    }
+}
 
+fn run(cli: Cli) -> Result<(), CliError> {
     let extension: &str;
-    let config = config::standard();
+    // Output read from stdin goes to stdout unless told otherwise.
+    let to_stdout = cli.stdout || (is_stdio(&cli.path) && cli.output.is_none());
 
     let ser_data: Box<dyn Any> = match cli.command {
+        Command::FromGodot if batch::is_batch(&cli.path) => {
+            let output_dir = cli.output.as_deref().ok_or_else(|| {
+                CliError::format("Converting a directory or pattern needs --output, the outputs would mix with the project")
+            })?;
+            return convert_all(&cli.path, output_dir, cli.format, cli.checksum, cli.json_errors);
+        }
         Command::FromGodot => {
-            let file_contents = fs::read_to_string(&cli.path)
-                .expect("Failed to read the file");
-            let _extension = cli.path.extension().and_then(OsStr::to_str).expect("Failed to get the file extension");
-            match _extension {
-                "godot" => {
-                    extension = "bin";
-                    let (_, godot_file) = parse_project_file(&file_contents).expect("Failed to parse the Godot file");
-                    Box::from(godot_file)
-                }
-                "tscn" => {
-                    extension = "scn";
-                    let (_, tscn_file) = parse_tscn_file(&file_contents).expect("Failed to parse the Godot file");
-                    Box::from(tscn_file)
-                }
-                "tres" => {
-                    extension = "res";
-                    let (_, tscn_file) = parse_tres_file(&file_contents).expect("Failed to parse the Godot file");
-                    Box::from(tscn_file)
-                }
-                _ => {
-                    panic!("Unsupported file extension");
+            if matches!(cli.path.extension().and_then(OsStr::to_str), Some("scn" | "res")) && !to_stdout && cli.output.is_none() {
+                return Err(CliError::format("Binary resources would be overwritten, use --output or --stdout"));
+            }
+            let (data, output_extension) = from_godot(&cli.path, cli.kind)?;
+            extension = output_extension;
+            data
+        }
+        Command::PckList => {
+            extension = "index";
+            Box::from(parse_binary(&cli.path, &read(&cli.path)?, parse_pck_file)?)
+        }
+        Command::PckExtract => {
+            let file_contents = read(&cli.path)?;
+            let pck_file = parse_binary(&cli.path, &file_contents, parse_pck_file)?;
+            let output_dir = cli.output.unwrap_or(cli.path.with_extension(""));
+            extract_pck(&file_contents, &pck_file, &output_dir).map_err(CliError::io(&output_dir, "extract the pack"))?;
+            return Ok(());
+        }
+        Command::PckPack { pck_version, alignment } => {
+            let options = PckOptions { format_version: pck_version, alignment, ..PckOptions::default() };
+            let data = pack_directory(&cli.path, &options).map_err(CliError::io(&cli.path, "pack the directory"))?;
+            let output_path = cli.output.unwrap_or(cli.path.with_extension("pck"));
+            fs::write(&output_path, data).map_err(CliError::io(&output_path, "write"))?;
+            return Ok(());
+        }
+        Command::Schema { lang, root, minname, pretty, natural } => {
+            let options = SchemaOptions { minname, pretty, natural };
+            let data = match lang {
+                SchemaLanguage::JsonSchema => json_schema(root.map(SchemaRoot::root), &options),
+                SchemaLanguage::Typescript => typescript(&options),
+            };
+            if cli.stdout || is_stdio(&cli.path) {
+                print!("{}", data);
+            } else {
+                fs::write(&cli.path, data).map_err(CliError::io(&cli.path, "write"))?;
+            }
+            return Ok(());
+        }
+        Command::Jsonl => {
+            let is_scene = |path: &Path| matches!(path.extension().and_then(OsStr::to_str), Some("tscn" | "tres" | "scn" | "res"));
+            // A directory or pattern has no name of its own to give the output.
+            if batch::is_batch(&cli.path) && !to_stdout && cli.output.is_none() {
+                return Err(CliError::format("Dumping a directory or pattern needs --output or --stdout"));
+            }
+            let (base, files) = batch::inputs(&cli.path, &is_scene)?;
+            let project = ProjectPaths::find(&base);
+            let output_path = cli.output.unwrap_or(cli.path.with_extension("jsonl"));
+            let mut out: BufWriter<Box<dyn Write>> = if to_stdout {
+                BufWriter::new(Box::new(std::io::stdout().lock()))
+            } else {
+                BufWriter::new(Box::new(fs::File::create(&output_path).map_err(CliError::io(&output_path, "create"))?))
+            };
+            for path in files {
+                let scene = match read_scene(&path, cli.kind) {
+                    Ok(scene) => scene,
+                    Err(error) => {
+                        // One broken file shouldn't stop a dump of the whole project.
+                        report(&error, cli.json_errors);
+                        continue;
+                    }
+                };
+                // `res://` paths inside a project, so records from different machines match.
+                let file = project
+                    .as_ref()
+                    .filter(|_| !is_stdio(&path))
+                    .and_then(|project| project.to_res(&path))
+                    .unwrap_or_else(|| path.display().to_string());
+                for record in scene_records(&file, &scene) {
+                    writeln!(out, "{}", record.serialize_json()).map_err(CliError::io(&output_path, "write"))?;
                 }
             }
-
+            out.flush().map_err(CliError::io(&output_path, "write"))?;
+            return Ok(());
+        }
+        Command::Watch { interval, debounce } => {
+            // The editor has the project open, converted files have no place in it.
+            let output_dir = cli.output.as_deref().ok_or_else(|| CliError::format("Watching needs --output"))?;
+            watch_all(
+                &cli.path,
+                output_dir,
+                cli.format,
+                cli.checksum,
+                cli.json_errors,
+                Duration::from_millis(interval),
+                Duration::from_millis(debounce),
+            );
         }
         Command::FromFormat { format_in, extension: _extension } => {
             match _extension.as_str() {
                 "godot" => {
                     extension = "bin";
                     match format_in {
-                        Format::JSON => {
-                            let file_contents = fs::read_to_string(&cli.path)
-                                .expect("Failed to read the file");
-                            let godot_file: ProjectFile = ProjectFile::deserialize_json(&file_contents).expect("Failed to deserialize the JSON file");
-                            Box::from(godot_file)
-                        }
-                        Format::BIN => {
-                            let file_contents = fs::read(&cli.path)
-                                .expect("Failed to read the file");
-                            let (godot_file, _): (ProjectFile, _) = bincode::decode_from_slice(file_contents.as_slice(), config).expect("Failed to deserialize the BIN file");
-                            Box::from(godot_file)
-                        }
-                        Format::RON => {
-                            let file_contents = fs::read_to_string(&cli.path)
-                                .expect("Failed to read the file");
-                            let godot_file: ProjectFile = ProjectFile::deserialize_ron(&file_contents).expect("Failed to deserialize the RON file");
-                            Box::from(godot_file)
-                        }
+                        Format::TOML => Box::from(from_toml(&read_text(&cli.path)?).map_err(|error| CliError::parse(&cli.path, error))?),
+                        Format::Godot => Box::from(parse_binary(&cli.path, &read(&cli.path)?, parse_project_binary)?),
+                        format => Box::from(deserialize::<ProjectFile>(&cli.path, format, PayloadKind::Project)?),
                     }
                 }
                 "tscn" => {
                     extension = "scn";
                     match format_in {
-                        Format::JSON => {
-                            let file_contents = fs::read_to_string(&cli.path)
-                                .expect("Failed to read the file");
-                            let tscn_file = TSCNFile::deserialize_json(&file_contents).expect("Failed to deserialize the JSON file");
-                            Box::from(tscn_file)
-                        }
-                        Format::BIN => {
-                            let file_contents = fs::read(&cli.path)
-                                .expect("Failed to read the file");
-                            let (tscn_file, _): (TSCNFile, _) = bincode::decode_from_slice(file_contents.as_slice(), config).expect("Failed to deserialize the BIN file");
-                            Box::from(tscn_file)
-                        }
-                        Format::RON => {
-                            let file_contents = fs::read_to_string(&cli.path)
-                                .expect("Failed to read the file");
-                            let tscn_file = TSCNFile::deserialize_ron(&file_contents).expect("Failed to deserialize the RON file");
-                            Box::from(tscn_file)
-                        }
+                        Format::Godot => Box::from(parse_binary(&cli.path, &read(&cli.path)?, parse_rsrc_file)?),
+                        format => Box::from(deserialize::<TSCNFile>(&cli.path, format, PayloadKind::Scene)?),
                     }
                 }
                 "tres" => {
                     extension = "res";
                     match format_in {
-                        Format::JSON => {
-                            let file_contents = fs::read_to_string(&cli.path)
-                                .expect("Failed to read the file");
-                            let tscn_file = TSCNFile::deserialize_json(&file_contents).expect("Failed to deserialize the JSON file");
-                            Box::from(tscn_file)
-                        }
-                        Format::BIN => {
-                            let file_contents = fs::read(&cli.path)
-                                .expect("Failed to read the file");
-                            let (tscn_file, _): (TSCNFile, _) = bincode::decode_from_slice(file_contents.as_slice(), config).expect("Failed to deserialize the BIN file");
-                            Box::from(tscn_file)
-                        }
-                        Format::RON => {
-                            let file_contents = fs::read_to_string(&cli.path)
-                                .expect("Failed to read the file");
-                            let tscn_file = TSCNFile::deserialize_ron(&file_contents).expect("Failed to deserialize the RON file");
-                            Box::from(tscn_file)
-                        }
+                        Format::Godot => Box::from(parse_binary(&cli.path, &read(&cli.path)?, parse_rsrc_file)?),
+                        format => Box::from(deserialize::<TSCNFile>(&cli.path, format, PayloadKind::Resource)?),
                     }
                 }
                 _ => {
-                    panic!("Unsupported file extension");
+                    return Err(CliError::format(format!("Unsupported file extension {}", _extension)));
                 }
             }
         }
     };
 
-    match cli.format {
-        Format::JSON => {
-            let data = if let Some(godot_file) = ser_data.downcast_ref::<ProjectFile>() {
-                godot_file.serialize_json()
-            } else if let Some(tscn_file) = ser_data.downcast_ref::<TSCNFile>() {
-                tscn_file.serialize_json()
-            } else {
-                panic!("Failed to downcast the data");
-            };
-            if cli.stdout {
-                println!("{}", data);
-            } else {
-                let output_path = cli.output.unwrap_or(cli.path.with_extension(extension));
-                let output_dir = output_path.parent().expect("Failed to get the parent directory");
-                fs::create_dir_all(output_dir).expect("Failed to create the output directory");
-                fs::write(output_path, data).expect("Failed to write the output file");
+    let (data, extension) = encode(&*ser_data, extension, cli.format, cli.checksum)?;
+    if to_stdout {
+        print_output(data, cli.raw)
+    } else {
+        write_output(data, &cli.output.unwrap_or(cli.path.with_extension(extension)))
+    }
+}
+
+/// Writes `ser_data` in `format`, returning the extension of the written file.
+fn encode<'a>(ser_data: &dyn Any, extension: &'a str, format: Format, checksum: bool) -> Result<(Output, &'a str), CliError> {
+    Ok(if let Some(godot_file) = ser_data.downcast_ref::<ProjectFile>() {
+        match format {
+            Format::TOML => (Output::Text(to_toml(godot_file).map_err(CliError::format)?), extension),
+            Format::Godot => {
+                let data = write_project_binary(godot_file)
+                    .ok_or_else(|| CliError::format("The settings can't be encoded as project.binary"))?;
+                (Output::Binary(data), "binary")
             }
+            format => (serialize(godot_file, format, PayloadKind::Project, checksum)?, extension),
         }
-        Format::BIN => {
-            let data = if let Some(godot_file) = ser_data.downcast_ref::<ProjectFile>() {
-                bincode::encode_to_vec(&godot_file, config).expect("Failed to serialize the data")
-            } else if let Some(tscn_file) = ser_data.downcast_ref::<TSCNFile>() {
-                bincode::encode_to_vec(&tscn_file, config).expect("Failed to serialize the data")
-            } else {
-                panic!("Failed to downcast the data");
-            };
-            if cli.stdout {
-                println!("{}", BASE64_STANDARD.encode(data.as_slice()));
-            } else {
-                let output_path = cli.output.unwrap_or(cli.path.with_extension(extension));
-                let output_dir = output_path.parent().expect("Failed to get the parent directory");
-                fs::create_dir_all(output_dir).expect("Failed to create the output directory");
-                fs::write(output_path, data).expect("Failed to write the output file");
+    } else if let Some(tscn_file) = ser_data.downcast_ref::<TSCNFile>() {
+        match format {
+            Format::Godot => {
+                let data = write_rsrc_file(tscn_file)
+                    .ok_or_else(|| CliError::format("The resource can't be encoded in Godot's binary format"))?;
+                (Output::Binary(data), extension)
+            }
+            format => {
+                let kind = if tscn_file.header._type == TagType::GdScene { PayloadKind::Scene } else { PayloadKind::Resource };
+                (serialize(tscn_file, format, kind, checksum)?, extension)
             }
         }
-        Format::RON => {
-            let data = if let Some(godot_file) = ser_data.downcast_ref::<ProjectFile>() {
-                godot_file.serialize_ron()
-            } else if let Some(tscn_file) = ser_data.downcast_ref::<TSCNFile>() {
-                tscn_file.serialize_ron()
-            } else {
-                panic!("Failed to downcast the data");
-            };
-            if cli.stdout {
-                println!("{}", data);
-            } else {
-                let output_path = cli.output.unwrap_or(cli.path.with_extension(extension));
-                let output_dir = output_path.parent().expect("Failed to get the parent directory");
-                fs::create_dir_all(output_dir).expect("Failed to create the output directory");
-                fs::write(output_path, data).expect("Failed to write the output file");
+    } else if let Some(pck_file) = ser_data.downcast_ref::<PckFile>() {
+        (serialize(pck_file, format, PayloadKind::Pack, checksum)?, extension)
+    } else {
+        unreachable!("every command reads a project, a resource or a pack");
+    })
+}
+
+fn write_output(data: Output, output_path: &Path) -> Result<(), CliError> {
+    if let Some(output_dir) = output_path.parent() {
+        fs::create_dir_all(output_dir).map_err(CliError::io(output_dir, "create the directory"))?;
+    }
+    let bytes = match data {
+        Output::Text(text) => text.into_bytes(),
+        Output::Binary(bytes) => bytes,
+    };
+    fs::write(output_path, bytes).map_err(CliError::io(output_path, "write"))
+}
+
+/// Converts every Godot file named by `path`, a directory or a glob pattern, in parallel, into
+/// the same tree under `output_dir`.
+fn convert_all(path: &Path, output_dir: &Path, format: Format, checksum: bool, json_errors: bool) -> Result<(), CliError> {
+    let (base, files) = batch::inputs(path, &is_godot_text)?;
+    if format.extension().is_some() {
+        // `a.tscn` and `a.tres` would both be written to `a.json`.
+        let mut stems = HashSet::new();
+        if let Some(file) = files.iter().find(|file| !stems.insert(file.with_extension(""))) {
+            return Err(CliError::format(format!("{} has the same name as another input, convert it on its own", file.display())));
+        }
+    }
+    let results = batch::parallel(&files, |file| convert_file(file, &base, output_dir, format, checksum));
+
+    let failures: Vec<CliError> = results.into_iter().filter_map(Result::err).collect();
+    for error in &failures {
+        report(error, json_errors);
+    }
+    let converted = files.len() - failures.len();
+    if json_errors {
+        eprintln!("{{\"kind\":\"summary\",\"converted\":{},\"failed\":{}}}", converted, failures.len());
+    } else {
+        eprintln!("Converted {} of {} files, {} failed", converted, files.len(), failures.len());
+    }
+    match failures.first() {
+        Some(error) => Err(CliError::Batch { failed: failures.len(), code: error.exit_code() }),
+        None => Ok(()),
+    }
+}
+
+/// Converts `file` found under `base` into the same place under `output_dir`, returning the
+/// path written. Outputs are named after the format, so they never pass for Godot's own files.
+fn convert_file(file: &Path, base: &Path, output_dir: &Path, format: Format, checksum: bool) -> Result<PathBuf, CliError> {
+    let (data, extension) = from_godot(file, None)?;
+    let (data, extension) = encode(&*data, extension, format, checksum)?;
+    let output_path = output_dir.join(file.strip_prefix(base).unwrap_or(file)).with_extension(format.extension().unwrap_or(extension));
+    write_output(data, &output_path)?;
+    Ok(output_path)
+}
+
+/// Converts the Godot files under `dir` into `output_dir` whenever they change. Errors are
+/// reported and the watch goes on, the next save of the file trying again.
+fn watch_all(
+    dir: &Path,
+    output_dir: &Path,
+    format: Format,
+    checksum: bool,
+    json_errors: bool,
+    interval: Duration,
+    debounce: Duration,
+) -> ! {
+    let mut changes = watch::Changes::default();
+    loop {
+        match batch::inputs(dir, &is_godot_text) {
+            Ok((base, files)) => {
+                let current = files.into_iter().filter_map(|file| watch::stamp(&file).map(|stamp| (file, stamp))).collect();
+                let ready = changes.update(current, Instant::now(), debounce);
+                let results = batch::parallel(&ready, |file| convert_file(file, &base, output_dir, format, checksum));
+                for (file, result) in ready.iter().zip(results) {
+                    match result {
+                        Ok(output_path) if json_errors => eprintln!(
+                            "{{\"kind\":\"converted\",\"path\":{},\"output\":{}}}",
+                            file.display().to_string().serialize_json(),
+                            output_path.display().to_string().serialize_json()
+                        ),
+                        Ok(output_path) => eprintln!("Converted {} to {}", file.display(), output_path.display()),
+                        Err(error) => report(&error, json_errors),
+                    }
+                }
             }
+            Err(error) => report(&error, json_errors),
         }
+        thread::sleep(interval);
     }
 }
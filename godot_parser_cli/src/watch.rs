@@ -0,0 +1,77 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant, SystemTime};
+
+/// What a scan knows of a file: a save changes at least one of them.
+pub type Stamp = (Option<SystemTime>, u64);
+
+pub fn stamp(path: &Path) -> Option<Stamp> {
+    let metadata = fs::metadata(path).ok()?;
+    Some((metadata.modified().ok(), metadata.len()))
+}
+
+/// Tracks the files of successive scans and hands out those that changed once they have
+/// stayed untouched for a while, so a burst of saves converts a file only once.
+#[derive(Default)]
+pub struct Changes {
+    seen: HashMap<PathBuf, Stamp>,
+    /// Changed files and when they last changed.
+    pending: HashMap<PathBuf, Instant>,
+}
+
+impl Changes {
+    /// Takes the files found by a scan at `now`, returning those unchanged for `quiet` since
+    /// their last change, in order. Every file of the first scan counts as changed.
+    pub fn update(&mut self, current: HashMap<PathBuf, Stamp>, now: Instant, quiet: Duration) -> Vec<PathBuf> {
+        for (path, stamp) in &current {
+            if self.seen.get(path) != Some(stamp) {
+                self.pending.insert(path.clone(), now);
+            }
+        }
+        self.pending.retain(|path, _| current.contains_key(path));
+        self.seen = current;
+
+        let mut ready: Vec<PathBuf> = self
+            .pending
+            .iter()
+            .filter(|(_, changed)| now.duration_since(**changed) >= quiet)
+            .map(|(path, _)| path.clone())
+            .collect();
+        ready.sort();
+        for path in &ready {
+            self.pending.remove(path);
+        }
+        ready
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_debounce() {
+        let quiet = Duration::from_millis(300);
+        let start = Instant::now();
+        let at = |millis| start + Duration::from_millis(millis);
+        let scan = |files: &[(&str, u64)]| -> HashMap<PathBuf, Stamp> {
+            files.iter().map(|(path, len)| (PathBuf::from(path), (None, *len))).collect()
+        };
+        let mut changes = Changes::default();
+
+        assert!(changes.update(scan(&[("a.tscn", 1), ("b.tres", 1)]), at(0), quiet).is_empty());
+        assert_eq!(changes.update(scan(&[("a.tscn", 1), ("b.tres", 1)]), at(300), quiet), ["a.tscn", "b.tres"].map(PathBuf::from));
+        assert!(changes.update(scan(&[("a.tscn", 1), ("b.tres", 1)]), at(600), quiet).is_empty());
+
+        // Saved three times in a row: converted once, after the last save.
+        assert!(changes.update(scan(&[("a.tscn", 2), ("b.tres", 1)]), at(700), quiet).is_empty());
+        assert!(changes.update(scan(&[("a.tscn", 3), ("b.tres", 1)]), at(900), quiet).is_empty());
+        assert!(changes.update(scan(&[("a.tscn", 4), ("b.tres", 1)]), at(1100), quiet).is_empty());
+        assert_eq!(changes.update(scan(&[("a.tscn", 4), ("b.tres", 1)]), at(1400), quiet), [PathBuf::from("a.tscn")]);
+
+        // Removed before it settled.
+        assert!(changes.update(scan(&[("a.tscn", 4), ("b.tres", 2)]), at(1500), quiet).is_empty());
+        assert!(changes.update(scan(&[("a.tscn", 4)]), at(1900), quiet).is_empty());
+    }
+}
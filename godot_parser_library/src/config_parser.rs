@@ -0,0 +1,331 @@
+use std::collections::HashMap;
+use nom::branch::alt;
+use nom::bytes::complete::{is_not, tag, take_until1};
+use nom::character::complete::{line_ending, not_line_ending, space0};
+use nom::combinator::{eof, map, opt, verify};
+use nom::error::ParseError;
+use nom::multi::many0;
+use nom::IResult;
+use godot_data::config_file::ConfigFile;
+use godot_data::export_presets::{ExportPreset, ExportPresets};
+use godot_data::gdextension::GDExtension;
+use godot_data::project_file::GodotFileParameters;
+use godot_data::values::GodotValue;
+use crate::data::values::parse_godot_value;
+use crate::data::writer::write_godot_value;
+
+fn parse_comment(input: &str) -> IResult<&str, String> {
+    let (input, _) = tag(";")(input)?;
+    let (input, text) = not_line_ending(input)?;
+    let (input, _) = opt(line_ending)(input)?;
+    Ok((input, text.trim().to_string()))
+}
+
+fn parse_parameter(input: &str) -> IResult<&str, (String, GodotValue)> {
+    let (input, name) = verify(is_not("=\r\n"), |name: &str| {
+        !name.starts_with('[') && !name.trim().is_empty()
+    })(input)?;
+    let (input, _) = tag("=")(input)?;
+    let (input, _) = space0(input)?;
+    let (input, value) = parse_godot_value(input)?;
+    let (input, _) = space0(input)?;
+    let (input, _) = alt((line_ending, eof))(input)?;
+    Ok((input, (name.trim().to_string(), value)))
+}
+
+/// Parameters up to the next section header, skipping comments and blank lines.
+fn parse_parameters(input: &str) -> IResult<&str, GodotFileParameters> {
+    let (input, parameters) = many0(alt((
+        map(parse_parameter, Some),
+        map(parse_comment, |_| None),
+        map(line_ending, |_| None),
+    )))(input)?;
+    Ok((input, parameters.into_iter().flatten().collect()))
+}
+
+fn parse_section(input: &str) -> IResult<&str, (String, GodotFileParameters)> {
+    let (input, _) = tag("[")(input)?;
+    let (input, name) = take_until1("]")(input)?;
+    let (input, _) = tag("]")(input)?;
+    let (input, _) = space0(input)?;
+    let (input, parameters) = parse_parameters(input)?;
+    Ok((input, (name.trim().to_string(), parameters)))
+}
+
+pub fn parse_config_file(input: &str) -> IResult<&str, ConfigFile> {
+    let (input, global) = parse_parameters(input)?;
+    let (input, sections) = many0(parse_section)(input)?;
+    let mut sections = sections.into_iter().collect::<HashMap<_, _>>();
+    if !global.is_empty() {
+        sections.insert(String::new(), global);
+    }
+    Ok((input, ConfigFile { sections }))
+}
+
+fn write_parameters(out: &mut String, parameters: &GodotFileParameters) {
+    let mut keys = parameters.keys().collect::<Vec<_>>();
+    keys.sort();
+    for key in keys {
+        out.push_str(&format!("{}={}\n", key, write_godot_value(&parameters[key])));
+    }
+}
+
+/// Writes sections and keys in sorted order; the global section comes first, without a header.
+pub fn write_config_file(config: &ConfigFile) -> String {
+    let mut out = String::new();
+    let mut names = config.sections.keys().collect::<Vec<_>>();
+    names.sort();
+    for name in names {
+        if !out.is_empty() {
+            out.push('\n');
+        }
+        if !name.is_empty() {
+            out.push_str(&format!("[{}]\n\n", name));
+        }
+        write_parameters(&mut out, &config.sections[name]);
+    }
+    out
+}
+
+pub(crate) fn fail<T>(input: &str) -> IResult<&str, T> {
+    Err(nom::Err::Error(nom::error::Error::from_error_kind(
+        input,
+        nom::error::ErrorKind::Fail,
+    )))
+}
+
+pub(crate) fn take_string(params: &mut GodotFileParameters, key: &str) -> Option<String> {
+    match params.remove(key) {
+        Some(GodotValue::String(s)) | Some(GodotValue::StringName(s)) => Some(s),
+        _ => None,
+    }
+}
+
+pub(crate) fn take_bool(params: &mut GodotFileParameters, key: &str) -> bool {
+    matches!(params.remove(key), Some(GodotValue::Boolean(true)))
+}
+
+pub(crate) fn take_string_list(params: &mut GodotFileParameters, key: &str) -> Vec<String> {
+    match params.remove(key) {
+        Some(GodotValue::Array(values)) => values
+            .into_iter()
+            .filter_map(|v| match v {
+                GodotValue::String(s) => Some(s),
+                _ => None,
+            })
+            .collect(),
+        Some(GodotValue::PackedStringArray(values)) => values,
+        _ => Vec::new(),
+    }
+}
+
+fn string_map(params: GodotFileParameters) -> HashMap<String, String> {
+    params
+        .into_iter()
+        .filter_map(|(key, value)| match value {
+            GodotValue::String(s) => Some((key, s)),
+            _ => None,
+        })
+        .collect()
+}
+
+pub fn parse_export_presets(input: &str) -> IResult<&str, ExportPresets> {
+    let (remain, config) = parse_config_file(input)?;
+    let mut sections = config.sections;
+    let mut indices = sections
+        .keys()
+        .filter_map(|name| name.strip_prefix("preset.")?.parse::<u32>().ok())
+        .collect::<Vec<_>>();
+    indices.sort();
+
+    let presets = indices
+        .into_iter()
+        .map(|index| {
+            let mut settings = sections.remove(&format!("preset.{}", index)).unwrap_or_default();
+            let options = sections.remove(&format!("preset.{}.options", index)).unwrap_or_default();
+            ExportPreset {
+                index,
+                name: take_string(&mut settings, "name").unwrap_or_default(),
+                platform: take_string(&mut settings, "platform").unwrap_or_default(),
+                runnable: take_bool(&mut settings, "runnable"),
+                dedicated_server: take_bool(&mut settings, "dedicated_server"),
+                custom_features: take_string(&mut settings, "custom_features")
+                    .map(|features| {
+                        features
+                            .split(',')
+                            .map(str::trim)
+                            .filter(|f| !f.is_empty())
+                            .map(String::from)
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                export_filter: take_string(&mut settings, "export_filter").unwrap_or_default(),
+                include_filter: take_string(&mut settings, "include_filter").unwrap_or_default(),
+                exclude_filter: take_string(&mut settings, "exclude_filter").unwrap_or_default(),
+                export_path: take_string(&mut settings, "export_path").unwrap_or_default(),
+                settings,
+                options,
+            }
+        })
+        .collect();
+
+    Ok((remain, ExportPresets { presets }))
+}
+
+fn version_string(value: GodotValue) -> Option<String> {
+    match value {
+        GodotValue::String(s) => Some(s),
+        v @ (GodotValue::Float(_) | GodotValue::Integer(_)) => Some(write_godot_value(&v)),
+        _ => None,
+    }
+}
+
+pub fn parse_gdextension(input: &str) -> IResult<&str, GDExtension> {
+    let (remain, config) = parse_config_file(input)?;
+    let mut sections = config.sections;
+    let mut configuration = sections.remove("configuration").unwrap_or_default();
+
+    let Some(entry_symbol) = take_string(&mut configuration, "entry_symbol") else {
+        return fail(input);
+    };
+    let dependencies = sections
+        .remove("dependencies")
+        .unwrap_or_default()
+        .into_iter()
+        .filter_map(|(features, value)| match value {
+            GodotValue::Dictionary(entries) => Some((
+                features,
+                entries
+                    .into_iter()
+                    .filter_map(|(path, target)| match target {
+                        GodotValue::String(target) => Some((path, target)),
+                        _ => None,
+                    })
+                    .collect(),
+            )),
+            _ => None,
+        })
+        .collect();
+
+    Ok((
+        remain,
+        GDExtension {
+            entry_symbol,
+            compatibility_minimum: configuration.remove("compatibility_minimum").and_then(version_string),
+            compatibility_maximum: configuration.remove("compatibility_maximum").and_then(version_string),
+            reloadable: take_bool(&mut configuration, "reloadable"),
+            libraries: string_map(sections.remove("libraries").unwrap_or_default()),
+            icons: string_map(sections.remove("icons").unwrap_or_default()),
+            dependencies,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_file_round_trip() {
+        let input = r#"; Plugin configuration.
+
+[plugin]
+
+name="Dialogue"
+description="Branching dialogue editor"
+script="plugin.gd"
+paths={
+"win": "C:\\tools\\dialogue",
+"size": Vector2(1, 2.5)
+}
+"#;
+        let (remain, config) = parse_config_file(input).unwrap();
+        assert!(remain.is_empty());
+        let plugin = &config.sections["plugin"];
+        assert!(matches!(plugin.get("script"), Some(GodotValue::String(s)) if s == "plugin.gd"));
+
+        let written = write_config_file(&config);
+        let (_, reparsed) = parse_config_file(&written).unwrap();
+        assert_eq!(written, write_config_file(&reparsed));
+    }
+
+    #[test]
+    fn test_parse_export_presets() {
+        let input = r#"[preset.1]
+
+name="Web"
+platform="Web"
+runnable=false
+custom_features="demo, itch"
+export_filter="all_resources"
+export_path="build/web/index.html"
+
+[preset.1.options]
+
+html/export_icon=true
+
+[preset.0]
+
+name="Android"
+platform="Android"
+runnable=true
+dedicated_server=false
+custom_features=""
+export_filter="all_resources"
+include_filter=""
+exclude_filter=""
+export_path=""
+encryption_include_filters=""
+
+[preset.0.options]
+
+gradle_build/use_gradle_build=false
+version/code=1
+permissions/custom_permissions=PackedStringArray()
+"#;
+        let (_, presets) = parse_export_presets(input).unwrap();
+        assert_eq!(presets.presets.len(), 2);
+        let android = &presets.presets[0];
+        assert_eq!(android.name, "Android");
+        assert!(android.runnable);
+        assert!(android.custom_features.is_empty());
+        assert!(android.settings.contains_key("encryption_include_filters"));
+        assert!(matches!(android.options.get("version/code"), Some(GodotValue::Integer(1))));
+        let web = &presets.presets[1];
+        assert_eq!(web.index, 1);
+        assert_eq!(web.custom_features, vec!["demo", "itch"]);
+    }
+
+    #[test]
+    fn test_parse_gdextension() {
+        let input = r#"[configuration]
+
+entry_symbol = "gdext_rust_init"
+compatibility_minimum = 4.1
+reloadable = true
+
+[libraries]
+
+linux.debug.x86_64 = "res://../rust/target/debug/libgame.so"
+windows.release.x86_64 = "res://../rust/target/release/game.dll"
+
+[icons]
+
+Player = "res://icons/player.svg"
+
+[dependencies]
+
+linux.debug = { "res://lib/libdep.so" : "" }
+"#;
+        let (_, extension) = parse_gdextension(input).unwrap();
+        assert_eq!(extension.entry_symbol, "gdext_rust_init");
+        assert_eq!(extension.compatibility_minimum.as_deref(), Some("4.1"));
+        assert!(extension.reloadable);
+        assert_eq!(extension.libraries.len(), 2);
+        assert_eq!(extension.icons["Player"], "res://icons/player.svg");
+        assert_eq!(
+            extension.dependencies["linux.debug"],
+            vec![("res://lib/libdep.so".to_string(), String::new())]
+        );
+    }
+}
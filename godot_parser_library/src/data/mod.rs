@@ -1 +1,2 @@
-pub mod values;
\ No newline at end of file
+pub mod values;
+pub mod writer;
@@ -1,23 +1,22 @@
 use nom::branch::alt;
-use nom::bytes::complete::{is_not, tag, take, take_while1};
+use nom::bytes::complete::{is_not, tag, take_until, take_while1};
 use nom::character::complete;
-use nom::character::complete::line_ending;
-use nom::combinator::{map, opt};
+use nom::character::complete::{anychar, char, multispace0};
+use nom::combinator::{map, map_opt, recognize};
 use nom::error::ParseError;
 use nom::multi::{many0, separated_list0};
-use nom::sequence::{delimited, separated_pair, tuple};
+use nom::sequence::{delimited, pair, preceded, separated_pair, tuple};
 use nom::IResult;
 use godot_data::values::GodotValue;
 
+type Properties = Vec<(String, GodotValue)>;
+
 fn quotes_str(s: &str) -> IResult<&str, &str> {
-    let (_, parts): (&str, Vec<&str>) = delimited(
+    delimited(
         tag("\""),
-        many0(alt((tag("\\\""), is_not("\\\"")))),
+        recognize(many0(alt((recognize(pair(char('\\'), anychar)), is_not("\\\""))))),
         tag("\""),
-    )(s)?;
-    let len = parts.join("").chars().count();
-    let (remain, out) = delimited(tag("\""), take(len), tag("\""))(s)?;
-    Ok((remain, out))
+    )(s)
 }
 
 fn string_name(s: &str) -> IResult<&str, &str> {
@@ -28,19 +27,115 @@ fn string_name(s: &str) -> IResult<&str, &str> {
 
 fn mf64(s: &str) -> IResult<&str, f64> {
     let (remain, parsed_number) =
-        take_while1(|c: char| c.is_ascii_digit() || c == '.' || c == '-')(s)?;
-    if !parsed_number.contains('.') {
+        take_while1(|c: char| c.is_ascii_digit() || c == '.' || c == '-' || c == '+' || c == 'e')(s)?;
+    if !parsed_number.contains('.') && !parsed_number.contains('e') {
         return Err(nom::Err::Error(nom::error::Error::from_error_kind(
             s,
             nom::error::ErrorKind::Digit,
         )));
     }
+    match parsed_number.parse() {
+        Ok(number) => Ok((remain, number)),
+        Err(_) => Err(nom::Err::Error(nom::error::Error::from_error_kind(
+            s,
+            nom::error::ErrorKind::Float,
+        ))),
+    }
+}
 
-    Ok((remain, parsed_number.parse().unwrap()))
+fn special_float(s: &str) -> IResult<&str, f64> {
+    alt((
+        map(tag("inf_neg"), |_| f64::NEG_INFINITY),
+        map(tag("inf"), |_| f64::INFINITY),
+        map(tag("nan"), |_| f64::NAN),
+    ))(s)
 }
 
 fn mf64_1(s: &str) -> IResult<&str, f64> {
-    alt((mf64, map(complete::i64, |v: i64| v as f64)))(s)
+    alt((mf64, special_float, map(complete::i64, |v: i64| v as f64)))(s)
+}
+
+fn comma(s: &str) -> IResult<&str, &str> {
+    delimited(multispace0, tag(","), multispace0)(s)
+}
+
+/// `Name(1, 2.5, ...)`
+fn floats<'a>(name: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<f64>> {
+    move |s| delimited(pair(tag(name), tag("(")), separated_list0(comma, mf64_1), tag(")"))(s)
+}
+
+/// `Name(1, 2, ...)`
+fn ints<'a>(name: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<i64>> {
+    move |s| delimited(pair(tag(name), tag("(")), separated_list0(comma, complete::i64), tag(")"))(s)
+}
+
+fn fixed_floats<'a, const N: usize>(name: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, [f64; N]> {
+    map_opt(floats(name), |v| v.try_into().ok())
+}
+
+fn fixed_ints<'a, const N: usize>(name: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, [i32; N]> {
+    map_opt(ints(name), |v| {
+        v.into_iter()
+            .map(|i| i32::try_from(i).ok())
+            .collect::<Option<Vec<_>>>()?
+            .try_into()
+            .ok()
+    })
+}
+
+fn float_chunks<'a, const N: usize>(name: &'static str) -> impl FnMut(&'a str) -> IResult<&'a str, Vec<[f64; N]>> {
+    map_opt(floats(name), |v| {
+        if v.len() % N != 0 {
+            return None;
+        }
+        Some(v.chunks(N).map(|c| c.try_into().unwrap()).collect())
+    })
+}
+
+fn engine_type(s: &str) -> IResult<&str, GodotValue> {
+    alt((
+        map(fixed_ints::<2>("Vector2i"), |[x, y]| GodotValue::Vector2i((x, y))),
+        map(fixed_ints::<4>("Rect2i"), |[x, y, w, h]| GodotValue::Rect2i((x, y, w, h))),
+        map(fixed_floats::<3>("Vector3"), |[x, y, z]| GodotValue::Vector3((x, y, z))),
+        map(fixed_ints::<3>("Vector3i"), |[x, y, z]| GodotValue::Vector3i((x, y, z))),
+        map(fixed_floats::<4>("Vector4"), |[x, y, z, w]| GodotValue::Vector4((x, y, z, w))),
+        map(fixed_ints::<4>("Vector4i"), |[x, y, z, w]| GodotValue::Vector4i((x, y, z, w))),
+        map(fixed_floats::<6>("Transform2D"), GodotValue::Transform2D),
+        map(fixed_floats::<4>("Plane"), |[a, b, c, d]| GodotValue::Plane((a, b, c, d))),
+        map(fixed_floats::<4>("Quaternion"), |[x, y, z, w]| GodotValue::Quaternion((x, y, z, w))),
+        map(fixed_floats::<6>("AABB"), GodotValue::AABB),
+        map(fixed_floats::<9>("Basis"), GodotValue::Basis),
+        map(fixed_floats::<12>("Transform3D"), GodotValue::Transform3D),
+        map(fixed_floats::<16>("Projection"), GodotValue::Projection),
+    ))(s)
+}
+
+fn packed_array(s: &str) -> IResult<&str, GodotValue> {
+    alt((
+        map_opt(ints("PackedByteArray"), |v| {
+            v.into_iter().map(|i| u8::try_from(i).ok()).collect::<Option<_>>().map(GodotValue::PackedByteArray)
+        }),
+        map_opt(ints("PackedInt32Array"), |v| {
+            v.into_iter().map(|i| i32::try_from(i).ok()).collect::<Option<_>>().map(GodotValue::PackedInt32Array)
+        }),
+        map(ints("PackedInt64Array"), GodotValue::PackedInt64Array),
+        map(floats("PackedFloat32Array"), |v| {
+            GodotValue::PackedFloat32Array(v.into_iter().map(|f| f as f32).collect())
+        }),
+        map(floats("PackedFloat64Array"), GodotValue::PackedFloat64Array),
+        map(float_chunks::<2>("PackedVector2Array"), |v| {
+            GodotValue::PackedVector2Array(v.into_iter().map(|[x, y]| (x, y)).collect())
+        }),
+        map(float_chunks::<3>("PackedVector3Array"), |v| {
+            GodotValue::PackedVector3Array(v.into_iter().map(|[x, y, z]| (x, y, z)).collect())
+        }),
+        map(float_chunks::<4>("PackedColorArray"), |v| {
+            GodotValue::PackedColorArray(v.into_iter().map(|[r, g, b, a]| (r, g, b, a)).collect())
+        }),
+        map(float_chunks::<4>("PackedVector4Array"), |v| {
+            GodotValue::PackedVector4Array(v.into_iter().map(|[x, y, z, w]| (x, y, z, w)).collect())
+        }),
+    ))(s)
 }
 
 fn boolean(s: &str) -> IResult<&str, bool> {
@@ -57,7 +152,7 @@ fn parse_packed_string_array(input: &str) -> IResult<&str, Vec<String>> {
 
 fn node_path(s: &str) -> IResult<&str, String> {
     map(
-        delimited(tag("NodePath(\""), is_not("\""), tag("\")")),
+        delimited(tag("NodePath("), quotes_str, tag(")")),
         |s: &str| s.to_string(),
     )(s)
 }
@@ -117,36 +212,105 @@ fn color(s: &str) -> IResult<&str, (f64, f64, f64, f64)> {
     ))
 }
 
+/// `Array[Type]([...])`; the element type is not kept.
+fn typed_array(s: &str) -> IResult<&str, Vec<GodotValue>> {
+    let (remain, _) = tuple((tag("Array["), take_until("]("), tag("](")))(s)?;
+    let (remain, list) = array(remain)?;
+    let (remain, _) = tag(")")(remain)?;
+    Ok((remain, list))
+}
+
 fn array(s: &str) -> IResult<&str, Vec<GodotValue>> {
-    let (remain, _) = tag("[")(s)?;
-    let (remain, list) = separated_list0(tag(", "), parse_godot_value)(remain)?;
-    let (remain, _) = tag("]")(remain)?;
+    let (remain, _) = pair(tag("["), multispace0)(s)?;
+    let (remain, list) = separated_list0(
+        delimited(multispace0, tag(","), multispace0),
+        parse_godot_value,
+    )(remain)?;
+    let (remain, _) = pair(multispace0, tag("]"))(remain)?;
     Ok((remain, list))
 }
 
-fn dictionary(s: &str) -> IResult<&str, Vec<(String, GodotValue)>> {
-    let (remain, _) = tag("{\n")(s)?;
-    let (remain, list) = separated_list0(tag(",\n"), separated_pair(map(quotes_str, |s: &str| s.to_string()), tag(": "), parse_godot_value))(remain)?;
-    let (remain, _) = tag("\n}")(remain)?;
+fn dictionary(s: &str) -> IResult<&str, Properties> {
+    let (remain, _) = pair(tag("{"), multispace0)(s)?;
+    let (remain, list) = separated_list0(
+        delimited(multispace0, tag(","), multispace0),
+        separated_pair(
+            map(quotes_str, |s: &str| s.to_string()),
+            delimited(multispace0, tag(":"), multispace0),
+            parse_godot_value,
+        ),
+    )(remain)?;
+    let (remain, _) = pair(multispace0, tag("}"))(remain)?;
     Ok((remain, list))
 }
 
+fn object(s: &str) -> IResult<&str, (String, Properties)> {
+    let (remain, class) = preceded(tag("Object("), is_not(",)"))(s)?;
+    let (remain, properties) = many0(preceded(
+        pair(tag(","), multispace0),
+        separated_pair(
+            map(quotes_str, |s: &str| s.to_string()),
+            tag(":"),
+            parse_godot_value,
+        ),
+    ))(remain)?;
+    let (remain, _) = tag(")")(remain)?;
+    Ok((remain, (class.to_string(), properties)))
+}
+
 pub fn parse_godot_value(input: &str) -> IResult<&str, GodotValue> {
     alt((
         map(tag("null"), |_| GodotValue::Null),
+        map(special_float, GodotValue::Float),
         map(string_name, |s: &str| GodotValue::StringName(s.to_string())),
         map(quotes_str, |s: &str| GodotValue::String(s.to_string())),
-        map(mf64, |s: f64| GodotValue::Float(s)),
-        map(complete::i64, |s: i64| GodotValue::Integer(s)),
-        map(boolean, |s: bool| GodotValue::Boolean(s)),
-        map(parse_packed_string_array, |s| GodotValue::PackedStringArray(s)),
-        map(node_path, |s| GodotValue::NodePath(s)),
-        map(vec2, |s| GodotValue::Vector2(s)),
-        map(rect2, |s| GodotValue::Rect2(s)),
-        map(ext_resource, |s| GodotValue::ExtResourceLink(s)),
-        map(sub_resource, |s| GodotValue::SubResourceLink(s)),
-        map(color, |s| GodotValue::Color(s)),
-        map(array, |s| GodotValue::Array(s)),
-        map(dictionary, |s| GodotValue::Dictionary(s)),
+        map(mf64, GodotValue::Float),
+        map(complete::i64, GodotValue::Integer),
+        map(boolean, GodotValue::Boolean),
+        map(parse_packed_string_array, GodotValue::PackedStringArray),
+        map(node_path, GodotValue::NodePath),
+        map(vec2, GodotValue::Vector2),
+        map(rect2, GodotValue::Rect2),
+        map(ext_resource, GodotValue::ExtResourceLink),
+        map(sub_resource, GodotValue::SubResourceLink),
+        map(color, GodotValue::Color),
+        map(array, GodotValue::Array),
+        map(dictionary, GodotValue::Dictionary),
+        map(object, GodotValue::Object),
+        map(typed_array, GodotValue::Array),
+        engine_type,
+        packed_array,
     ))(input)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use godot_data::values::GodotValue;
+    use super::parse_godot_value;
+
+    #[test]
+    fn test_escaped_strings() {
+        // Escapes stay as written; only the quote that ends the string is special.
+        assert_eq!(
+            parse_godot_value(r#""say \"hi\"" tail"#).unwrap(),
+            (" tail", GodotValue::String(r#"say \"hi\""#.to_string()))
+        );
+        assert_eq!(
+            parse_godot_value(r#""C:\\dir\\""#).unwrap().1,
+            GodotValue::String(r#"C:\\dir\\"#.to_string())
+        );
+        assert_eq!(parse_godot_value(r#"&"a\"b""#).unwrap().1, GodotValue::StringName(r#"a\"b"#.to_string()));
+    }
+
+    #[test]
+    fn test_dictionary_spacing() {
+        let expected = GodotValue::Dictionary(vec![
+            ("a".to_string(), GodotValue::Integer(1)),
+            ("b".to_string(), GodotValue::Array(vec![GodotValue::Integer(2), GodotValue::Integer(3)])),
+        ]);
+        // Godot's own layout, then the inline one of hand-edited config files.
+        assert_eq!(parse_godot_value("{\n\"a\": 1,\n\"b\": [2, 3]\n}").unwrap().1, expected);
+        assert_eq!(parse_godot_value("{\"a\":1 , \"b\": [ 2,3 ]}").unwrap().1, expected);
+        assert_eq!(parse_godot_value("{}").unwrap().1, GodotValue::Dictionary(Vec::new()));
+    }
+}
@@ -0,0 +1,214 @@
+use godot_data::values::GodotValue;
+
+/// 2^53, above which doubles no longer hold every integer.
+const MAX_EXACT_INTEGER: f64 = 9007199254740992.0;
+
+fn write_float(value: f64) -> String {
+    if value.is_nan() {
+        "nan".to_string()
+    } else if value.is_infinite() {
+        if value > 0.0 { "inf" } else { "inf_neg" }.to_string()
+    } else if value.fract() == 0.0 {
+        format!("{:.1}", value)
+    } else {
+        format!("{}", value)
+    }
+}
+
+// Godot writes constructor components without the trailing `.0`, e.g. `Vector2(1, 2.5)`.
+// Only integers a double holds exactly take that form, larger ones would saturate in `i64`.
+fn write_component(value: f64) -> String {
+    if value.fract() == 0.0 && value.abs() < MAX_EXACT_INTEGER {
+        format!("{}", value as i64)
+    } else {
+        write_float(value)
+    }
+}
+
+fn write_component_f32(value: f32) -> String {
+    if value.is_finite() && value.fract() != 0.0 {
+        format!("{}", value)
+    } else {
+        write_component(value as f64)
+    }
+}
+
+fn write_floats(name: &str, values: &[f64]) -> String {
+    format!("{}({})", name, values.iter().map(|v| write_component(*v)).collect::<Vec<_>>().join(", "))
+}
+
+fn write_ints<T: ToString>(name: &str, values: &[T]) -> String {
+    format!("{}({})", name, values.iter().map(T::to_string).collect::<Vec<_>>().join(", "))
+}
+
+/// Writes a value in the text form `parse_godot_value` reads.
+pub fn write_godot_value(value: &GodotValue) -> String {
+    match value {
+        GodotValue::Null => "null".to_string(),
+        GodotValue::String(s) => format!("\"{}\"", s),
+        GodotValue::StringName(s) => format!("&\"{}\"", s),
+        GodotValue::Integer(i) => i.to_string(),
+        GodotValue::Float(f) => write_float(*f),
+        GodotValue::Boolean(b) => b.to_string(),
+        GodotValue::PackedStringArray(list) => format!(
+            "PackedStringArray({})",
+            list.iter().map(|s| format!("\"{}\"", s)).collect::<Vec<_>>().join(", ")
+        ),
+        GodotValue::NodePath(path) => format!("NodePath(\"{}\")", path),
+        GodotValue::Vector2((x, y)) => format!("Vector2({}, {})", write_component(*x), write_component(*y)),
+        GodotValue::Rect2((x, y, w, h)) => format!(
+            "Rect2({}, {}, {}, {})",
+            write_component(*x),
+            write_component(*y),
+            write_component(*w),
+            write_component(*h)
+        ),
+        GodotValue::Color((r, g, b, a)) => format!(
+            "Color({}, {}, {}, {})",
+            write_component(*r),
+            write_component(*g),
+            write_component(*b),
+            write_component(*a)
+        ),
+        GodotValue::ExtResourceLink(id) => format!("ExtResource(\"{}\")", id),
+        GodotValue::SubResourceLink(id) => format!("SubResource(\"{}\")", id),
+        GodotValue::Array(list) => format!(
+            "[{}]",
+            list.iter().map(write_godot_value).collect::<Vec<_>>().join(", ")
+        ),
+        GodotValue::Dictionary(entries) if entries.is_empty() => "{}".to_string(),
+        GodotValue::Dictionary(entries) => format!(
+            "{{\n{}\n}}",
+            entries
+                .iter()
+                .map(|(k, v)| format!("\"{}\": {}", k, write_godot_value(v)))
+                .collect::<Vec<_>>()
+                .join(",\n")
+        ),
+        GodotValue::Vector2i((x, y)) => write_ints("Vector2i", &[*x, *y]),
+        GodotValue::Rect2i((x, y, w, h)) => write_ints("Rect2i", &[*x, *y, *w, *h]),
+        GodotValue::Vector3((x, y, z)) => write_floats("Vector3", &[*x, *y, *z]),
+        GodotValue::Vector3i((x, y, z)) => write_ints("Vector3i", &[*x, *y, *z]),
+        GodotValue::Vector4((x, y, z, w)) => write_floats("Vector4", &[*x, *y, *z, *w]),
+        GodotValue::Vector4i((x, y, z, w)) => write_ints("Vector4i", &[*x, *y, *z, *w]),
+        GodotValue::Transform2D(v) => write_floats("Transform2D", v),
+        GodotValue::Plane((a, b, c, d)) => write_floats("Plane", &[*a, *b, *c, *d]),
+        GodotValue::Quaternion((x, y, z, w)) => write_floats("Quaternion", &[*x, *y, *z, *w]),
+        GodotValue::AABB(v) => write_floats("AABB", v),
+        GodotValue::Basis(v) => write_floats("Basis", v),
+        GodotValue::Transform3D(v) => write_floats("Transform3D", v),
+        GodotValue::Projection(v) => write_floats("Projection", v),
+        GodotValue::PackedByteArray(v) => write_ints("PackedByteArray", v),
+        GodotValue::PackedInt32Array(v) => write_ints("PackedInt32Array", v),
+        GodotValue::PackedInt64Array(v) => write_ints("PackedInt64Array", v),
+        GodotValue::PackedFloat32Array(v) => format!(
+            "PackedFloat32Array({})",
+            v.iter().map(|f| write_component_f32(*f)).collect::<Vec<_>>().join(", ")
+        ),
+        GodotValue::PackedFloat64Array(v) => write_floats("PackedFloat64Array", v),
+        GodotValue::PackedVector2Array(v) => {
+            write_floats("PackedVector2Array", &v.iter().flat_map(|(x, y)| [*x, *y]).collect::<Vec<_>>())
+        }
+        GodotValue::PackedVector3Array(v) => write_floats(
+            "PackedVector3Array",
+            &v.iter().flat_map(|(x, y, z)| [*x, *y, *z]).collect::<Vec<_>>(),
+        ),
+        GodotValue::PackedColorArray(v) => write_floats(
+            "PackedColorArray",
+            &v.iter().flat_map(|(r, g, b, a)| [*r, *g, *b, *a]).collect::<Vec<_>>(),
+        ),
+        GodotValue::PackedVector4Array(v) => write_floats(
+            "PackedVector4Array",
+            &v.iter().flat_map(|(x, y, z, w)| [*x, *y, *z, *w]).collect::<Vec<_>>(),
+        ),
+        GodotValue::Object((class, properties)) => format!(
+            "Object({}{})",
+            class,
+            properties
+                .iter()
+                .map(|(k, v)| format!(",\"{}\":{}", k, write_godot_value(v)))
+                .collect::<String>()
+        ),
+    }
+}
+
+/// Escapes a string the way Godot writes it between quotes (`\\` and `\"`).
+pub fn escape_string(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Reverses [`escape_string`] and the other C escapes Godot accepts in text resources.
+pub fn unescape_string(s: &str) -> String {
+    let mut out = String::with_capacity(s.len());
+    let mut chars = s.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            out.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => out.push('\n'),
+            Some('t') => out.push('\t'),
+            Some('r') => out.push('\r'),
+            Some(other) => out.push(other),
+            None => out.push('\\'),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use godot_data::values::GodotValue;
+    use crate::data::values::parse_godot_value;
+    use super::*;
+
+    #[test]
+    fn test_write_godot_value() {
+        assert_eq!(write_godot_value(&GodotValue::Float(2.0)), "2.0");
+        assert_eq!(write_godot_value(&GodotValue::Float(f64::NEG_INFINITY)), "inf_neg");
+        assert_eq!(write_godot_value(&GodotValue::Vector2((1.0, 2.5))), "Vector2(1, 2.5)");
+        assert_eq!(
+            write_godot_value(&GodotValue::Dictionary(vec![("a".to_string(), GodotValue::Null)])),
+            "{\n\"a\": null\n}"
+        );
+        assert_eq!(
+            write_godot_value(&GodotValue::Object(("Resource".to_string(), vec![("k".to_string(), GodotValue::Integer(1))]))),
+            "Object(Resource,\"k\":1)"
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let values = [
+            GodotValue::Null,
+            GodotValue::String(r#"a \"b\""#.to_string()),
+            GodotValue::StringName("name".to_string()),
+            GodotValue::Integer(-3),
+            GodotValue::Float(0.25),
+            GodotValue::Boolean(true),
+            GodotValue::PackedStringArray(vec!["x".to_string(), "y".to_string()]),
+            GodotValue::NodePath("../Player:position".to_string()),
+            GodotValue::Rect2((1.0, 2.0, 3.5, 4.0)),
+            GodotValue::Color((1.0, 0.5, 0.0, 1.0)),
+            GodotValue::ExtResourceLink("1_abc".to_string()),
+            GodotValue::SubResourceLink("Res_2".to_string()),
+            GodotValue::Array(vec![GodotValue::Integer(1), GodotValue::Dictionary(Vec::new())]),
+            GodotValue::Vector3i((1, -2, 3)),
+            GodotValue::Quaternion((0.0, 0.0, 0.0, 1.0)),
+            GodotValue::PackedFloat32Array(vec![0.5, 1.0]),
+            GodotValue::PackedColorArray(vec![(1.0, 1.0, 1.0, 0.5)]),
+        ];
+        for value in values {
+            let written = write_godot_value(&value);
+            assert_eq!(parse_godot_value(&written).unwrap().1, value, "{}", written);
+        }
+    }
+
+    #[test]
+    fn test_escape_string() {
+        assert_eq!(escape_string(r#"a"b\c"#), r#"a\"b\\c"#);
+        assert_eq!(unescape_string(r#"a\"b\\c"#), r#"a"b\c"#);
+        assert_eq!(unescape_string(r"line\nnext\ttab"), "line\nnext\ttab");
+    }
+}
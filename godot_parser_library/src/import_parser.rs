@@ -0,0 +1,92 @@
+use nom::IResult;
+use godot_data::import_file::ImportFile;
+use godot_data::values::GodotValue;
+use crate::config_parser::{fail, parse_config_file, take_string, take_string_list};
+
+pub fn parse_import_file(input: &str) -> IResult<&str, ImportFile> {
+    let (remain, config) = parse_config_file(input)?;
+    let mut sections = config.sections;
+
+    let mut remap = sections.remove("remap").unwrap_or_default();
+    let mut deps = sections.remove("deps").unwrap_or_default();
+    let params = sections.remove("params").unwrap_or_default();
+
+    let Some(importer) = take_string(&mut remap, "importer") else {
+        return fail(input);
+    };
+    let platform_paths = remap
+        .iter()
+        .filter_map(|(key, value)| match (key.strip_prefix("path."), value) {
+            (Some(feature), GodotValue::String(path)) => Some((feature.to_string(), path.clone())),
+            _ => None,
+        })
+        .collect();
+
+    Ok((
+        remain,
+        ImportFile {
+            importer,
+            _type: take_string(&mut remap, "type"),
+            uid: take_string(&mut remap, "uid"),
+            path: take_string(&mut remap, "path"),
+            platform_paths,
+            source_file: take_string(&mut deps, "source_file"),
+            dest_files: take_string_list(&mut deps, "dest_files"),
+            params,
+        },
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use godot_data::values::GodotValue;
+    use super::*;
+
+    #[test]
+    fn test_parse_import() {
+        let input = r#"[remap]
+
+importer="texture"
+type="CompressedTexture2D"
+uid="uid://bqov4kuchixhi"
+path.s3tc="res://.godot/imported/icons.png-b3e1.s3tc.ctex"
+path.etc2="res://.godot/imported/icons.png-b3e1.etc2.ctex"
+metadata={
+"imported_formats": ["s3tc_bptc", "etc2_astc"],
+"vram_texture": true
+}
+
+[deps]
+
+source_file="res://atlases/icons.png"
+dest_files=["res://.godot/imported/icons.png-b3e1.s3tc.ctex", "res://.godot/imported/icons.png-b3e1.etc2.ctex"]
+
+[params]
+
+compress/mode=2
+compress/high_quality=false
+compress/lossy_quality=0.7
+mipmaps/generate=true
+mipmaps/limit=-1
+roughness/src_normal=""
+"#;
+        let (_, import) = parse_import_file(input).unwrap();
+        assert_eq!(import.importer, "texture");
+        assert_eq!(import._type.as_deref(), Some("CompressedTexture2D"));
+        assert_eq!(import.uid.as_deref(), Some("uid://bqov4kuchixhi"));
+        assert_eq!(import.path, None);
+        assert_eq!(
+            import.platform_paths.get("s3tc").map(String::as_str),
+            Some("res://.godot/imported/icons.png-b3e1.s3tc.ctex")
+        );
+        assert_eq!(import.source_file.as_deref(), Some("res://atlases/icons.png"));
+        assert_eq!(import.dest_files.len(), 2);
+        assert!(matches!(import.params.get("compress/mode"), Some(GodotValue::Integer(2))));
+        assert!(matches!(import.params.get("mipmaps/generate"), Some(GodotValue::Boolean(true))));
+    }
+
+    #[test]
+    fn test_parse_import_without_importer() {
+        assert!(parse_import_file("[deps]\n\nsource_file=\"res://a.png\"\n").is_err());
+    }
+}
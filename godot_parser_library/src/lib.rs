@@ -1,3 +1,10 @@
 pub mod project_parser;
 mod data;
-pub mod tscn_tres_parser;
\ No newline at end of file
+pub mod tscn_tres_parser;
+pub mod project_paths;
+pub mod import_parser;
+pub mod config_parser;
+pub mod uid;
+pub mod rsrc_parser;
+pub mod marshalls;
+pub mod pck_parser;
\ No newline at end of file
@@ -0,0 +1,687 @@
+//! Godot's `encode_variant`/`decode_variant` binary format, as used by `var_to_bytes`,
+//! `FileAccess.store_var`, multiplayer and `project.binary`. Values are little endian and
+//! padded to 4 bytes.
+
+use nom::bytes::complete::take;
+use nom::error::ParseError;
+use nom::multi::count;
+use nom::number::complete::{le_f32, le_f64, le_i32, le_i64, le_u32, le_u64, u8};
+use nom::IResult;
+use godot_data::values::GodotValue;
+use crate::data::writer::{escape_string, unescape_string};
+use crate::rsrc_parser::{put_f32, put_i32, put_u32, widen};
+
+pub(crate) const TYPE_NIL: u32 = 0;
+pub(crate) const TYPE_BOOL: u32 = 1;
+pub(crate) const TYPE_INT: u32 = 2;
+pub(crate) const TYPE_FLOAT: u32 = 3;
+pub(crate) const TYPE_STRING: u32 = 4;
+pub(crate) const TYPE_VECTOR2: u32 = 5;
+pub(crate) const TYPE_VECTOR2I: u32 = 6;
+pub(crate) const TYPE_RECT2: u32 = 7;
+pub(crate) const TYPE_RECT2I: u32 = 8;
+pub(crate) const TYPE_VECTOR3: u32 = 9;
+pub(crate) const TYPE_VECTOR3I: u32 = 10;
+pub(crate) const TYPE_TRANSFORM2D: u32 = 11;
+pub(crate) const TYPE_VECTOR4: u32 = 12;
+pub(crate) const TYPE_VECTOR4I: u32 = 13;
+pub(crate) const TYPE_PLANE: u32 = 14;
+pub(crate) const TYPE_QUATERNION: u32 = 15;
+pub(crate) const TYPE_AABB: u32 = 16;
+pub(crate) const TYPE_BASIS: u32 = 17;
+pub(crate) const TYPE_TRANSFORM3D: u32 = 18;
+pub(crate) const TYPE_PROJECTION: u32 = 19;
+pub(crate) const TYPE_COLOR: u32 = 20;
+pub(crate) const TYPE_STRING_NAME: u32 = 21;
+pub(crate) const TYPE_NODE_PATH: u32 = 22;
+pub(crate) const TYPE_RID: u32 = 23;
+pub(crate) const TYPE_OBJECT: u32 = 24;
+pub(crate) const TYPE_CALLABLE: u32 = 25;
+pub(crate) const TYPE_SIGNAL: u32 = 26;
+pub(crate) const TYPE_DICTIONARY: u32 = 27;
+pub(crate) const TYPE_ARRAY: u32 = 28;
+pub(crate) const TYPE_PACKED_BYTE_ARRAY: u32 = 29;
+pub(crate) const TYPE_PACKED_INT32_ARRAY: u32 = 30;
+pub(crate) const TYPE_PACKED_INT64_ARRAY: u32 = 31;
+pub(crate) const TYPE_PACKED_FLOAT32_ARRAY: u32 = 32;
+pub(crate) const TYPE_PACKED_FLOAT64_ARRAY: u32 = 33;
+pub(crate) const TYPE_PACKED_STRING_ARRAY: u32 = 34;
+pub(crate) const TYPE_PACKED_VECTOR2_ARRAY: u32 = 35;
+pub(crate) const TYPE_PACKED_VECTOR3_ARRAY: u32 = 36;
+pub(crate) const TYPE_PACKED_COLOR_ARRAY: u32 = 37;
+pub(crate) const TYPE_PACKED_VECTOR4_ARRAY: u32 = 38;
+
+pub(crate) const HEADER_TYPE_MASK: u32 = 0xFF;
+/// 64-bit integers, doubles and double precision reals.
+pub(crate) const HEADER_DATA_FLAG_64: u32 = 1 << 16;
+pub(crate) const HEADER_DATA_FLAG_OBJECT_AS_ID: u32 = 1 << 16;
+// Typed arrays and dictionaries store their element types after the header.
+pub(crate) const HEADER_TYPED_ARRAY_SHIFT: u32 = 16;
+pub(crate) const HEADER_TYPED_DICTIONARY_KEY_SHIFT: u32 = 16;
+pub(crate) const HEADER_TYPED_DICTIONARY_VALUE_SHIFT: u32 = 18;
+pub(crate) const CONTAINER_TYPE_NONE: u32 = 0;
+pub(crate) const CONTAINER_TYPE_BUILTIN: u32 = 1;
+
+const NODE_PATH_NEW_FORMAT: u32 = 0x80000000;
+const NODE_PATH_FLAG_ABSOLUTE: u32 = 1;
+const NODE_PATH_FLAG_PROPERTY: u32 = 2;
+const SHARED_FLAG_MASK: u32 = 0x7FFFFFFF;
+
+fn fail<T>(input: &[u8]) -> IResult<&[u8], T> {
+    Err(nom::Err::Error(nom::error::Error::from_error_kind(
+        input,
+        nom::error::ErrorKind::Fail,
+    )))
+}
+
+fn padding(len: usize) -> usize {
+    (4 - len % 4) % 4
+}
+
+fn string(input: &[u8]) -> IResult<&[u8], String> {
+    let (input, len) = le_u32(input)?;
+    let (input, bytes) = take(len as usize)(input)?;
+    let (input, _) = take(padding(len as usize))(input)?;
+    Ok((input, String::from_utf8_lossy(bytes).into_owned()))
+}
+
+fn real(input: &[u8], wide: bool) -> IResult<&[u8], f64> {
+    if wide {
+        le_f64(input)
+    } else {
+        let (input, value) = le_f32(input)?;
+        Ok((input, widen(value)))
+    }
+}
+
+fn reals<const N: usize>(input: &[u8], wide: bool) -> IResult<&[u8], [f64; N]> {
+    let mut out = [0.0; N];
+    let mut input = input;
+    for value in out.iter_mut() {
+        (input, *value) = real(input, wide)?;
+    }
+    Ok((input, out))
+}
+
+fn i32s<const N: usize>(input: &[u8]) -> IResult<&[u8], [i32; N]> {
+    let mut out = [0; N];
+    let mut input = input;
+    for value in out.iter_mut() {
+        (input, *value) = le_i32(input)?;
+    }
+    Ok((input, out))
+}
+
+/// Skips the element type of a typed array or dictionary: a builtin type, class name or script path.
+fn container_type(input: &[u8], kind: u32) -> IResult<&[u8], ()> {
+    match kind {
+        CONTAINER_TYPE_NONE => Ok((input, ())),
+        CONTAINER_TYPE_BUILTIN => le_u32(input).map(|(input, _)| (input, ())),
+        _ => string(input).map(|(input, _)| (input, ())),
+    }
+}
+
+fn node_path(input: &[u8]) -> IResult<&[u8], String> {
+    let (remain, name_count) = le_u32(input)?;
+    if name_count & NODE_PATH_NEW_FORMAT == 0 {
+        return fail(input);
+    }
+    let (remain, mut subname_count) = le_u32(remain)?;
+    let (remain, flags) = le_u32(remain)?;
+    if flags & NODE_PATH_FLAG_PROPERTY != 0 {
+        subname_count += 1;
+    }
+    let (remain, names) = count(string, (name_count & SHARED_FLAG_MASK) as usize)(remain)?;
+    let (remain, subnames) = count(string, subname_count as usize)(remain)?;
+    let mut path = if flags & NODE_PATH_FLAG_ABSOLUTE != 0 { "/".to_string() } else { String::new() };
+    path.push_str(&names.join("/"));
+    for subname in subnames {
+        path.push(':');
+        path.push_str(&subname);
+    }
+    Ok((remain, path))
+}
+
+fn properties(input: &[u8], allow_objects: bool) -> IResult<&[u8], Vec<(String, GodotValue)>> {
+    let (input, len) = le_u32(input)?;
+    count(
+        |i| {
+            let (i, name) = string(i)?;
+            let (i, value) = decode_variant(i, allow_objects)?;
+            Ok((i, (name, value)))
+        },
+        len as usize,
+    )(input)
+}
+
+/// Decodes one value. Objects sent in full decode to [`GodotValue::Object`] when `allow_objects`
+/// is set and fail otherwise, like Godot's `allow_objects`; objects sent as instance ids decode
+/// to `Null`, as do RIDs, callables and signals.
+pub fn decode_variant(input: &[u8], allow_objects: bool) -> IResult<&[u8], GodotValue> {
+    let (remain, header) = le_u32(input)?;
+    let wide = header & HEADER_DATA_FLAG_64 != 0;
+    match header & HEADER_TYPE_MASK {
+        TYPE_NIL | TYPE_CALLABLE => Ok((remain, GodotValue::Null)),
+        TYPE_BOOL => {
+            let (remain, value) = le_u32(remain)?;
+            Ok((remain, GodotValue::Boolean(value != 0)))
+        }
+        TYPE_INT if wide => {
+            let (remain, value) = le_i64(remain)?;
+            Ok((remain, GodotValue::Integer(value)))
+        }
+        TYPE_INT => {
+            let (remain, value) = le_i32(remain)?;
+            Ok((remain, GodotValue::Integer(value as i64)))
+        }
+        TYPE_FLOAT => {
+            let (remain, value) = real(remain, wide)?;
+            Ok((remain, GodotValue::Float(value)))
+        }
+        TYPE_STRING => {
+            let (remain, value) = string(remain)?;
+            Ok((remain, GodotValue::String(escape_string(&value))))
+        }
+        TYPE_STRING_NAME => {
+            let (remain, value) = string(remain)?;
+            Ok((remain, GodotValue::StringName(escape_string(&value))))
+        }
+        TYPE_VECTOR2 => {
+            let (remain, [x, y]) = reals(remain, wide)?;
+            Ok((remain, GodotValue::Vector2((x, y))))
+        }
+        TYPE_VECTOR2I => {
+            let (remain, [x, y]) = i32s(remain)?;
+            Ok((remain, GodotValue::Vector2i((x, y))))
+        }
+        TYPE_RECT2 => {
+            let (remain, [x, y, w, h]) = reals(remain, wide)?;
+            Ok((remain, GodotValue::Rect2((x, y, w, h))))
+        }
+        TYPE_RECT2I => {
+            let (remain, [x, y, w, h]) = i32s(remain)?;
+            Ok((remain, GodotValue::Rect2i((x, y, w, h))))
+        }
+        TYPE_VECTOR3 => {
+            let (remain, [x, y, z]) = reals(remain, wide)?;
+            Ok((remain, GodotValue::Vector3((x, y, z))))
+        }
+        TYPE_VECTOR3I => {
+            let (remain, [x, y, z]) = i32s(remain)?;
+            Ok((remain, GodotValue::Vector3i((x, y, z))))
+        }
+        TYPE_VECTOR4 => {
+            let (remain, [x, y, z, w]) = reals(remain, wide)?;
+            Ok((remain, GodotValue::Vector4((x, y, z, w))))
+        }
+        TYPE_VECTOR4I => {
+            let (remain, [x, y, z, w]) = i32s(remain)?;
+            Ok((remain, GodotValue::Vector4i((x, y, z, w))))
+        }
+        TYPE_PLANE => {
+            let (remain, [a, b, c, d]) = reals(remain, wide)?;
+            Ok((remain, GodotValue::Plane((a, b, c, d))))
+        }
+        TYPE_QUATERNION => {
+            let (remain, [x, y, z, w]) = reals(remain, wide)?;
+            Ok((remain, GodotValue::Quaternion((x, y, z, w))))
+        }
+        TYPE_TRANSFORM2D => {
+            let (remain, values) = reals(remain, wide)?;
+            Ok((remain, GodotValue::Transform2D(values)))
+        }
+        TYPE_AABB => {
+            let (remain, values) = reals(remain, wide)?;
+            Ok((remain, GodotValue::AABB(values)))
+        }
+        TYPE_BASIS => {
+            let (remain, values) = reals(remain, wide)?;
+            Ok((remain, GodotValue::Basis(values)))
+        }
+        TYPE_TRANSFORM3D => {
+            let (remain, values) = reals(remain, wide)?;
+            Ok((remain, GodotValue::Transform3D(values)))
+        }
+        TYPE_PROJECTION => {
+            let (remain, values) = reals(remain, wide)?;
+            Ok((remain, GodotValue::Projection(values)))
+        }
+        TYPE_COLOR => {
+            let (remain, [r, g, b, a]) = reals(remain, false)?;
+            Ok((remain, GodotValue::Color((r, g, b, a))))
+        }
+        TYPE_NODE_PATH => {
+            let (remain, path) = node_path(remain)?;
+            Ok((remain, GodotValue::NodePath(escape_string(&path))))
+        }
+        TYPE_RID => {
+            let (remain, _) = le_u64(remain)?;
+            Ok((remain, GodotValue::Null))
+        }
+        TYPE_OBJECT if header & HEADER_DATA_FLAG_OBJECT_AS_ID != 0 => {
+            let (remain, _) = le_u64(remain)?;
+            Ok((remain, GodotValue::Null))
+        }
+        TYPE_OBJECT if !allow_objects => fail(input),
+        TYPE_OBJECT => {
+            let (remain, class) = string(remain)?;
+            if class.is_empty() {
+                return Ok((remain, GodotValue::Null));
+            }
+            let (remain, properties) = properties(remain, allow_objects)?;
+            Ok((remain, GodotValue::Object((class, properties))))
+        }
+        TYPE_SIGNAL => {
+            let (remain, _) = string(remain)?;
+            let (remain, _) = le_u64(remain)?;
+            Ok((remain, GodotValue::Null))
+        }
+        TYPE_DICTIONARY => {
+            let (remain, _) = container_type(remain, (header >> HEADER_TYPED_DICTIONARY_KEY_SHIFT) & 0b11)?;
+            let (remain, _) = container_type(remain, (header >> HEADER_TYPED_DICTIONARY_VALUE_SHIFT) & 0b11)?;
+            let (remain, len) = le_u32(remain)?;
+            let (remain, entries) = count(
+                |i| {
+                    let (remain, key) = decode_variant(i, allow_objects)?;
+                    // Dictionaries only have text keys, writing others as text would mix up `1` and `"1"`.
+                    let key = match key {
+                        GodotValue::String(s) | GodotValue::StringName(s) => s,
+                        _ => return fail(i),
+                    };
+                    let (remain, value) = decode_variant(remain, allow_objects)?;
+                    Ok((remain, (key, value)))
+                },
+                (len & SHARED_FLAG_MASK) as usize,
+            )(remain)?;
+            Ok((remain, GodotValue::Dictionary(entries)))
+        }
+        TYPE_ARRAY => {
+            let (remain, _) = container_type(remain, (header >> HEADER_TYPED_ARRAY_SHIFT) & 0b11)?;
+            let (remain, len) = le_u32(remain)?;
+            let (remain, values) = count(|i| decode_variant(i, allow_objects), (len & SHARED_FLAG_MASK) as usize)(remain)?;
+            Ok((remain, GodotValue::Array(values)))
+        }
+        TYPE_PACKED_BYTE_ARRAY => {
+            let (remain, len) = le_u32(remain)?;
+            let (remain, bytes) = count(u8, len as usize)(remain)?;
+            let (remain, _) = take(padding(len as usize))(remain)?;
+            Ok((remain, GodotValue::PackedByteArray(bytes)))
+        }
+        TYPE_PACKED_INT32_ARRAY => {
+            let (remain, len) = le_u32(remain)?;
+            let (remain, values) = count(le_i32, len as usize)(remain)?;
+            Ok((remain, GodotValue::PackedInt32Array(values)))
+        }
+        TYPE_PACKED_INT64_ARRAY => {
+            let (remain, len) = le_u32(remain)?;
+            let (remain, values) = count(le_i64, len as usize)(remain)?;
+            Ok((remain, GodotValue::PackedInt64Array(values)))
+        }
+        TYPE_PACKED_FLOAT32_ARRAY => {
+            let (remain, len) = le_u32(remain)?;
+            let (remain, values) = count(le_f32, len as usize)(remain)?;
+            Ok((remain, GodotValue::PackedFloat32Array(values)))
+        }
+        TYPE_PACKED_FLOAT64_ARRAY => {
+            let (remain, len) = le_u32(remain)?;
+            let (remain, values) = count(le_f64, len as usize)(remain)?;
+            Ok((remain, GodotValue::PackedFloat64Array(values)))
+        }
+        TYPE_PACKED_STRING_ARRAY => {
+            let (remain, len) = le_u32(remain)?;
+            let (remain, values) = count(string, len as usize)(remain)?;
+            Ok((remain, GodotValue::PackedStringArray(values.iter().map(|s| escape_string(s)).collect())))
+        }
+        TYPE_PACKED_VECTOR2_ARRAY => {
+            let (remain, len) = le_u32(remain)?;
+            let (remain, values) = count(|i| reals::<2>(i, wide), len as usize)(remain)?;
+            Ok((remain, GodotValue::PackedVector2Array(values.into_iter().map(|[x, y]| (x, y)).collect())))
+        }
+        TYPE_PACKED_VECTOR3_ARRAY => {
+            let (remain, len) = le_u32(remain)?;
+            let (remain, values) = count(|i| reals::<3>(i, wide), len as usize)(remain)?;
+            Ok((remain, GodotValue::PackedVector3Array(values.into_iter().map(|[x, y, z]| (x, y, z)).collect())))
+        }
+        TYPE_PACKED_VECTOR4_ARRAY => {
+            let (remain, len) = le_u32(remain)?;
+            let (remain, values) = count(|i| reals::<4>(i, wide), len as usize)(remain)?;
+            Ok((remain, GodotValue::PackedVector4Array(values.into_iter().map(|[x, y, z, w]| (x, y, z, w)).collect())))
+        }
+        TYPE_PACKED_COLOR_ARRAY => {
+            let (remain, len) = le_u32(remain)?;
+            let (remain, values) = count(|i| reals::<4>(i, false), len as usize)(remain)?;
+            Ok((remain, GodotValue::PackedColorArray(values.into_iter().map(|[r, g, b, a]| (r, g, b, a)).collect())))
+        }
+        _ => fail(input),
+    }
+}
+
+fn put_string(out: &mut Vec<u8>, value: &str) {
+    put_u32(out, value.len() as u32);
+    out.extend(value.as_bytes());
+    out.extend(std::iter::repeat_n(0, padding(value.len())));
+}
+
+fn put_reals(out: &mut Vec<u8>, values: &[f64]) {
+    values.iter().for_each(|v| put_f32(out, *v));
+}
+
+fn put_node_path(out: &mut Vec<u8>, path: &str) {
+    let path = unescape_string(path);
+    let (names, subnames) = match path.split_once(':') {
+        Some((names, subnames)) => (names, subnames.split(':').collect::<Vec<_>>()),
+        None => (path.as_str(), Vec::new()),
+    };
+    let absolute = names.starts_with('/');
+    let names = names.split('/').filter(|n| !n.is_empty()).collect::<Vec<_>>();
+    put_u32(out, names.len() as u32 | NODE_PATH_NEW_FORMAT);
+    put_u32(out, subnames.len() as u32);
+    put_u32(out, if absolute { NODE_PATH_FLAG_ABSOLUTE } else { 0 });
+    names.into_iter().chain(subnames).for_each(|name| put_string(out, name));
+}
+
+/// Appends the encoding of `value` to `out`. Objects are written with their class and properties
+/// when `full_objects` is set; otherwise, lacking an instance id to send, they fail to encode.
+/// Resource links fail too, as they only mean something inside the file that declares them.
+pub fn encode_variant(out: &mut Vec<u8>, value: &GodotValue, full_objects: bool) -> Option<()> {
+    match value {
+        GodotValue::Null => put_u32(out, TYPE_NIL),
+        GodotValue::Boolean(value) => {
+            put_u32(out, TYPE_BOOL);
+            put_u32(out, *value as u32);
+        }
+        GodotValue::Integer(value) => match i32::try_from(*value) {
+            Ok(value) => {
+                put_u32(out, TYPE_INT);
+                put_i32(out, value);
+            }
+            Err(_) => {
+                put_u32(out, TYPE_INT | HEADER_DATA_FLAG_64);
+                out.extend(value.to_le_bytes());
+            }
+        },
+        GodotValue::Float(value) => {
+            if (*value as f32) as f64 == *value {
+                put_u32(out, TYPE_FLOAT);
+                put_f32(out, *value);
+            } else {
+                put_u32(out, TYPE_FLOAT | HEADER_DATA_FLAG_64);
+                out.extend(value.to_le_bytes());
+            }
+        }
+        GodotValue::String(value) => {
+            put_u32(out, TYPE_STRING);
+            put_string(out, &unescape_string(value));
+        }
+        GodotValue::StringName(value) => {
+            put_u32(out, TYPE_STRING_NAME);
+            put_string(out, &unescape_string(value));
+        }
+        GodotValue::NodePath(path) => {
+            put_u32(out, TYPE_NODE_PATH);
+            put_node_path(out, path);
+        }
+        GodotValue::Vector2((x, y)) => {
+            put_u32(out, TYPE_VECTOR2);
+            put_reals(out, &[*x, *y]);
+        }
+        GodotValue::Vector2i((x, y)) => {
+            put_u32(out, TYPE_VECTOR2I);
+            [*x, *y].into_iter().for_each(|v| put_i32(out, v));
+        }
+        GodotValue::Rect2((x, y, w, h)) => {
+            put_u32(out, TYPE_RECT2);
+            put_reals(out, &[*x, *y, *w, *h]);
+        }
+        GodotValue::Rect2i((x, y, w, h)) => {
+            put_u32(out, TYPE_RECT2I);
+            [*x, *y, *w, *h].into_iter().for_each(|v| put_i32(out, v));
+        }
+        GodotValue::Vector3((x, y, z)) => {
+            put_u32(out, TYPE_VECTOR3);
+            put_reals(out, &[*x, *y, *z]);
+        }
+        GodotValue::Vector3i((x, y, z)) => {
+            put_u32(out, TYPE_VECTOR3I);
+            [*x, *y, *z].into_iter().for_each(|v| put_i32(out, v));
+        }
+        GodotValue::Vector4((x, y, z, w)) => {
+            put_u32(out, TYPE_VECTOR4);
+            put_reals(out, &[*x, *y, *z, *w]);
+        }
+        GodotValue::Vector4i((x, y, z, w)) => {
+            put_u32(out, TYPE_VECTOR4I);
+            [*x, *y, *z, *w].into_iter().for_each(|v| put_i32(out, v));
+        }
+        GodotValue::Plane((a, b, c, d)) => {
+            put_u32(out, TYPE_PLANE);
+            put_reals(out, &[*a, *b, *c, *d]);
+        }
+        GodotValue::Quaternion((x, y, z, w)) => {
+            put_u32(out, TYPE_QUATERNION);
+            put_reals(out, &[*x, *y, *z, *w]);
+        }
+        GodotValue::Transform2D(values) => {
+            put_u32(out, TYPE_TRANSFORM2D);
+            put_reals(out, values);
+        }
+        GodotValue::AABB(values) => {
+            put_u32(out, TYPE_AABB);
+            put_reals(out, values);
+        }
+        GodotValue::Basis(values) => {
+            put_u32(out, TYPE_BASIS);
+            put_reals(out, values);
+        }
+        GodotValue::Transform3D(values) => {
+            put_u32(out, TYPE_TRANSFORM3D);
+            put_reals(out, values);
+        }
+        GodotValue::Projection(values) => {
+            put_u32(out, TYPE_PROJECTION);
+            put_reals(out, values);
+        }
+        GodotValue::Color((r, g, b, a)) => {
+            put_u32(out, TYPE_COLOR);
+            put_reals(out, &[*r, *g, *b, *a]);
+        }
+        GodotValue::ExtResourceLink(_) | GodotValue::SubResourceLink(_) => return None,
+        GodotValue::Object(_) if !full_objects => return None,
+        GodotValue::Object((class, properties)) => {
+            put_u32(out, TYPE_OBJECT);
+            put_string(out, class);
+            put_u32(out, properties.len() as u32);
+            for (name, value) in properties {
+                put_string(out, name);
+                encode_variant(out, value, full_objects)?;
+            }
+        }
+        GodotValue::Dictionary(entries) => {
+            put_u32(out, TYPE_DICTIONARY);
+            put_u32(out, entries.len() as u32);
+            for (key, value) in entries {
+                put_u32(out, TYPE_STRING);
+                put_string(out, &unescape_string(key));
+                encode_variant(out, value, full_objects)?;
+            }
+        }
+        GodotValue::Array(values) => {
+            put_u32(out, TYPE_ARRAY);
+            put_u32(out, values.len() as u32);
+            for value in values {
+                encode_variant(out, value, full_objects)?;
+            }
+        }
+        GodotValue::PackedByteArray(values) => {
+            put_u32(out, TYPE_PACKED_BYTE_ARRAY);
+            put_u32(out, values.len() as u32);
+            out.extend(values);
+            out.extend(std::iter::repeat_n(0, padding(values.len())));
+        }
+        GodotValue::PackedInt32Array(values) => {
+            put_u32(out, TYPE_PACKED_INT32_ARRAY);
+            put_u32(out, values.len() as u32);
+            values.iter().for_each(|v| put_i32(out, *v));
+        }
+        GodotValue::PackedInt64Array(values) => {
+            put_u32(out, TYPE_PACKED_INT64_ARRAY);
+            put_u32(out, values.len() as u32);
+            values.iter().for_each(|v| out.extend(v.to_le_bytes()));
+        }
+        GodotValue::PackedFloat32Array(values) => {
+            put_u32(out, TYPE_PACKED_FLOAT32_ARRAY);
+            put_u32(out, values.len() as u32);
+            values.iter().for_each(|v| out.extend(v.to_le_bytes()));
+        }
+        GodotValue::PackedFloat64Array(values) => {
+            put_u32(out, TYPE_PACKED_FLOAT64_ARRAY);
+            put_u32(out, values.len() as u32);
+            values.iter().for_each(|v| out.extend(v.to_le_bytes()));
+        }
+        GodotValue::PackedStringArray(values) => {
+            put_u32(out, TYPE_PACKED_STRING_ARRAY);
+            put_u32(out, values.len() as u32);
+            values.iter().for_each(|v| put_string(out, &unescape_string(v)));
+        }
+        GodotValue::PackedVector2Array(values) => {
+            put_u32(out, TYPE_PACKED_VECTOR2_ARRAY);
+            put_u32(out, values.len() as u32);
+            values.iter().for_each(|(x, y)| put_reals(out, &[*x, *y]));
+        }
+        GodotValue::PackedVector3Array(values) => {
+            put_u32(out, TYPE_PACKED_VECTOR3_ARRAY);
+            put_u32(out, values.len() as u32);
+            values.iter().for_each(|(x, y, z)| put_reals(out, &[*x, *y, *z]));
+        }
+        GodotValue::PackedVector4Array(values) => {
+            put_u32(out, TYPE_PACKED_VECTOR4_ARRAY);
+            put_u32(out, values.len() as u32);
+            values.iter().for_each(|(x, y, z, w)| put_reals(out, &[*x, *y, *z, *w]));
+        }
+        GodotValue::PackedColorArray(values) => {
+            put_u32(out, TYPE_PACKED_COLOR_ARRAY);
+            put_u32(out, values.len() as u32);
+            values.iter().for_each(|(r, g, b, a)| put_reals(out, &[*r, *g, *b, *a]));
+        }
+    }
+    Some(())
+}
+
+/// Godot's `var_to_bytes`.
+pub fn var_to_bytes(value: &GodotValue) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    encode_variant(&mut out, value, false)?;
+    Some(out)
+}
+
+/// Godot's `var_to_bytes_with_objects`.
+pub fn var_to_bytes_with_objects(value: &GodotValue) -> Option<Vec<u8>> {
+    let mut out = Vec::new();
+    encode_variant(&mut out, value, true)?;
+    Some(out)
+}
+
+/// Godot's `bytes_to_var`.
+pub fn bytes_to_var(input: &[u8]) -> IResult<&[u8], GodotValue> {
+    decode_variant(input, false)
+}
+
+/// Godot's `bytes_to_var_with_objects`.
+pub fn bytes_to_var_with_objects(input: &[u8]) -> IResult<&[u8], GodotValue> {
+    decode_variant(input, true)
+}
+
+/// `FileAccess.store_var`: the encoded value prefixed with its length.
+pub fn store_var(out: &mut Vec<u8>, value: &GodotValue, full_objects: bool) -> Option<()> {
+    let mut encoded = Vec::new();
+    encode_variant(&mut encoded, value, full_objects)?;
+    put_u32(out, encoded.len() as u32);
+    out.extend(encoded);
+    Some(())
+}
+
+/// `FileAccess.get_var`, reading back what [`store_var`] wrote.
+pub fn get_var(input: &[u8], allow_objects: bool) -> IResult<&[u8], GodotValue> {
+    let (input, len) = le_u32(input)?;
+    let (input, encoded) = take(len as usize)(input)?;
+    let (_, value) = decode_variant(encoded, allow_objects)?;
+    Ok((input, value))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_var_to_bytes() {
+        assert_eq!(var_to_bytes(&GodotValue::Integer(1)).unwrap(), [2, 0, 0, 0, 1, 0, 0, 0]);
+        assert_eq!(
+            var_to_bytes(&GodotValue::Integer(1 << 40)).unwrap(),
+            [2, 0, 1, 0, 0, 0, 0, 0, 0, 1, 0, 0]
+        );
+        assert_eq!(var_to_bytes(&GodotValue::Float(1.5)).unwrap(), [3, 0, 0, 0, 0, 0, 0xc0, 0x3f]);
+        assert_eq!(&var_to_bytes(&GodotValue::Float(0.1)).unwrap()[..4], [3, 0, 1, 0]);
+        assert_eq!(
+            var_to_bytes(&GodotValue::String("hi".to_string())).unwrap(),
+            [4, 0, 0, 0, 2, 0, 0, 0, b'h', b'i', 0, 0]
+        );
+        assert_eq!(
+            var_to_bytes(&GodotValue::Vector2((1.0, 2.0))).unwrap(),
+            [5, 0, 0, 0, 0, 0, 0x80, 0x3f, 0, 0, 0, 0x40]
+        );
+        assert_eq!(
+            var_to_bytes(&GodotValue::NodePath("/root:position".to_string())).unwrap(),
+            [22, 0, 0, 0, 1, 0, 0, 0x80, 1, 0, 0, 0, 1, 0, 0, 0, 4, 0, 0, 0, b'r', b'o', b'o', b't',
+                8, 0, 0, 0, b'p', b'o', b's', b'i', b't', b'i', b'o', b'n']
+        );
+    }
+
+    #[test]
+    fn test_variant_round_trip() {
+        let value = GodotValue::Dictionary(vec![
+            ("name".to_string(), GodotValue::String("say \\\"hi\\\"".to_string())),
+            ("tag".to_string(), GodotValue::StringName("player".to_string())),
+            ("hp".to_string(), GodotValue::Integer(-20)),
+            ("speed".to_string(), GodotValue::Float(0.7)),
+            ("cell".to_string(), GodotValue::Vector3i((1, -2, 3))),
+            ("xform".to_string(), GodotValue::Transform2D([1.0, 0.0, 0.0, 1.0, 12.5, -4.0])),
+            ("tint".to_string(), GodotValue::Color((1.0, 0.5, 0.25, 1.0))),
+            ("bytes".to_string(), GodotValue::PackedByteArray(vec![1, 2, 3])),
+            ("names".to_string(), GodotValue::PackedStringArray(vec!["a".to_string(), "bcdef".to_string()])),
+            ("path".to_string(), GodotValue::NodePath("../Player:position:x".to_string())),
+            ("items".to_string(), GodotValue::Array(vec![GodotValue::Null, GodotValue::Boolean(true)])),
+        ]);
+        let bytes = var_to_bytes(&value).unwrap();
+        assert_eq!(bytes.len() % 4, 0);
+        let (remain, decoded) = bytes_to_var(&bytes).unwrap();
+        assert!(remain.is_empty());
+        assert_eq!(decoded, value);
+
+        // `{1: null}`: an integer key has no text form that keeps it apart from the string "1".
+        let words = |words: &[u32]| words.iter().flat_map(|w| w.to_le_bytes()).collect::<Vec<_>>();
+        assert!(bytes_to_var(&words(&[TYPE_DICTIONARY, 1, TYPE_INT, 1, TYPE_NIL])).is_err());
+        assert_eq!(
+            bytes_to_var(&words(&[TYPE_DICTIONARY, 1, TYPE_STRING, 1, u32::from(b'1'), TYPE_NIL])).unwrap().1,
+            GodotValue::Dictionary(vec![("1".to_string(), GodotValue::Null)])
+        );
+    }
+
+    #[test]
+    fn test_objects() {
+        let event = GodotValue::Object((
+            "InputEventKey".to_string(),
+            vec![("keycode".to_string(), GodotValue::Integer(32))],
+        ));
+        assert!(var_to_bytes(&event).is_none());
+        let bytes = var_to_bytes_with_objects(&event).unwrap();
+        assert!(bytes_to_var(&bytes).is_err());
+        assert_eq!(bytes_to_var_with_objects(&bytes).unwrap().1, event);
+
+        let mut stored = Vec::new();
+        store_var(&mut stored, &event, true).unwrap();
+        store_var(&mut stored, &GodotValue::Integer(7), false).unwrap();
+        let (remain, first) = get_var(&stored, true).unwrap();
+        assert_eq!(first, event);
+        assert_eq!(get_var(remain, false).unwrap().1, GodotValue::Integer(7));
+    }
+}
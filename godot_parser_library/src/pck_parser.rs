@@ -0,0 +1,374 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+use nom::bytes::complete::{tag, take};
+use nom::error::ParseError;
+use nom::multi::count;
+use nom::number::complete::{le_u32, le_u64};
+use nom::IResult;
+use godot_data::pck_file::{PckEntry, PckFile};
+use crate::project_paths::{normalize, RES_PREFIX, USER_PREFIX};
+use crate::rsrc_parser::{put_u32, put_u64};
+
+pub(crate) const PACK_HEADER_MAGIC: &[u8] = b"GDPC";
+pub(crate) const PACK_FORMAT_VERSION: u32 = 3;
+pub(crate) const PACK_RESERVED_FIELDS: usize = 16;
+
+pub(crate) const PACK_DIR_ENCRYPTED: u32 = 1;
+pub(crate) const PACK_REL_FILEBASE: u32 = 2;
+
+pub(crate) const PACK_FILE_ENCRYPTED: u32 = 1;
+pub(crate) const PACK_FILE_REMOVAL: u32 = 2;
+
+/// Settings for [`write_pck_file`] and [`pack_directory`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PckOptions {
+    /// 2 loads in every Godot 4 release, 3 needs Godot 4.4 or newer.
+    pub format_version: u32,
+    pub engine_version: (u32, u32, u32),
+    /// File data starts at multiples of this many bytes.
+    pub alignment: u64,
+}
+
+impl Default for PckOptions {
+    /// The layout the editor exports with for Godot 4.3.
+    fn default() -> Self {
+        PckOptions {
+            format_version: 2,
+            engine_version: (4, 3, 0),
+            alignment: 16,
+        }
+    }
+}
+
+fn fail<T>(input: &[u8]) -> IResult<&[u8], T> {
+    Err(nom::Err::Error(nom::error::Error::from_error_kind(
+        input,
+        nom::error::ErrorKind::Fail,
+    )))
+}
+
+fn padding(len: u64, alignment: u64) -> u64 {
+    match len % alignment {
+        0 => 0,
+        rest => alignment - rest,
+    }
+}
+
+/// Offset of the pack inside `file`: 0 for a `.pck`, or the start of a pack embedded at the
+/// end of an executable, which is followed by its size and the magic again.
+fn pack_start(file: &[u8]) -> Option<usize> {
+    if file.starts_with(PACK_HEADER_MAGIC) {
+        return Some(0);
+    }
+    let trailer = file.len().checked_sub(12)?;
+    if &file[trailer + 8..] != PACK_HEADER_MAGIC {
+        return None;
+    }
+    let size = u64::from_le_bytes(file[trailer..trailer + 8].try_into().ok()?);
+    let start = trailer.checked_sub(usize::try_from(size).ok()?)?;
+    file[start..].starts_with(PACK_HEADER_MAGIC).then_some(start)
+}
+
+fn md5_hex(digest: &[u8]) -> String {
+    digest.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn parse_entry(input: &[u8]) -> IResult<&[u8], (String, u64, u64, String, u32)> {
+    let (input, path_len) = le_u32(input)?;
+    let (input, path) = take(path_len as usize)(input)?;
+    let (input, offset) = le_u64(input)?;
+    let (input, size) = le_u64(input)?;
+    let (input, md5) = take(16usize)(input)?;
+    let (input, flags) = le_u32(input)?;
+    let end = path.iter().position(|b| *b == 0).unwrap_or(path.len());
+    let path = String::from_utf8_lossy(&path[..end]).into_owned();
+    Ok((input, (path, offset, size, md5_hex(md5), flags)))
+}
+
+/// Reads the header and directory of a `.pck` file, or of a pack embedded in an exported
+/// executable. Entry offsets point into `file`; see [`pck_entry_data`].
+pub fn parse_pck_file(file: &[u8]) -> IResult<&[u8], PckFile> {
+    let Some(start) = pack_start(file) else {
+        return fail(file);
+    };
+    let (input, _) = tag(PACK_HEADER_MAGIC)(&file[start..])?;
+    let (input, format_version) = le_u32(input)?;
+    if format_version > PACK_FORMAT_VERSION {
+        return fail(input);
+    }
+    let (input, major) = le_u32(input)?;
+    let (input, minor) = le_u32(input)?;
+    let (input, patch) = le_u32(input)?;
+    let (input, flags) = if format_version >= 2 { le_u32(input)? } else { (input, 0) };
+    let (input, file_base) = if format_version >= 2 { le_u64(input)? } else { (input, 0) };
+    let (input, dir_offset) = if format_version >= 3 { le_u64(input)? } else { (input, 0) };
+    let (input, _) = take(PACK_RESERVED_FIELDS * 4)(input)?;
+    if flags & PACK_DIR_ENCRYPTED != 0 {
+        return fail(input);
+    }
+
+    // Offsets come from the file, so any of them may overflow.
+    let relative = flags & PACK_REL_FILEBASE != 0;
+    let base = |offset: u64| if relative { offset.checked_add(start as u64) } else { Some(offset) };
+    let directory = if format_version >= 3 {
+        match base(dir_offset).and_then(|offset| usize::try_from(offset).ok()).and_then(|offset| file.get(offset..)) {
+            Some(directory) => directory,
+            None => return fail(input),
+        }
+    } else {
+        input
+    };
+    let (remain, file_count) = le_u32(directory)?;
+    let (remain, entries) = count(parse_entry, file_count as usize)(remain)?;
+
+    let Some(file_base) = base(file_base) else {
+        return fail(input);
+    };
+    let entries = entries
+        .into_iter()
+        .map(|(path, offset, size, md5, entry_flags)| {
+            Some(PckEntry {
+                path: if path.starts_with(RES_PREFIX) || path.starts_with(USER_PREFIX) {
+                    path
+                } else {
+                    format!("{}{}", RES_PREFIX, path)
+                },
+                offset: file_base.checked_add(offset)?,
+                size,
+                md5,
+                encrypted: entry_flags & PACK_FILE_ENCRYPTED != 0,
+                removal: entry_flags & PACK_FILE_REMOVAL != 0,
+            })
+        })
+        .collect::<Option<Vec<_>>>();
+    let Some(entries) = entries else {
+        return fail(input);
+    };
+
+    Ok((
+        remain,
+        PckFile {
+            format_version,
+            engine_version: (major, minor, patch),
+            flags,
+            entries,
+        },
+    ))
+}
+
+/// The stored bytes of `entry`, still encrypted if the entry is.
+pub fn pck_entry_data<'a>(file: &'a [u8], entry: &PckEntry) -> Option<&'a [u8]> {
+    let start = usize::try_from(entry.offset).ok()?;
+    let end = start.checked_add(usize::try_from(entry.size).ok()?)?;
+    file.get(start..end)
+}
+
+/// Writes every file of the pack below `dir`, `res://a/b.png` becoming `dir/a/b.png`.
+/// Fails on encrypted entries and on paths that would leave `dir`.
+pub fn extract_pck(file: &[u8], pck: &PckFile, dir: &Path) -> io::Result<()> {
+    for entry in pck.entries.iter().filter(|entry| !entry.removal) {
+        if entry.encrypted {
+            return Err(io::Error::new(io::ErrorKind::Unsupported, format!("{} is encrypted", entry.path)));
+        }
+        let relative = entry.path.strip_prefix(RES_PREFIX).unwrap_or(&entry.path);
+        let relative = normalize(relative)
+            .filter(|path| !path.is_empty())
+            .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, format!("invalid path {}", entry.path)))?;
+        let data = pck_entry_data(file, entry)
+            .ok_or_else(|| io::Error::new(io::ErrorKind::UnexpectedEof, format!("{} is truncated", entry.path)))?;
+
+        let target = relative.split('/').fold(dir.to_path_buf(), |path, part| path.join(part));
+        if let Some(parent) = target.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        fs::write(target, data)?;
+    }
+    Ok(())
+}
+
+/// Builds a pack from `(res:// path, contents)` pairs, in the given order.
+pub fn write_pck_file(files: &[(String, Vec<u8>)], options: &PckOptions) -> Vec<u8> {
+    let stored_path = |path: &str| -> Vec<u8> {
+        // Godot 4.4 stores paths without the `res://` prefix.
+        if options.format_version >= 3 {
+            path.strip_prefix(RES_PREFIX).unwrap_or(path).as_bytes().to_vec()
+        } else {
+            path.as_bytes().to_vec()
+        }
+    };
+
+    let mut out = PACK_HEADER_MAGIC.to_vec();
+    put_u32(&mut out, options.format_version);
+    let (major, minor, patch) = options.engine_version;
+    [major, minor, patch, PACK_REL_FILEBASE].into_iter().for_each(|v| put_u32(&mut out, v));
+    let file_base_at = out.len();
+    put_u64(&mut out, 0);
+    let dir_offset_at = out.len();
+    if options.format_version >= 3 {
+        put_u64(&mut out, 0);
+    }
+    (0..PACK_RESERVED_FIELDS).for_each(|_| put_u32(&mut out, 0));
+
+    let mut offsets = Vec::with_capacity(files.len());
+    let mut offset = 0;
+    for (_, data) in files {
+        offsets.push(offset);
+        offset += data.len() as u64 + padding(data.len() as u64, options.alignment);
+    }
+
+    let mut directory = Vec::new();
+    put_u32(&mut directory, files.len() as u32);
+    for ((path, data), offset) in files.iter().zip(&offsets) {
+        let path = stored_path(path);
+        let path_len = path.len() as u64 + padding(path.len() as u64, 4);
+        put_u32(&mut directory, path_len as u32);
+        directory.extend(&path);
+        directory.resize(directory.len() + (path_len as usize - path.len()), 0);
+        put_u64(&mut directory, *offset);
+        put_u64(&mut directory, data.len() as u64);
+        directory.extend(md5::compute(data).0);
+        put_u32(&mut directory, 0);
+    }
+
+    if options.format_version < 3 {
+        out.extend(&directory);
+    }
+    out.resize(out.len() + padding(out.len() as u64, options.alignment) as usize, 0);
+    let file_base = out.len() as u64;
+    out[file_base_at..file_base_at + 8].copy_from_slice(&file_base.to_le_bytes());
+    for (_, data) in files {
+        out.extend(data);
+        out.resize(out.len() + padding(data.len() as u64, options.alignment) as usize, 0);
+    }
+    if options.format_version >= 3 {
+        let dir_offset = out.len() as u64;
+        out[dir_offset_at..dir_offset_at + 8].copy_from_slice(&dir_offset.to_le_bytes());
+        out.extend(&directory);
+    }
+    out
+}
+
+fn collect_files(root: &Path, dir: &Path, files: &mut Vec<(String, Vec<u8>)>) -> io::Result<()> {
+    let mut children = fs::read_dir(dir)?.collect::<io::Result<Vec<_>>>()?;
+    children.sort_by_key(|child| child.file_name());
+    for child in children {
+        let path = child.path();
+        if child.file_type()?.is_dir() {
+            collect_files(root, &path, files)?;
+        } else {
+            let relative = path
+                .strip_prefix(root)
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "file outside of the packed directory"))?
+                .components()
+                .map(|c| c.as_os_str().to_string_lossy())
+                .collect::<Vec<_>>()
+                .join("/");
+            files.push((format!("{}{}", RES_PREFIX, relative), fs::read(&path)?));
+        }
+    }
+    Ok(())
+}
+
+/// Packs every file below `dir`, sorted by path, as `res://` paths relative to `dir`.
+pub fn pack_directory(dir: &Path, options: &PckOptions) -> io::Result<Vec<u8>> {
+    let mut files = Vec::new();
+    collect_files(dir, dir, &mut files)?;
+    Ok(write_pck_file(&files, options))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn files() -> Vec<(String, Vec<u8>)> {
+        vec![
+            ("res://project.binary".to_string(), b"ECFG\0\0\0\0".to_vec()),
+            ("res://scenes/game.scn".to_string(), b"RSRC and then some".to_vec()),
+            ("res://icon.png".to_string(), Vec::new()),
+        ]
+    }
+
+    #[test]
+    fn test_pck_round_trip() {
+        for format_version in [2, 3] {
+            let options = PckOptions { format_version, alignment: 32, ..PckOptions::default() };
+            let pck = write_pck_file(&files(), &options);
+            let (_, read) = parse_pck_file(&pck).unwrap();
+            assert_eq!(read.format_version, format_version);
+            assert_eq!(read.engine_version, (4, 3, 0));
+            assert_eq!(read.entries.len(), 3);
+            for ((path, data), entry) in files().iter().zip(&read.entries) {
+                assert_eq!(&entry.path, path);
+                assert_eq!(entry.offset % 32, 0);
+                assert_eq!(pck_entry_data(&pck, entry).unwrap(), data.as_slice());
+                assert_eq!(entry.md5, format!("{:x}", md5::compute(data)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_pck_layout() {
+        let pck = write_pck_file(&files()[..1], &PckOptions::default());
+        assert_eq!(&pck[..4], PACK_HEADER_MAGIC);
+        assert_eq!(u32::from_le_bytes(pck[4..8].try_into().unwrap()), 2);
+        // Header, file count, then the first path padded to 4 bytes.
+        let directory = 4 + 4 * 5 + 8 + PACK_RESERVED_FIELDS * 4;
+        assert_eq!(u32::from_le_bytes(pck[directory..directory + 4].try_into().unwrap()), 1);
+        assert_eq!(u32::from_le_bytes(pck[directory + 4..directory + 8].try_into().unwrap()), 20);
+        assert_eq!(&pck[directory + 8..directory + 28], b"res://project.binary");
+        let file_base = u64::from_le_bytes(pck[24..32].try_into().unwrap()) as usize;
+        assert_eq!(file_base % 16, 0);
+        assert_eq!(&pck[file_base..file_base + 8], b"ECFG\0\0\0\0");
+    }
+
+    #[test]
+    fn test_embedded_pck() {
+        let pck = write_pck_file(&files(), &PckOptions::default());
+        let mut executable = b"\x7fELF executable".to_vec();
+        executable.extend(&pck);
+        executable.extend((pck.len() as u64).to_le_bytes());
+        executable.extend(PACK_HEADER_MAGIC);
+        let (_, read) = parse_pck_file(&executable).unwrap();
+        let entry = read.get("res://scenes/game.scn").unwrap();
+        assert_eq!(pck_entry_data(&executable, entry).unwrap(), b"RSRC and then some");
+    }
+
+    #[test]
+    fn test_overflowing_offsets() {
+        // A file base of u64::MAX overflows with the first entry's offset.
+        let mut pck = write_pck_file(&files(), &PckOptions::default());
+        pck[24..32].copy_from_slice(&u64::MAX.to_le_bytes());
+        assert!(parse_pck_file(&pck).is_err());
+
+        // Relative to an embedded pack, the file base or directory offset themselves overflow.
+        for (format_version, field) in [(2, 24..32), (3, 32..40)] {
+            let mut pck = write_pck_file(&files(), &PckOptions { format_version, ..PckOptions::default() });
+            pck[field].copy_from_slice(&u64::MAX.to_le_bytes());
+            let mut executable = b"\x7fELF executable".to_vec();
+            executable.extend(&pck);
+            executable.extend((pck.len() as u64).to_le_bytes());
+            executable.extend(PACK_HEADER_MAGIC);
+            assert!(parse_pck_file(&executable).is_err());
+        }
+    }
+
+    #[test]
+    fn test_pack_and_extract_directory() {
+        let root = std::env::temp_dir().join(format!("godot_pck_test_{}", std::process::id()));
+        let source = root.join("source");
+        fs::create_dir_all(source.join("levels")).unwrap();
+        fs::write(source.join("levels").join("one.tscn"), "[gd_scene format=3]\n").unwrap();
+        fs::write(source.join("project.godot"), "config_version=5\n").unwrap();
+
+        let pck = pack_directory(&source, &PckOptions::default()).unwrap();
+        let (_, read) = parse_pck_file(&pck).unwrap();
+        let paths = read.entries.iter().map(|e| e.path.as_str()).collect::<Vec<_>>();
+        assert_eq!(paths, ["res://levels/one.tscn", "res://project.godot"]);
+
+        let target = root.join("target");
+        extract_pck(&pck, &read, &target).unwrap();
+        assert_eq!(fs::read_to_string(target.join("levels").join("one.tscn")).unwrap(), "[gd_scene format=3]\n");
+        fs::remove_dir_all(root).unwrap();
+    }
+}
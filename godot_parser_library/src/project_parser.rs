@@ -1,59 +1,111 @@
 use std::collections::HashMap;
-use nom::{
-    bytes::complete::{tag},
-    character::complete::{line_ending, not_line_ending},
-    combinator::opt,
-    IResult,
-};
-use nom::bytes::complete::take_until1;
-use nom::character::complete;
-use nom::combinator::{map, not};
-use nom::multi::many0;
-use nom::sequence::{preceded, separated_pair};
-use godot_data::project_file::{ProjectFile, GodotFileParameters};
+use nom::bytes::complete::{tag, take};
+use nom::multi::count;
+use nom::number::complete::le_u32;
+use nom::IResult;
+use godot_data::config_file::ConfigFile;
+use godot_data::project_file::{GodotFileParameters, ProjectFile};
 use godot_data::values::GodotValue;
-use crate::data::values::parse_godot_value;
+use crate::config_parser::{fail, parse_config_file, write_config_file};
+use crate::marshalls::{decode_variant, encode_variant};
+use crate::rsrc_parser::put_u32;
 
-fn parse_comment(input: &str) -> IResult<&str, String> {
-    let (input, _) = tag(";")(input)?;
-    let (input, text) = not_line_ending(input)?;
-    let (input, _) = opt(line_ending)(input)?;
-    Ok((input, text.trim().to_string()))
+const PROJECT_FILE_HEADER: &str = "; Engine configuration file.
+; It's best edited using the editor UI and not directly,
+; since the parameters that go here are not all obvious.
+;
+; Format:
+;   [section] ; section goes between []
+;   param=value ; assign values to parameters
+
+";
+
+const ECFG_MAGIC: &[u8] = b"ECFG";
+
+/// `config_version` of Godot 4 projects, assumed for `project.binary` which doesn't store it.
+pub const CONFIG_VERSION: u32 = 5;
+
+pub fn parse_project_file(input: &str) -> IResult<&str, ProjectFile> {
+    let (remain, config) = parse_config_file(input)?;
+    let mut sections = config.sections;
+    let mut global = sections.remove("").unwrap_or_default();
+    let config_version = match global.remove("config_version") {
+        Some(GodotValue::Integer(version)) if version >= 0 => version as u32,
+        _ => return fail(input),
+    };
+    if !global.is_empty() {
+        sections.insert(String::new(), global);
+    }
+    Ok((remain, ProjectFile {
+        config_version,
+        sections,
+    }))
 }
 
-fn parse_parameter(input: &str) -> IResult<&str, (String, GodotValue)> {
-    not(line_ending)(input)?;
-    let (input, (name, value)) = separated_pair(take_until1("="), tag("="), parse_godot_value)(input)?;
-    let (input, _) = line_ending(input)?;
-    Ok((input, (name.trim().to_string(), value)))
+pub fn write_project_file(project: &ProjectFile) -> String {
+    let mut sections = project.sections.clone();
+    sections
+        .entry(String::new())
+        .or_default()
+        .insert("config_version".to_string(), GodotValue::Integer(project.config_version as i64));
+    format!("{}{}", PROJECT_FILE_HEADER, write_config_file(&ConfigFile { sections }))
 }
 
-fn parse_section(input: &str) -> IResult<&str, (String, GodotFileParameters)> {
-    let (input, _) = tag("[")(input)?;
-    let (input, name) = take_until1("]")(input)?;
-    let (input, _) = tag("]")(input)?;
-    let (input, _) = many0(line_ending)(input)?;
-    let (input, parameters) = many0(parse_parameter)(input)?;
-    let parameters_map = parameters.into_iter().collect::<HashMap<_, _>>();
-    let (input, _) = many0(line_ending)(input)?;
-    Ok((input, (
-        name.trim().to_string(),
-        parameters_map
-    )))
+fn parse_binary_setting(input: &[u8]) -> IResult<&[u8], (String, GodotValue)> {
+    let (input, key_len) = le_u32(input)?;
+    let (input, key) = take(key_len as usize)(input)?;
+    let (input, value_len) = le_u32(input)?;
+    let (input, value) = take(value_len as usize)(input)?;
+    let (_, value) = decode_variant(value, true)?;
+    Ok((input, (String::from_utf8_lossy(key).into_owned(), value)))
 }
 
-pub fn parse_project_file(input: &str) -> IResult<&str, ProjectFile> {
-    let (input, _) = many0(parse_comment)(input)?;
-    let (input, _) = line_ending(input)?;
-    let (input, config_version) = preceded(tag("config_version="), complete::u32)(input)?;
-    let (input, _) = many0(line_ending)(input)?;
-    let (input, sections) = map(many0(parse_section), |section| section.into_iter().collect::<HashMap<_, _>>())(input)?;
+/// Reads the `project.binary` (`ECFG`) file of an exported game. Settings are split into
+/// section and key at the first `/`; paths without one go to the global (`""`) section.
+pub fn parse_project_binary(input: &[u8]) -> IResult<&[u8], ProjectFile> {
+    let (input, _) = tag(ECFG_MAGIC)(input)?;
+    let (input, setting_count) = le_u32(input)?;
+    let (input, settings) = count(parse_binary_setting, setting_count as usize)(input)?;
+
+    let mut sections: HashMap<String, GodotFileParameters> = HashMap::new();
+    for (path, value) in settings {
+        let (section, key) = path.split_once('/').unwrap_or(("", &path));
+        sections.entry(section.to_string()).or_default().insert(key.to_string(), value);
+    }
     Ok((input, ProjectFile {
-        config_version,
+        config_version: CONFIG_VERSION,
         sections,
     }))
 }
 
+/// Writes `project` as a `project.binary` file, settings sorted by path. Returns `None` when a
+/// setting holds a value the format can't store, such as a resource link.
+pub fn write_project_binary(project: &ProjectFile) -> Option<Vec<u8>> {
+    let mut settings = project
+        .sections
+        .iter()
+        .flat_map(|(section, parameters)| {
+            parameters.iter().map(move |(key, value)| match section.as_str() {
+                "" => (key.clone(), value),
+                section => (format!("{}/{}", section, key), value),
+            })
+        })
+        .collect::<Vec<_>>();
+    settings.sort_by(|a, b| a.0.cmp(&b.0));
+
+    let mut out = ECFG_MAGIC.to_vec();
+    put_u32(&mut out, settings.len() as u32);
+    for (path, value) in settings {
+        let mut encoded = Vec::new();
+        encode_variant(&mut encoded, value, true)?;
+        put_u32(&mut out, path.len() as u32);
+        out.extend(path.as_bytes());
+        put_u32(&mut out, encoded.len() as u32);
+        out.extend(encoded);
+    }
+    Some(out)
+}
+
 #[cfg(test)]
 mod tests {
     use godot_data::nanoserde::{SerJson};
@@ -92,4 +144,205 @@ locale/langs={
         let (_, godot_file) = parse_project_file(input).unwrap();
         println!("{:?}", godot_file.serialize_json());
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn test_write_project_file() {
+        let input = r#"config_version=5
+
+[application]
+
+config/features=PackedStringArray("4.3", "Mobile")
+config/name="test"
+
+[display]
+
+window/size/viewport_width=1280
+window/stretch/scale=1.5
+"#;
+        let (_, godot_file) = parse_project_file(input).unwrap();
+        let written = write_project_file(&godot_file);
+        assert!(written.starts_with("; Engine configuration file."));
+        assert!(written.ends_with(input));
+        let (_, reparsed) = parse_project_file(&written).unwrap();
+        assert_eq!(reparsed.config_version, 5);
+        assert_eq!(reparsed.sections, godot_file.sections);
+    }
+
+    #[test]
+    fn test_global_keys() {
+        let input = "config_version=5\nglobal_key=1\n\n[application]\n\nconfig/name=\"test\"\n";
+        let (_, project) = parse_project_file(input).unwrap();
+        assert_eq!(project.sections[""]["global_key"], GodotValue::Integer(1));
+        assert!(!project.sections[""].contains_key("config_version"));
+        assert!(write_project_file(&project).ends_with(input));
+
+        let binary = write_project_binary(&project).unwrap();
+        let (_, from_binary) = parse_project_binary(&binary).unwrap();
+        assert!(write_project_file(&from_binary).ends_with(input));
+    }
+
+    #[test]
+    fn test_project_settings() {
+        let input = r#"config_version=5
+
+[application]
+
+config/name="test"
+config/version="1.2.0"
+run/main_scene="res://game.tscn"
+config/features=PackedStringArray("4.3", "Mobile")
+
+[autoload]
+
+Global="*res://global.gd"
+Sfx="res://sfx.gd"
+
+[display]
+
+window/size/viewport_width=720
+
+[input]
+
+jump={
+"deadzone": 0.2,
+"events": [Object(InputEventKey,"resource_local_to_scene":false,"device":-1,"keycode":0,"physical_keycode":32,"unicode":32,"echo":false,"script":null)
+, Object(InputEventJoypadButton,"resource_local_to_scene":false,"device":-1,"button_index":0,"pressure":0.0,"pressed":true,"script":null)
+]
+}
+
+[layer_names]
+
+2d_physics/layer_1="player"
+2d_physics/layer_3="enemies"
+3d_render/layer_1="world"
+
+[rendering]
+
+renderer/rendering_method="forward_plus"
+renderer/rendering_method.mobile="gl_compatibility"
+"#;
+        let (remain, godot_file) = parse_project_file(input).unwrap();
+        assert!(remain.is_empty());
+        let settings = godot_file.settings();
+        assert_eq!(settings.name(), Some("test"));
+        assert_eq!(settings.version(), Some("1.2.0"));
+        assert_eq!(settings.main_scene(), Some("res://game.tscn"));
+        assert_eq!(settings.icon(), None);
+        assert_eq!(settings.features(), ["4.3", "Mobile"]);
+        assert_eq!(settings.viewport_size(), (720, 648));
+
+        let autoloads = settings.autoloads();
+        assert_eq!(autoloads.len(), 2);
+        assert_eq!((autoloads[0].name, autoloads[0].path, autoloads[0].singleton), ("Global", "res://global.gd", true));
+        assert!(!autoloads[1].singleton);
+
+        let actions = settings.input_actions();
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0].deadzone, 0.2);
+        assert_eq!(actions[0].events.len(), 2);
+        assert_eq!(actions[0].events[0].class, "InputEventKey");
+        assert_eq!(actions[0].events[0].get("physical_keycode"), Some(&GodotValue::Integer(32)));
+
+        assert_eq!(settings.layer_names("2d_physics"), vec![(1, "player"), (3, "enemies")]);
+        assert_eq!(
+            godot_file.get("rendering/renderer/rendering_method.mobile"),
+            Some(&GodotValue::String("gl_compatibility".to_string()))
+        );
+        assert_eq!(
+            godot_file.get_override("rendering/renderer/rendering_method", "mobile"),
+            godot_file.get("rendering/renderer/rendering_method.mobile")
+        );
+
+        let (_, reparsed) = parse_project_file(&write_project_file(&godot_file)).unwrap();
+        assert_eq!(reparsed.sections, godot_file.sections);
+    }
+
+    #[test]
+    fn test_resolve_features() {
+        let input = r#"config_version=5
+
+[display]
+
+window/size/viewport_width=1280
+window/size/viewport_width.web=800
+window/size/viewport_width.android.ios=720
+
+[rendering]
+
+renderer/rendering_method="forward_plus"
+renderer/rendering_method.mobile="gl_compatibility"
+"#;
+        let (_, godot_file) = parse_project_file(input).unwrap();
+
+        let desktop = godot_file.resolve_features(&["windows", "pc", "release"]);
+        assert_eq!(desktop.sections["display"].len(), 1);
+        assert_eq!(desktop.get("display/window/size/viewport_width"), Some(&GodotValue::Integer(1280)));
+        assert_eq!(desktop.get("rendering/renderer/rendering_method.mobile"), None);
+
+        let android = godot_file.resolve_features(&["android", "mobile"]);
+        assert_eq!(android.get("display/window/size/viewport_width"), Some(&GodotValue::Integer(720)));
+        assert_eq!(
+            android.get("rendering/renderer/rendering_method"),
+            Some(&GodotValue::String("gl_compatibility".to_string()))
+        );
+
+        assert_eq!(
+            godot_file.get_with_features("display/window/size/viewport_width", &["web", "ios"]),
+            Some(&GodotValue::Integer(720))
+        );
+        assert_eq!(
+            godot_file.get_with_features("display/window/size/viewport_width", &["web"]),
+            Some(&GodotValue::Integer(800))
+        );
+    }
+
+    #[test]
+    fn test_project_binary() {
+        let input = r#"config_version=5
+
+[application]
+
+config/name="Game \"X\""
+config/features=PackedStringArray("4.3", "Mobile")
+boot_splash/bg_color=Color(0.1, 0.2, 0.3, 1)
+
+[display]
+
+window/size/viewport_width=1280
+window/stretch/scale=1.5
+
+[input]
+
+jump={
+"deadzone": 0.2,
+"events": [Object(InputEventKey,"resource_local_to_scene":false,"device":-1,"physical_keycode":32,"script":null)
+]
+}
+
+[layer_names]
+
+2d_physics/layer_1="player"
+"#;
+        let (_, godot_file) = parse_project_file(input).unwrap();
+        let binary = write_project_binary(&godot_file).unwrap();
+        let (remain, read) = parse_project_binary(&binary).unwrap();
+        assert!(remain.is_empty());
+        assert_eq!(read.config_version, CONFIG_VERSION);
+        assert_eq!(read.sections, godot_file.sections);
+        assert_eq!(read.settings().name(), Some("Game \\\"X\\\""));
+    }
+
+    #[test]
+    fn test_project_binary_layout() {
+        let project = ProjectFile {
+            config_version: CONFIG_VERSION,
+            sections: HashMap::from([(
+                "application".to_string(),
+                HashMap::from([("config/name".to_string(), GodotValue::String("Game".to_string()))]),
+            )]),
+        };
+        let mut expected = b"ECFG\x01\0\0\0\x17\0\0\0application/config/name\x0c\0\0\0".to_vec();
+        expected.extend(b"\x04\0\0\0\x04\0\0\0Game");
+        assert_eq!(write_project_binary(&project).unwrap(), expected);
+    }
+}
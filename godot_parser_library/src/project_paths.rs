@@ -0,0 +1,222 @@
+use std::path::{Component, Path, PathBuf};
+use godot_data::tscn_file::Tag;
+use godot_data::values::GodotValue;
+
+pub const RES_PREFIX: &str = "res://";
+pub const USER_PREFIX: &str = "user://";
+pub const PROJECT_FILE_NAME: &str = "project.godot";
+
+/// Converts between Godot virtual paths (`res://`, `user://`) and filesystem paths
+/// for a single project.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ProjectPaths {
+    root: PathBuf,
+    user_root: Option<PathBuf>,
+}
+
+impl ProjectPaths {
+    /// `root` is the directory that contains `project.godot`.
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        ProjectPaths {
+            root: root.into(),
+            user_root: None,
+        }
+    }
+
+    /// Builds the resolver from the path of a `project.godot` file.
+    pub fn from_project_file(project_file: &Path) -> Option<Self> {
+        project_file.parent().map(ProjectPaths::new)
+    }
+
+    /// Walks up from `start` until a directory containing `project.godot` is found.
+    pub fn find(start: &Path) -> Option<Self> {
+        start
+            .ancestors()
+            .find(|dir| dir.join(PROJECT_FILE_NAME).is_file())
+            .map(ProjectPaths::new)
+    }
+
+    /// Sets the directory `user://` paths resolve to.
+    pub fn with_user_root(mut self, user_root: impl Into<PathBuf>) -> Self {
+        self.user_root = Some(user_root.into());
+        self
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn user_root(&self) -> Option<&Path> {
+        self.user_root.as_deref()
+    }
+
+    /// Maps a `res://` or `user://` path to the filesystem. Returns `None` for other
+    /// schemes, for `user://` without a configured root, and for paths escaping the root.
+    pub fn to_filesystem(&self, godot_path: &str) -> Option<PathBuf> {
+        if let Some(rest) = godot_path.strip_prefix(RES_PREFIX) {
+            join_normalized(&self.root, rest)
+        } else if let Some(rest) = godot_path.strip_prefix(USER_PREFIX) {
+            join_normalized(self.user_root.as_ref()?, rest)
+        } else {
+            None
+        }
+    }
+
+    /// Maps a filesystem path inside the project to its `res://` path. A relative `path`
+    /// is taken from the current directory, like any other filesystem path.
+    pub fn to_res(&self, path: &Path) -> Option<String> {
+        relative_to(&self.root, path).map(|rel| format!("{}{}", RES_PREFIX, rel))
+    }
+
+    /// Maps a filesystem path inside the user root to its `user://` path.
+    pub fn to_user(&self, path: &Path) -> Option<String> {
+        relative_to(self.user_root.as_ref()?, path).map(|rel| format!("{}{}", USER_PREFIX, rel))
+    }
+}
+
+/// Resolves `path` as written inside the resource at `base` (a `res://` path).
+/// Absolute Godot paths are normalized, relative ones are joined with the
+/// directory of `base`.
+pub fn resolve(base: &str, path: &str) -> Option<String> {
+    if let Some(rest) = path.strip_prefix(RES_PREFIX) {
+        return normalize(rest).map(|p| format!("{}{}", RES_PREFIX, p));
+    }
+    if let Some(rest) = path.strip_prefix(USER_PREFIX) {
+        return normalize(rest).map(|p| format!("{}{}", USER_PREFIX, p));
+    }
+    let base = base.strip_prefix(RES_PREFIX)?;
+    let dir = base.rsplit_once('/').map(|(dir, _)| dir).unwrap_or("");
+    normalize(&format!("{}/{}", dir, path)).map(|p| format!("{}{}", RES_PREFIX, p))
+}
+
+/// Resolves the `path` attribute of an `ext_resource` tag found in the resource at `base`.
+pub fn resolve_ext_resource(base: &str, tag: &Tag) -> Option<String> {
+    match tag.attrs.get("path") {
+        Some(GodotValue::String(path)) => resolve(base, path),
+        _ => None,
+    }
+}
+
+/// Collapses `.`, `..`, repeated and trailing separators of a `/`-separated path.
+/// Returns `None` when `..` climbs above the start of the path.
+pub fn normalize(path: &str) -> Option<String> {
+    let mut parts: Vec<&str> = Vec::new();
+    for part in path.split(['/', '\\']) {
+        match part {
+            "" | "." => {}
+            ".." => {
+                parts.pop()?;
+            }
+            _ => parts.push(part),
+        }
+    }
+    Some(parts.join("/"))
+}
+
+fn join_normalized(root: &Path, rest: &str) -> Option<PathBuf> {
+    let rest = normalize(rest)?;
+    let mut out = root.to_path_buf();
+    out.extend(rest.split('/').filter(|p| !p.is_empty()));
+    Some(out)
+}
+
+fn relative_to(root: &Path, path: &Path) -> Option<String> {
+    let cwd = std::env::current_dir().ok()?;
+    let root = normalize_fs(&cwd.join(root));
+    let path = normalize_fs(&cwd.join(path));
+    let rel = path.strip_prefix(&root).ok()?;
+    let parts = rel
+        .components()
+        .map(|c| c.as_os_str().to_str())
+        .collect::<Option<Vec<_>>>()?;
+    Some(parts.join("/"))
+}
+
+fn normalize_fs(path: &Path) -> PathBuf {
+    let mut out = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !out.pop() {
+                    out.push(component);
+                }
+            }
+            _ => out.push(component),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+    use godot_data::tscn_file::TagType;
+    use super::*;
+
+    #[test]
+    fn test_normalize() {
+        assert_eq!(normalize("a/./b/../c//d/").as_deref(), Some("a/c/d"));
+        assert_eq!(normalize("").as_deref(), Some(""));
+        assert_eq!(normalize("../a"), None);
+    }
+
+    #[test]
+    fn test_res_paths() {
+        let paths = ProjectPaths::new("/game");
+        assert_eq!(
+            paths.to_filesystem("res://langs/../langs/en.json"),
+            Some(PathBuf::from("/game/langs/en.json"))
+        );
+        assert_eq!(paths.to_filesystem("res://../secret"), None);
+        assert_eq!(paths.to_filesystem("user://save.dat"), None);
+        assert_eq!(
+            paths.to_res(Path::new("/game/./atlases/icons.png")).as_deref(),
+            Some("res://atlases/icons.png")
+        );
+        assert_eq!(paths.to_res(Path::new("/other/icons.png")), None);
+    }
+
+    #[test]
+    fn test_relative_fs_paths() {
+        // Relative paths, the root included, start from the current directory.
+        let paths = ProjectPaths::new("game");
+        assert_eq!(
+            paths.to_res(Path::new("game/scenes/../atlases/icons.png")).as_deref(),
+            Some("res://atlases/icons.png")
+        );
+        assert_eq!(paths.to_res(Path::new("atlases/icons.png")), None);
+        let paths = ProjectPaths::new(std::env::current_dir().unwrap());
+        assert_eq!(paths.to_res(Path::new("atlases/icons.png")).as_deref(), Some("res://atlases/icons.png"));
+        assert_eq!(ProjectPaths::new("/game").to_res(Path::new("atlases/icons.png")), None);
+    }
+
+    #[test]
+    fn test_user_paths() {
+        let paths = ProjectPaths::new("/game").with_user_root("/home/me/.local/share/godot/app_userdata/test");
+        assert_eq!(
+            paths.to_filesystem("user://saves/1.dat"),
+            Some(PathBuf::from("/home/me/.local/share/godot/app_userdata/test/saves/1.dat"))
+        );
+        assert_eq!(
+            paths.to_user(Path::new("/home/me/.local/share/godot/app_userdata/test/saves/1.dat")).as_deref(),
+            Some("user://saves/1.dat")
+        );
+    }
+
+    #[test]
+    fn test_resolve_ext_resource() {
+        let tag = Tag {
+            _type: TagType::ExtResource,
+            attrs: HashMap::from([("path".to_string(), GodotValue::String("../atlases/icons.png".to_string()))]),
+            props: HashMap::new(),
+        };
+        assert_eq!(
+            resolve_ext_resource("res://scenes/game.tscn", &tag).as_deref(),
+            Some("res://atlases/icons.png")
+        );
+        assert_eq!(resolve("res://game.tscn", "icon.svg").as_deref(), Some("res://icon.svg"));
+        assert_eq!(resolve("res://a/game.tscn", "res://./b/icon.svg").as_deref(), Some("res://b/icon.svg"));
+        assert_eq!(resolve("res://game.tscn", "../icon.svg"), None);
+    }
+}
@@ -0,0 +1,1586 @@
+use std::collections::HashMap;
+use nom::bytes::complete::{tag, take};
+use nom::error::ParseError;
+use nom::multi::count;
+use nom::number::complete::{f32, f64, i32, i64, u16, u32, u64, u8};
+use nom::number::Endianness;
+use nom::IResult;
+use godot_data::tscn_file::{TSCNFile, Tag, TagType};
+use godot_data::values::GodotValue;
+use crate::data::writer::{escape_string, unescape_string};
+use crate::uid::{id_to_text, text_to_id, INVALID_UID};
+
+pub(crate) const RSRC_MAGIC: &[u8] = b"RSRC";
+pub(crate) const FORMAT_VERSION: u32 = 6;
+// Version 6 only added `PackedVector4Array`; files without one are written as version 5 so
+// that Godot 4.0 - 4.2 can load them too.
+pub(crate) const FORMAT_VERSION_NO_VECTOR4_ARRAY: u32 = 5;
+pub(crate) const RESERVED_FIELDS: usize = 11;
+
+pub(crate) const FORMAT_FLAG_NAMED_SCENE_IDS: u32 = 1;
+pub(crate) const FORMAT_FLAG_UIDS: u32 = 2;
+pub(crate) const FORMAT_FLAG_REAL_T_IS_DOUBLE: u32 = 4;
+pub(crate) const FORMAT_FLAG_HAS_SCRIPT_CLASS: u32 = 8;
+
+pub(crate) const VARIANT_NIL: u32 = 1;
+pub(crate) const VARIANT_BOOL: u32 = 2;
+pub(crate) const VARIANT_INT: u32 = 3;
+pub(crate) const VARIANT_FLOAT: u32 = 4;
+pub(crate) const VARIANT_STRING: u32 = 5;
+pub(crate) const VARIANT_VECTOR2: u32 = 10;
+pub(crate) const VARIANT_RECT2: u32 = 11;
+pub(crate) const VARIANT_VECTOR3: u32 = 12;
+pub(crate) const VARIANT_PLANE: u32 = 13;
+pub(crate) const VARIANT_QUATERNION: u32 = 14;
+pub(crate) const VARIANT_AABB: u32 = 15;
+pub(crate) const VARIANT_BASIS: u32 = 16;
+pub(crate) const VARIANT_TRANSFORM3D: u32 = 17;
+pub(crate) const VARIANT_TRANSFORM2D: u32 = 18;
+pub(crate) const VARIANT_COLOR: u32 = 20;
+pub(crate) const VARIANT_NODE_PATH: u32 = 22;
+pub(crate) const VARIANT_RID: u32 = 23;
+pub(crate) const VARIANT_OBJECT: u32 = 24;
+pub(crate) const VARIANT_DICTIONARY: u32 = 26;
+pub(crate) const VARIANT_ARRAY: u32 = 30;
+pub(crate) const VARIANT_PACKED_BYTE_ARRAY: u32 = 31;
+pub(crate) const VARIANT_PACKED_INT32_ARRAY: u32 = 32;
+pub(crate) const VARIANT_PACKED_FLOAT32_ARRAY: u32 = 33;
+pub(crate) const VARIANT_PACKED_STRING_ARRAY: u32 = 34;
+pub(crate) const VARIANT_PACKED_VECTOR3_ARRAY: u32 = 35;
+pub(crate) const VARIANT_PACKED_COLOR_ARRAY: u32 = 36;
+pub(crate) const VARIANT_PACKED_VECTOR2_ARRAY: u32 = 37;
+pub(crate) const VARIANT_INT64: u32 = 40;
+pub(crate) const VARIANT_DOUBLE: u32 = 41;
+pub(crate) const VARIANT_CALLABLE: u32 = 42;
+pub(crate) const VARIANT_SIGNAL: u32 = 43;
+pub(crate) const VARIANT_STRING_NAME: u32 = 44;
+pub(crate) const VARIANT_VECTOR2I: u32 = 45;
+pub(crate) const VARIANT_RECT2I: u32 = 46;
+pub(crate) const VARIANT_VECTOR3I: u32 = 47;
+pub(crate) const VARIANT_PACKED_INT64_ARRAY: u32 = 48;
+pub(crate) const VARIANT_PACKED_FLOAT64_ARRAY: u32 = 49;
+pub(crate) const VARIANT_VECTOR4: u32 = 50;
+pub(crate) const VARIANT_VECTOR4I: u32 = 51;
+pub(crate) const VARIANT_PROJECTION: u32 = 52;
+pub(crate) const VARIANT_PACKED_VECTOR4_ARRAY: u32 = 53;
+
+pub(crate) const OBJECT_EMPTY: u32 = 0;
+pub(crate) const OBJECT_EXTERNAL_RESOURCE: u32 = 1;
+pub(crate) const OBJECT_INTERNAL_RESOURCE: u32 = 2;
+pub(crate) const OBJECT_EXTERNAL_RESOURCE_INDEX: u32 = 3;
+
+// SceneState bundle layout (`PackedScene._bundled`).
+pub(crate) const NAME_INDEX_BITS: u32 = 18;
+pub(crate) const FLAG_ID_IS_PATH: i32 = 1 << 30;
+pub(crate) const FLAG_MASK: i32 = (1 << 24) - 1;
+pub(crate) const TYPE_INSTANTIATED: i32 = 0x7FFFFFFF;
+pub(crate) const FLAG_INSTANCE_IS_PLACEHOLDER: i32 = 1 << 30;
+pub(crate) const FLAG_PATH_PROPERTY_IS_NODE: i32 = 1 << 30;
+pub(crate) const FLAG_PROP_NAME_MASK: i32 = FLAG_PATH_PROPERTY_IS_NODE - 1;
+pub(crate) const CONNECT_PERSIST: i64 = 2;
+pub(crate) const PACKED_SCENE_VERSION: i64 = 3;
+
+pub(crate) const LOCAL_PREFIX: &str = "local://";
+
+type Properties = Vec<(String, GodotValue)>;
+
+fn fail<T>(input: &[u8]) -> IResult<&[u8], T> {
+    Err(nom::Err::Error(nom::error::Error::from_error_kind(
+        input,
+        nom::error::ErrorKind::Fail,
+    )))
+}
+
+/// Widens an `f32` through its shortest decimal form, so `0.7f32` reads back as `0.7`.
+pub(crate) fn widen(value: f32) -> f64 {
+    if value.is_finite() {
+        value.to_string().parse().unwrap_or(value as f64)
+    } else {
+        value as f64
+    }
+}
+
+fn utf8_until_nul(bytes: &[u8]) -> String {
+    let end = bytes.iter().position(|b| *b == 0).unwrap_or(bytes.len());
+    String::from_utf8_lossy(&bytes[..end]).into_owned()
+}
+
+struct Reader {
+    endian: Endianness,
+    real_is_double: bool,
+    strings: Vec<String>,
+    ext_paths: Vec<String>,
+    ext_ids: Vec<String>,
+    int_ids: Vec<String>,
+}
+
+impl Reader {
+    fn u32<'a>(&self, input: &'a [u8]) -> IResult<&'a [u8], u32> {
+        u32(self.endian)(input)
+    }
+
+    fn i32<'a>(&self, input: &'a [u8]) -> IResult<&'a [u8], i32> {
+        i32(self.endian)(input)
+    }
+
+    fn u64<'a>(&self, input: &'a [u8]) -> IResult<&'a [u8], u64> {
+        u64(self.endian)(input)
+    }
+
+    fn f32<'a>(&self, input: &'a [u8]) -> IResult<&'a [u8], f64> {
+        let (input, value) = f32(self.endian)(input)?;
+        Ok((input, widen(value)))
+    }
+
+    fn real<'a>(&self, input: &'a [u8]) -> IResult<&'a [u8], f64> {
+        if self.real_is_double {
+            f64(self.endian)(input)
+        } else {
+            self.f32(input)
+        }
+    }
+
+    fn reals<'a, const N: usize>(&self, input: &'a [u8]) -> IResult<&'a [u8], [f64; N]> {
+        let mut out = [0.0; N];
+        let mut input = input;
+        for value in out.iter_mut() {
+            (input, *value) = self.real(input)?;
+        }
+        Ok((input, out))
+    }
+
+    fn i32s<'a, const N: usize>(&self, input: &'a [u8]) -> IResult<&'a [u8], [i32; N]> {
+        let mut out = [0; N];
+        let mut input = input;
+        for value in out.iter_mut() {
+            (input, *value) = self.i32(input)?;
+        }
+        Ok((input, out))
+    }
+
+    fn unicode_string<'a>(&self, input: &'a [u8]) -> IResult<&'a [u8], String> {
+        let (input, len) = self.u32(input)?;
+        let (input, bytes) = take(len as usize)(input)?;
+        Ok((input, utf8_until_nul(bytes)))
+    }
+
+    /// A string table index, or an inline string when the high bit is set.
+    fn table_string<'a>(&self, input: &'a [u8]) -> IResult<&'a [u8], String> {
+        let (remain, id) = self.u32(input)?;
+        if id & 0x80000000 != 0 {
+            let (remain, bytes) = take((id & 0x7FFFFFFF) as usize)(remain)?;
+            return Ok((remain, utf8_until_nul(bytes)));
+        }
+        match self.strings.get(id as usize) {
+            Some(s) => Ok((remain, s.clone())),
+            None => fail(input),
+        }
+    }
+
+    fn len<'a>(&self, input: &'a [u8]) -> IResult<&'a [u8], usize> {
+        let (input, len) = self.u32(input)?;
+        Ok((input, (len & 0x7FFFFFFF) as usize))
+    }
+
+    fn node_path<'a>(&self, input: &'a [u8]) -> IResult<&'a [u8], String> {
+        let (input, name_count) = u16(self.endian)(input)?;
+        let (input, subname_count) = u16(self.endian)(input)?;
+        let absolute = subname_count & 0x8000 != 0;
+        let (input, names) = count(|i| self.table_string(i), name_count as usize)(input)?;
+        let (input, subnames) = count(|i| self.table_string(i), (subname_count & 0x7FFF) as usize)(input)?;
+        let mut path = if absolute { "/".to_string() } else { String::new() };
+        path.push_str(&names.join("/"));
+        for subname in subnames {
+            path.push(':');
+            path.push_str(&subname);
+        }
+        Ok((input, path))
+    }
+
+    fn object<'a>(&self, input: &'a [u8]) -> IResult<&'a [u8], GodotValue> {
+        let (remain, object_type) = self.u32(input)?;
+        match object_type {
+            OBJECT_EMPTY => Ok((remain, GodotValue::Null)),
+            OBJECT_EXTERNAL_RESOURCE => {
+                let (remain, _type) = self.unicode_string(remain)?;
+                let (remain, path) = self.unicode_string(remain)?;
+                match self.ext_paths.iter().position(|p| *p == path) {
+                    Some(index) => Ok((remain, GodotValue::ExtResourceLink(self.ext_ids[index].clone()))),
+                    None => fail(input),
+                }
+            }
+            OBJECT_INTERNAL_RESOURCE => {
+                let (remain, index) = self.u32(remain)?;
+                match self.int_ids.get(index as usize) {
+                    Some(id) => Ok((remain, GodotValue::SubResourceLink(id.clone()))),
+                    None => fail(input),
+                }
+            }
+            OBJECT_EXTERNAL_RESOURCE_INDEX => {
+                let (remain, index) = self.u32(remain)?;
+                match self.ext_ids.get(index as usize) {
+                    Some(id) => Ok((remain, GodotValue::ExtResourceLink(id.clone()))),
+                    None => fail(input),
+                }
+            }
+            _ => fail(input),
+        }
+    }
+
+    fn variant<'a>(&self, input: &'a [u8]) -> IResult<&'a [u8], GodotValue> {
+        let (remain, variant_type) = self.u32(input)?;
+        let endian = self.endian;
+        match variant_type {
+            VARIANT_NIL | VARIANT_CALLABLE | VARIANT_SIGNAL => Ok((remain, GodotValue::Null)),
+            VARIANT_BOOL => {
+                let (remain, value) = self.u32(remain)?;
+                Ok((remain, GodotValue::Boolean(value != 0)))
+            }
+            VARIANT_INT => {
+                let (remain, value) = self.i32(remain)?;
+                Ok((remain, GodotValue::Integer(value as i64)))
+            }
+            VARIANT_INT64 => {
+                let (remain, value) = i64(endian)(remain)?;
+                Ok((remain, GodotValue::Integer(value)))
+            }
+            VARIANT_FLOAT => {
+                let (remain, value) = self.f32(remain)?;
+                Ok((remain, GodotValue::Float(value)))
+            }
+            VARIANT_DOUBLE => {
+                let (remain, value) = f64(endian)(remain)?;
+                Ok((remain, GodotValue::Float(value)))
+            }
+            VARIANT_STRING => {
+                let (remain, value) = self.unicode_string(remain)?;
+                Ok((remain, GodotValue::String(escape_string(&value))))
+            }
+            VARIANT_STRING_NAME => {
+                let (remain, value) = self.unicode_string(remain)?;
+                Ok((remain, GodotValue::StringName(escape_string(&value))))
+            }
+            VARIANT_VECTOR2 => {
+                let (remain, [x, y]) = self.reals(remain)?;
+                Ok((remain, GodotValue::Vector2((x, y))))
+            }
+            VARIANT_VECTOR2I => {
+                let (remain, [x, y]) = self.i32s(remain)?;
+                Ok((remain, GodotValue::Vector2i((x, y))))
+            }
+            VARIANT_RECT2 => {
+                let (remain, [x, y, w, h]) = self.reals(remain)?;
+                Ok((remain, GodotValue::Rect2((x, y, w, h))))
+            }
+            VARIANT_RECT2I => {
+                let (remain, [x, y, w, h]) = self.i32s(remain)?;
+                Ok((remain, GodotValue::Rect2i((x, y, w, h))))
+            }
+            VARIANT_VECTOR3 => {
+                let (remain, [x, y, z]) = self.reals(remain)?;
+                Ok((remain, GodotValue::Vector3((x, y, z))))
+            }
+            VARIANT_VECTOR3I => {
+                let (remain, [x, y, z]) = self.i32s(remain)?;
+                Ok((remain, GodotValue::Vector3i((x, y, z))))
+            }
+            VARIANT_VECTOR4 => {
+                let (remain, [x, y, z, w]) = self.reals(remain)?;
+                Ok((remain, GodotValue::Vector4((x, y, z, w))))
+            }
+            VARIANT_VECTOR4I => {
+                let (remain, [x, y, z, w]) = self.i32s(remain)?;
+                Ok((remain, GodotValue::Vector4i((x, y, z, w))))
+            }
+            VARIANT_PLANE => {
+                let (remain, [a, b, c, d]) = self.reals(remain)?;
+                Ok((remain, GodotValue::Plane((a, b, c, d))))
+            }
+            VARIANT_QUATERNION => {
+                let (remain, [x, y, z, w]) = self.reals(remain)?;
+                Ok((remain, GodotValue::Quaternion((x, y, z, w))))
+            }
+            VARIANT_AABB => {
+                let (remain, values) = self.reals(remain)?;
+                Ok((remain, GodotValue::AABB(values)))
+            }
+            VARIANT_BASIS => {
+                let (remain, values) = self.reals(remain)?;
+                Ok((remain, GodotValue::Basis(values)))
+            }
+            VARIANT_TRANSFORM2D => {
+                let (remain, values) = self.reals(remain)?;
+                Ok((remain, GodotValue::Transform2D(values)))
+            }
+            VARIANT_TRANSFORM3D => {
+                let (remain, values) = self.reals(remain)?;
+                Ok((remain, GodotValue::Transform3D(values)))
+            }
+            VARIANT_PROJECTION => {
+                let (remain, values) = self.reals(remain)?;
+                Ok((remain, GodotValue::Projection(values)))
+            }
+            VARIANT_COLOR => {
+                let (remain, r) = self.f32(remain)?;
+                let (remain, g) = self.f32(remain)?;
+                let (remain, b) = self.f32(remain)?;
+                let (remain, a) = self.f32(remain)?;
+                Ok((remain, GodotValue::Color((r, g, b, a))))
+            }
+            VARIANT_NODE_PATH => {
+                let (remain, path) = self.node_path(remain)?;
+                Ok((remain, GodotValue::NodePath(escape_string(&path))))
+            }
+            VARIANT_RID => {
+                let (remain, _) = self.u32(remain)?;
+                Ok((remain, GodotValue::Null))
+            }
+            VARIANT_OBJECT => self.object(remain),
+            VARIANT_DICTIONARY => {
+                let (remain, len) = self.len(remain)?;
+                let (remain, entries) = count(
+                    |i| {
+                        let (remain, key) = self.variant(i)?;
+                        // Dictionaries only have text keys, writing others as text would mix up `1` and `"1"`.
+                        let key = match key {
+                            GodotValue::String(s) | GodotValue::StringName(s) => s,
+                            _ => return fail(i),
+                        };
+                        let (remain, value) = self.variant(remain)?;
+                        Ok((remain, (key, value)))
+                    },
+                    len,
+                )(remain)?;
+                Ok((remain, GodotValue::Dictionary(entries)))
+            }
+            VARIANT_ARRAY => {
+                let (remain, len) = self.len(remain)?;
+                let (remain, values) = count(|i| self.variant(i), len)(remain)?;
+                Ok((remain, GodotValue::Array(values)))
+            }
+            VARIANT_PACKED_BYTE_ARRAY => {
+                let (remain, len) = self.len(remain)?;
+                let (remain, bytes) = count(u8, len)(remain)?;
+                let (remain, _) = take((4 - len % 4) % 4)(remain)?;
+                Ok((remain, GodotValue::PackedByteArray(bytes)))
+            }
+            VARIANT_PACKED_INT32_ARRAY => {
+                let (remain, len) = self.len(remain)?;
+                let (remain, values) = count(|i| self.i32(i), len)(remain)?;
+                Ok((remain, GodotValue::PackedInt32Array(values)))
+            }
+            VARIANT_PACKED_INT64_ARRAY => {
+                let (remain, len) = self.len(remain)?;
+                let (remain, values) = count(i64(endian), len)(remain)?;
+                Ok((remain, GodotValue::PackedInt64Array(values)))
+            }
+            VARIANT_PACKED_FLOAT32_ARRAY => {
+                let (remain, len) = self.len(remain)?;
+                let (remain, values) = count(f32(endian), len)(remain)?;
+                Ok((remain, GodotValue::PackedFloat32Array(values)))
+            }
+            VARIANT_PACKED_FLOAT64_ARRAY => {
+                let (remain, len) = self.len(remain)?;
+                let (remain, values) = count(f64(endian), len)(remain)?;
+                Ok((remain, GodotValue::PackedFloat64Array(values)))
+            }
+            VARIANT_PACKED_STRING_ARRAY => {
+                let (remain, len) = self.len(remain)?;
+                let (remain, values) = count(|i| self.unicode_string(i), len)(remain)?;
+                Ok((remain, GodotValue::PackedStringArray(values.iter().map(|s| escape_string(s)).collect())))
+            }
+            VARIANT_PACKED_VECTOR2_ARRAY => {
+                let (remain, len) = self.len(remain)?;
+                let (remain, values) = count(|i| self.reals::<2>(i), len)(remain)?;
+                Ok((remain, GodotValue::PackedVector2Array(values.into_iter().map(|[x, y]| (x, y)).collect())))
+            }
+            VARIANT_PACKED_VECTOR3_ARRAY => {
+                let (remain, len) = self.len(remain)?;
+                let (remain, values) = count(|i| self.reals::<3>(i), len)(remain)?;
+                Ok((remain, GodotValue::PackedVector3Array(values.into_iter().map(|[x, y, z]| (x, y, z)).collect())))
+            }
+            VARIANT_PACKED_VECTOR4_ARRAY => {
+                let (remain, len) = self.len(remain)?;
+                let (remain, values) = count(|i| self.reals::<4>(i), len)(remain)?;
+                Ok((remain, GodotValue::PackedVector4Array(values.into_iter().map(|[x, y, z, w]| (x, y, z, w)).collect())))
+            }
+            VARIANT_PACKED_COLOR_ARRAY => {
+                let (remain, len) = self.len(remain)?;
+                let (remain, values) = count(
+                    |i| {
+                        let (i, r) = self.f32(i)?;
+                        let (i, g) = self.f32(i)?;
+                        let (i, b) = self.f32(i)?;
+                        let (i, a) = self.f32(i)?;
+                        Ok((i, (r, g, b, a)))
+                    },
+                    len,
+                )(remain)?;
+                Ok((remain, GodotValue::PackedColorArray(values)))
+            }
+            _ => fail(input),
+        }
+    }
+
+    /// Reads the internal resource stored at `offset`: its type and properties.
+    fn resource<'a>(&self, file: &'a [u8], offset: u64) -> IResult<&'a [u8], (String, Properties)> {
+        let Some(input) = file.get(offset as usize..) else {
+            return fail(file);
+        };
+        let (input, resource_type) = self.unicode_string(input)?;
+        let (input, property_count) = self.u32(input)?;
+        let (input, properties) = count(
+            |i| {
+                let (i, name) = self.table_string(i)?;
+                let (i, value) = self.variant(i)?;
+                Ok((i, (name, value)))
+            },
+            property_count as usize,
+        )(input)?;
+        Ok((input, (resource_type, properties)))
+    }
+}
+
+fn string_attr(value: &str) -> GodotValue {
+    GodotValue::String(escape_string(value))
+}
+
+fn bundle_get<'b>(bundle: &'b [(String, GodotValue)], key: &str) -> Option<&'b GodotValue> {
+    bundle.iter().find(|(k, _)| k == key).map(|(_, v)| v)
+}
+
+/// Rebuilds `[node]` and `[connection]` tags from a `PackedScene._bundled` dictionary.
+fn decode_bundled_scene(bundle: &[(String, GodotValue)]) -> Option<(Vec<Tag>, Vec<Tag>)> {
+    let names = match bundle_get(bundle, "names")? {
+        GodotValue::PackedStringArray(names) => names.iter().map(|n| unescape_string(n)).collect::<Vec<_>>(),
+        _ => return None,
+    };
+    let GodotValue::Array(variants) = bundle_get(bundle, "variants")? else {
+        return None;
+    };
+    let GodotValue::PackedInt32Array(node_data) = bundle_get(bundle, "nodes")? else {
+        return None;
+    };
+    let node_paths = match bundle_get(bundle, "node_paths") {
+        Some(GodotValue::Array(paths)) => paths
+            .iter()
+            .map(|p| match p {
+                GodotValue::NodePath(p) => unescape_string(p),
+                _ => String::new(),
+            })
+            .collect(),
+        _ => Vec::new(),
+    };
+    let node_count = match bundle_get(bundle, "node_count") {
+        Some(GodotValue::Integer(n)) => usize::try_from(*n).ok()?,
+        _ => 0,
+    };
+    let name = |index: i32| names.get((index & FLAG_PROP_NAME_MASK) as usize).cloned();
+    let variant = |index: i32| variants.get(index as usize).cloned();
+
+    let mut data = node_data.iter().copied();
+    let mut next = || data.next();
+
+    struct NodeEntry {
+        parent: i32,
+        owner: i32,
+        name: String,
+        tag: Tag,
+    }
+    // The count comes from the file: every node takes several entries of `nodes`, so that
+    // bounds the allocation whatever the count claims.
+    let mut entries: Vec<NodeEntry> = Vec::with_capacity(node_count.min(node_data.len()));
+    for _ in 0..node_count {
+        let parent = next()?;
+        let owner = next()?;
+        let node_type = next()?;
+        let name_data = next()?;
+        let instance = next()?;
+        let mut attrs = HashMap::new();
+        let mut props = HashMap::new();
+
+        let node_name = name(name_data & ((1 << NAME_INDEX_BITS) - 1))?;
+        attrs.insert("name".to_string(), string_attr(&node_name));
+        if node_type != TYPE_INSTANTIATED {
+            attrs.insert("type".to_string(), string_attr(&name(node_type)?));
+        }
+        let index = (name_data as u32 >> NAME_INDEX_BITS) as i64 - 1;
+        if index >= 0 {
+            attrs.insert("index".to_string(), GodotValue::String(index.to_string()));
+        }
+        if instance >= 0 {
+            if instance & FLAG_INSTANCE_IS_PLACEHOLDER != 0 {
+                attrs.insert("instance_placeholder".to_string(), variant(instance & FLAG_MASK)?);
+            } else {
+                attrs.insert("instance".to_string(), variant(instance & FLAG_MASK)?);
+            }
+        }
+
+        let property_count = next()?;
+        let mut path_properties = Vec::new();
+        for _ in 0..property_count {
+            let property_name = next()?;
+            let value = next()?;
+            let key = name(property_name)?;
+            if property_name & FLAG_PATH_PROPERTY_IS_NODE != 0 {
+                path_properties.push(escape_string(&key));
+            }
+            props.insert(key, variant(value)?);
+        }
+        if !path_properties.is_empty() {
+            attrs.insert("node_paths".to_string(), GodotValue::PackedStringArray(path_properties));
+        }
+
+        let group_count = next()?;
+        let mut groups = Vec::new();
+        for _ in 0..group_count {
+            groups.push(string_attr(&name(next()?)?));
+        }
+        if !groups.is_empty() {
+            attrs.insert("groups".to_string(), GodotValue::Array(groups));
+        }
+
+        entries.push(NodeEntry {
+            parent,
+            owner,
+            name: node_name,
+            tag: Tag { _type: TagType::Node, attrs, props },
+        });
+    }
+
+    // Paths relative to the scene root, as used by `parent`, `owner`, `from` and `to`.
+    let mut paths: Vec<String> = Vec::with_capacity(entries.len());
+    for (i, entry) in entries.iter().enumerate() {
+        let path = if entry.parent < 0 || entry.parent == TYPE_INSTANTIATED {
+            ".".to_string()
+        } else {
+            let parent = if entry.parent & FLAG_ID_IS_PATH != 0 {
+                node_paths.get((entry.parent & FLAG_MASK) as usize)?.clone()
+            } else if (entry.parent as usize) < i {
+                paths[entry.parent as usize].clone()
+            } else {
+                return None;
+            };
+            if parent == "." { entry.name.clone() } else { format!("{}/{}", parent, entry.name) }
+        };
+        paths.push(path);
+    }
+    let resolve = |id: i32| -> Option<String> {
+        if id & FLAG_ID_IS_PATH != 0 {
+            node_paths.get((id & FLAG_MASK) as usize).cloned()
+        } else {
+            paths.get(id as usize).cloned()
+        }
+    };
+
+    let mut nodes = Vec::with_capacity(entries.len());
+    for (i, mut entry) in entries.into_iter().enumerate() {
+        if i == 0 && !entry.tag.attrs.contains_key("instance") {
+            if let Some(GodotValue::Integer(base_scene)) = bundle_get(bundle, "base_scene") {
+                entry.tag.attrs.insert("instance".to_string(), variant(*base_scene as i32)?);
+            }
+        }
+        if entry.parent >= 0 && entry.parent != TYPE_INSTANTIATED {
+            let parent = resolve(entry.parent)?;
+            entry.tag.attrs.insert("parent".to_string(), string_attr(&parent));
+        }
+        if entry.owner >= 0 && entry.owner != TYPE_INSTANTIATED {
+            if let Some(owner) = resolve(entry.owner).filter(|o| o != ".") {
+                entry.tag.attrs.insert("owner".to_string(), string_attr(&owner));
+            }
+        }
+        nodes.push(entry.tag);
+    }
+
+    let connection_count = match bundle_get(bundle, "conn_count") {
+        Some(GodotValue::Integer(n)) => usize::try_from(*n).ok()?,
+        _ => 0,
+    };
+    let mut connections = Vec::new();
+    if connection_count > 0 {
+        let GodotValue::PackedInt32Array(connection_data) = bundle_get(bundle, "conns")? else {
+            return None;
+        };
+        connections.reserve(connection_count.min(connection_data.len()));
+        let mut data = connection_data.iter().copied();
+        let mut next = || data.next();
+        for _ in 0..connection_count {
+            let from = resolve(next()?)?;
+            let to = resolve(next()?)?;
+            let signal = name(next()?)?;
+            let method = name(next()?)?;
+            let flags = next()? as i64;
+            let bind_count = next()?;
+            let mut binds = Vec::new();
+            for _ in 0..bind_count {
+                binds.push(variant(next()?)?);
+            }
+            let unbinds = next()?;
+
+            let mut attrs = HashMap::from([
+                ("signal".to_string(), string_attr(&signal)),
+                ("from".to_string(), string_attr(&from)),
+                ("to".to_string(), string_attr(&to)),
+                ("method".to_string(), string_attr(&method)),
+            ]);
+            if flags != CONNECT_PERSIST {
+                attrs.insert("flags".to_string(), GodotValue::Integer(flags));
+            }
+            if unbinds > 0 {
+                attrs.insert("unbinds".to_string(), GodotValue::Integer(unbinds as i64));
+            }
+            if !binds.is_empty() {
+                attrs.insert("binds".to_string(), GodotValue::Array(binds));
+            }
+            connections.push(Tag { _type: TagType::Connection, attrs, props: HashMap::new() });
+        }
+    }
+
+    Some((nodes, connections))
+}
+
+/// Reads a Godot binary resource (`.scn`, `.res`) into the same model `parse_tscn_file`
+/// and `parse_tres_file` produce. External resources get sequential ids (`"1"`, `"2"`, ...),
+/// sub-resources keep their scene-unique ids.
+pub fn parse_rsrc_file(file: &[u8]) -> IResult<&[u8], TSCNFile> {
+    let (input, _) = tag(RSRC_MAGIC)(file)?;
+    let (input, big_endian) = u32(Endianness::Little)(input)?;
+    let endian = if big_endian != 0 { Endianness::Big } else { Endianness::Little };
+    let (input, use_real64) = u32(endian)(input)?;
+    let (input, _ver_major) = u32(endian)(input)?;
+    let (input, _ver_minor) = u32(endian)(input)?;
+    let (input, ver_format) = u32(endian)(input)?;
+    if ver_format > FORMAT_VERSION {
+        return fail(input);
+    }
+
+    let mut reader = Reader {
+        endian,
+        real_is_double: use_real64 != 0,
+        strings: Vec::new(),
+        ext_paths: Vec::new(),
+        ext_ids: Vec::new(),
+        int_ids: Vec::new(),
+    };
+    let (input, main_type) = reader.unicode_string(input)?;
+    let (input, _importmd_ofs) = reader.u64(input)?;
+    let (input, flags) = reader.u32(input)?;
+    let using_uids = flags & FORMAT_FLAG_UIDS != 0;
+    reader.real_is_double |= flags & FORMAT_FLAG_REAL_T_IS_DOUBLE != 0;
+    let (input, uid) = reader.u64(input)?;
+    let (input, script_class) = if flags & FORMAT_FLAG_HAS_SCRIPT_CLASS != 0 {
+        let (input, class) = reader.unicode_string(input)?;
+        (input, Some(class))
+    } else {
+        (input, None)
+    };
+    let (input, _) = take(RESERVED_FIELDS * 4)(input)?;
+
+    let (input, string_count) = reader.u32(input)?;
+    let (input, strings) = count(|i| reader.unicode_string(i), string_count as usize)(input)?;
+    reader.strings = strings;
+
+    let (input, ext_count) = reader.u32(input)?;
+    let mut input = input;
+    let mut ext_resources = HashMap::new();
+    for index in 0..ext_count as usize {
+        let (remain, resource_type) = reader.unicode_string(input)?;
+        let (remain, path) = reader.unicode_string(remain)?;
+        let (remain, ext_uid) = if using_uids { reader.u64(remain)? } else { (remain, u64::MAX) };
+        input = remain;
+
+        let id = (index + 1).to_string();
+        let mut attrs = HashMap::from([
+            ("type".to_string(), string_attr(&resource_type)),
+            ("path".to_string(), string_attr(&path)),
+            ("id".to_string(), GodotValue::String(id.clone())),
+        ]);
+        if let Some(uid) = id_to_text(ext_uid as i64) {
+            attrs.insert("uid".to_string(), GodotValue::String(uid));
+        }
+        ext_resources.insert(id.clone(), Tag { _type: TagType::ExtResource, attrs, props: HashMap::new() });
+        reader.ext_paths.push(path);
+        reader.ext_ids.push(id);
+    }
+
+    let (input, int_count) = reader.u32(input)?;
+    let (input, internal) = count(
+        |i| {
+            let (i, path) = reader.unicode_string(i)?;
+            let (i, offset) = reader.u64(i)?;
+            Ok((i, (path, offset)))
+        },
+        int_count as usize,
+    )(input)?;
+    if internal.is_empty() {
+        return fail(input);
+    }
+    reader.int_ids = internal
+        .iter()
+        .map(|(path, _)| path.strip_prefix(LOCAL_PREFIX).unwrap_or(path).to_string())
+        .collect();
+
+    let mut sub_resources = HashMap::new();
+    let mut main = None;
+    for (index, (_, offset)) in internal.iter().enumerate() {
+        let (_, (resource_type, properties)) = reader.resource(file, *offset)?;
+        if index + 1 == internal.len() {
+            main = Some((resource_type, properties));
+            continue;
+        }
+        let id = reader.int_ids[index].clone();
+        let attrs = HashMap::from([
+            ("type".to_string(), string_attr(&resource_type)),
+            ("id".to_string(), GodotValue::String(id.clone())),
+        ]);
+        sub_resources.insert(id, Tag { _type: TagType::SubResource, attrs, props: properties.into_iter().collect() });
+    }
+    let (main_type_read, main_properties) = main.unwrap();
+
+    let mut header_attrs = HashMap::from([("format".to_string(), GodotValue::Integer(3))]);
+    let load_steps = (ext_resources.len() + sub_resources.len() + 1) as i64;
+    if load_steps > 1 {
+        header_attrs.insert("load_steps".to_string(), GodotValue::Integer(load_steps));
+    }
+    if let Some(uid) = id_to_text(uid as i64).filter(|_| using_uids) {
+        header_attrs.insert("uid".to_string(), GodotValue::String(uid));
+    }
+
+    let (header_type, nodes, connections) = if main_type == "PackedScene" || main_type_read == "PackedScene" {
+        let Some(GodotValue::Dictionary(bundle)) =
+            main_properties.iter().find(|(k, _)| k == "_bundled").map(|(_, v)| v)
+        else {
+            return fail(input);
+        };
+        let Some((nodes, connections)) = decode_bundled_scene(bundle) else {
+            return fail(input);
+        };
+        (TagType::GdScene, nodes, connections)
+    } else {
+        header_attrs.insert("type".to_string(), string_attr(&main_type));
+        if let Some(script_class) = &script_class {
+            header_attrs.insert("script_class".to_string(), string_attr(script_class));
+        }
+        let resource = Tag {
+            _type: TagType::Resource,
+            attrs: HashMap::new(),
+            props: main_properties.into_iter().collect(),
+        };
+        (TagType::GdResource, vec![resource], Vec::new())
+    };
+
+    Ok((
+        &file[file.len()..],
+        TSCNFile {
+            header: Tag { _type: header_type, attrs: header_attrs, props: HashMap::new() },
+            ext_resources,
+            sub_resources,
+            nodes,
+            connections,
+        },
+    ))
+}
+
+fn attr_str<'t>(tag: &'t Tag, key: &str) -> Option<&'t str> {
+    match tag.attrs.get(key)? {
+        GodotValue::String(s) | GodotValue::StringName(s) => Some(s),
+        _ => None,
+    }
+}
+
+fn attr_i32(tag: &Tag, key: &str) -> Option<i32> {
+    match tag.attrs.get(key)? {
+        GodotValue::Integer(i) => i32::try_from(*i).ok(),
+        GodotValue::String(s) => s.parse().ok(),
+        _ => None,
+    }
+}
+
+/// Ids in file order: numeric prefixes (`"2_x1c4r"`, `"10"`) compare as numbers.
+fn sorted_ids(tags: &HashMap<String, Tag>) -> Vec<String> {
+    let mut ids = tags.keys().cloned().collect::<Vec<_>>();
+    ids.sort_by_key(|id| (id.split('_').next().and_then(|n| n.parse::<u64>().ok()), id.clone()));
+    ids
+}
+
+/// Property names with `script` first, so script-defined properties exist when they are set.
+fn property_order(properties: &HashMap<String, GodotValue>) -> Vec<&String> {
+    let mut keys = properties.keys().collect::<Vec<_>>();
+    keys.sort_by_key(|k| (*k != "script", *k));
+    keys
+}
+
+fn collect_sub_links<'v>(value: &'v GodotValue, links: &mut Vec<&'v str>) {
+    match value {
+        GodotValue::SubResourceLink(id) => links.push(id),
+        GodotValue::Array(values) => values.iter().for_each(|v| collect_sub_links(v, links)),
+        GodotValue::Dictionary(entries) | GodotValue::Object((_, entries)) => {
+            entries.iter().for_each(|(_, v)| collect_sub_links(v, links))
+        }
+        _ => {}
+    }
+}
+
+/// Sub-resource ids ordered so that each comes after the sub-resources it references, as the
+/// loader resolves internal references while reading.
+fn dependency_order(sub_resources: &HashMap<String, Tag>) -> Vec<String> {
+    fn visit(id: &str, sub_resources: &HashMap<String, Tag>, order: &mut Vec<String>, visiting: &mut Vec<String>) {
+        if order.iter().any(|o| o == id) || visiting.iter().any(|v| v == id) {
+            return;
+        }
+        let Some(tag) = sub_resources.get(id) else {
+            return;
+        };
+        visiting.push(id.to_string());
+        let mut links = Vec::new();
+        property_order(&tag.props).into_iter().for_each(|key| collect_sub_links(&tag.props[key], &mut links));
+        links.into_iter().for_each(|link| visit(link, sub_resources, order, visiting));
+        order.push(id.to_string());
+    }
+
+    let mut order = Vec::with_capacity(sub_resources.len());
+    for id in sorted_ids(sub_resources) {
+        visit(&id, sub_resources, &mut order, &mut Vec::new());
+    }
+    order
+}
+
+/// Names, values and node paths of a `PackedScene._bundled` dictionary being built.
+#[derive(Default)]
+struct SceneState {
+    names: Vec<String>,
+    variants: Vec<GodotValue>,
+    node_paths: Vec<String>,
+}
+
+impl SceneState {
+    fn name(&mut self, name: &str) -> i32 {
+        match self.names.iter().position(|n| n == name) {
+            Some(index) => index as i32,
+            None => {
+                self.names.push(name.to_string());
+                self.names.len() as i32 - 1
+            }
+        }
+    }
+
+    fn value(&mut self, value: &GodotValue) -> i32 {
+        self.variants.push(value.clone());
+        self.variants.len() as i32 - 1
+    }
+
+    fn node_path(&mut self, path: &str) -> i32 {
+        let index = match self.node_paths.iter().position(|p| p == path) {
+            Some(index) => index,
+            None => {
+                self.node_paths.push(path.to_string());
+                self.node_paths.len() - 1
+            }
+        };
+        index as i32 | FLAG_ID_IS_PATH
+    }
+}
+
+/// Packs `[node]` and `[connection]` tags into a `PackedScene._bundled` dictionary, the way
+/// Godot's text loader does: parents, owners and connection ends are stored as node paths.
+fn encode_bundled_scene(nodes: &[Tag], connections: &[Tag]) -> Option<GodotValue> {
+    let mut state = SceneState::default();
+    let mut base_scene = None;
+
+    let mut node_data = Vec::new();
+    for (i, node) in nodes.iter().enumerate() {
+        let parent = attr_str(node, "parent").map(|p| state.node_path(p));
+        let owner = match attr_str(node, "owner") {
+            Some(owner) => state.node_path(owner),
+            None if parent.is_some() => 0,
+            None => -1,
+        };
+        let node_type = match attr_str(node, "type") {
+            Some(node_type) => state.name(node_type),
+            None => TYPE_INSTANTIATED,
+        };
+        let mut name = state.name(attr_str(node, "name")?);
+        if let Some(index) = attr_i32(node, "index") {
+            name |= (index + 1) << NAME_INDEX_BITS;
+        }
+        let mut instance = -1;
+        if let Some(value) = node.attrs.get("instance") {
+            let value = state.value(value);
+            if i == 0 && parent.is_none() {
+                base_scene = Some(value);
+            } else {
+                instance = value;
+            }
+        } else if let Some(value) = node.attrs.get("instance_placeholder") {
+            instance = state.value(value) | FLAG_INSTANCE_IS_PLACEHOLDER;
+        }
+        node_data.extend([parent.unwrap_or(-1), owner, node_type, name, instance]);
+
+        let path_properties = match node.attrs.get("node_paths") {
+            Some(GodotValue::PackedStringArray(paths)) => paths.iter().map(|p| unescape_string(p)).collect(),
+            _ => Vec::new(),
+        };
+        node_data.push(node.props.len() as i32);
+        for key in property_order(&node.props) {
+            let mut property_name = state.name(&escape_string(key));
+            if path_properties.contains(key) {
+                property_name |= FLAG_PATH_PROPERTY_IS_NODE;
+            }
+            node_data.extend([property_name, state.value(&node.props[key])]);
+        }
+
+        let groups = match node.attrs.get("groups") {
+            Some(GodotValue::Array(groups)) => groups
+                .iter()
+                .filter_map(|g| match g {
+                    GodotValue::String(g) | GodotValue::StringName(g) => Some(state.name(g)),
+                    _ => None,
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        node_data.push(groups.len() as i32);
+        node_data.extend(groups);
+    }
+
+    let mut connection_data = Vec::new();
+    for connection in connections {
+        let from = state.node_path(attr_str(connection, "from")?);
+        let to = state.node_path(attr_str(connection, "to")?);
+        let signal = state.name(attr_str(connection, "signal")?);
+        let method = state.name(attr_str(connection, "method")?);
+        let flags = attr_i32(connection, "flags").unwrap_or(CONNECT_PERSIST as i32);
+        connection_data.extend([from, to, signal, method, flags]);
+        let binds = match connection.attrs.get("binds") {
+            Some(GodotValue::Array(binds)) => binds.iter().map(|b| state.value(b)).collect(),
+            _ => Vec::new(),
+        };
+        connection_data.push(binds.len() as i32);
+        connection_data.extend(binds);
+        connection_data.push(attr_i32(connection, "unbinds").unwrap_or(0));
+    }
+
+    let mut bundle = vec![
+        ("names", GodotValue::PackedStringArray(state.names)),
+        ("variants", GodotValue::Array(state.variants)),
+        ("node_count", GodotValue::Integer(nodes.len() as i64)),
+        ("nodes", GodotValue::PackedInt32Array(node_data)),
+        ("conn_count", GodotValue::Integer(connections.len() as i64)),
+        ("conns", GodotValue::PackedInt32Array(connection_data)),
+        ("node_paths", GodotValue::Array(state.node_paths.into_iter().map(GodotValue::NodePath).collect())),
+        ("editable_instances", GodotValue::Array(Vec::new())),
+    ];
+    if let Some(base_scene) = base_scene {
+        bundle.push(("base_scene", GodotValue::Integer(base_scene as i64)));
+    }
+    bundle.push(("version", GodotValue::Integer(PACKED_SCENE_VERSION)));
+    Some(GodotValue::Dictionary(bundle.into_iter().map(|(k, v)| (k.to_string(), v)).collect()))
+}
+
+pub(crate) fn put_u32(out: &mut Vec<u8>, value: u32) {
+    out.extend(value.to_le_bytes());
+}
+
+pub(crate) fn put_i32(out: &mut Vec<u8>, value: i32) {
+    out.extend(value.to_le_bytes());
+}
+
+pub(crate) fn put_u64(out: &mut Vec<u8>, value: u64) {
+    out.extend(value.to_le_bytes());
+}
+
+pub(crate) fn put_f32(out: &mut Vec<u8>, value: f64) {
+    out.extend((value as f32).to_le_bytes());
+}
+
+fn put_unicode_string(out: &mut Vec<u8>, value: &str) {
+    put_u32(out, value.len() as u32 + 1);
+    out.extend(value.as_bytes());
+    out.push(0);
+}
+
+/// Encodes resources little endian with 32-bit reals, as Godot's own exports do.
+#[derive(Default)]
+struct Writer {
+    strings: Vec<String>,
+    string_ids: HashMap<String, u32>,
+    ext_ids: Vec<String>,
+    int_ids: Vec<String>,
+    uses_vector4_array: bool,
+}
+
+impl Writer {
+    fn string_index(&mut self, value: &str) -> u32 {
+        if let Some(id) = self.string_ids.get(value) {
+            return *id;
+        }
+        let id = self.strings.len() as u32;
+        self.strings.push(value.to_string());
+        self.string_ids.insert(value.to_string(), id);
+        id
+    }
+
+    fn reals(&self, out: &mut Vec<u8>, values: &[f64]) {
+        values.iter().for_each(|v| put_f32(out, *v));
+    }
+
+    fn node_path(&mut self, out: &mut Vec<u8>, path: &str) {
+        let path = unescape_string(path);
+        let (names, subnames) = match path.split_once(':') {
+            Some((names, subnames)) => (names, subnames.split(':').collect::<Vec<_>>()),
+            None => (path.as_str(), Vec::new()),
+        };
+        let absolute = names.starts_with('/');
+        let names = names.split('/').filter(|n| !n.is_empty()).collect::<Vec<_>>();
+        out.extend((names.len() as u16).to_le_bytes());
+        out.extend((subnames.len() as u16 | if absolute { 0x8000 } else { 0 }).to_le_bytes());
+        for name in names.into_iter().chain(subnames) {
+            let id = self.string_index(name);
+            put_u32(out, id);
+        }
+    }
+
+    fn object(&self, out: &mut Vec<u8>, object_type: u32, ids: &[String], id: &str) -> Option<()> {
+        let index = ids.iter().position(|i| i == id)?;
+        put_u32(out, VARIANT_OBJECT);
+        put_u32(out, object_type);
+        put_u32(out, index as u32);
+        Some(())
+    }
+
+    fn variant(&mut self, out: &mut Vec<u8>, value: &GodotValue) -> Option<()> {
+        match value {
+            GodotValue::Null => put_u32(out, VARIANT_NIL),
+            GodotValue::Boolean(value) => {
+                put_u32(out, VARIANT_BOOL);
+                put_u32(out, *value as u32);
+            }
+            GodotValue::Integer(value) => match i32::try_from(*value) {
+                Ok(value) => {
+                    put_u32(out, VARIANT_INT);
+                    put_i32(out, value);
+                }
+                Err(_) => {
+                    put_u32(out, VARIANT_INT64);
+                    out.extend(value.to_le_bytes());
+                }
+            },
+            GodotValue::Float(value) => {
+                if (*value as f32) as f64 == *value {
+                    put_u32(out, VARIANT_FLOAT);
+                    put_f32(out, *value);
+                } else {
+                    put_u32(out, VARIANT_DOUBLE);
+                    out.extend(value.to_le_bytes());
+                }
+            }
+            GodotValue::String(value) => {
+                put_u32(out, VARIANT_STRING);
+                put_unicode_string(out, &unescape_string(value));
+            }
+            GodotValue::StringName(value) => {
+                put_u32(out, VARIANT_STRING_NAME);
+                put_unicode_string(out, &unescape_string(value));
+            }
+            GodotValue::NodePath(path) => {
+                put_u32(out, VARIANT_NODE_PATH);
+                self.node_path(out, path);
+            }
+            GodotValue::Vector2((x, y)) => {
+                put_u32(out, VARIANT_VECTOR2);
+                self.reals(out, &[*x, *y]);
+            }
+            GodotValue::Vector2i((x, y)) => {
+                put_u32(out, VARIANT_VECTOR2I);
+                [*x, *y].into_iter().for_each(|v| put_i32(out, v));
+            }
+            GodotValue::Rect2((x, y, w, h)) => {
+                put_u32(out, VARIANT_RECT2);
+                self.reals(out, &[*x, *y, *w, *h]);
+            }
+            GodotValue::Rect2i((x, y, w, h)) => {
+                put_u32(out, VARIANT_RECT2I);
+                [*x, *y, *w, *h].into_iter().for_each(|v| put_i32(out, v));
+            }
+            GodotValue::Vector3((x, y, z)) => {
+                put_u32(out, VARIANT_VECTOR3);
+                self.reals(out, &[*x, *y, *z]);
+            }
+            GodotValue::Vector3i((x, y, z)) => {
+                put_u32(out, VARIANT_VECTOR3I);
+                [*x, *y, *z].into_iter().for_each(|v| put_i32(out, v));
+            }
+            GodotValue::Vector4((x, y, z, w)) => {
+                put_u32(out, VARIANT_VECTOR4);
+                self.reals(out, &[*x, *y, *z, *w]);
+            }
+            GodotValue::Vector4i((x, y, z, w)) => {
+                put_u32(out, VARIANT_VECTOR4I);
+                [*x, *y, *z, *w].into_iter().for_each(|v| put_i32(out, v));
+            }
+            GodotValue::Plane((a, b, c, d)) => {
+                put_u32(out, VARIANT_PLANE);
+                self.reals(out, &[*a, *b, *c, *d]);
+            }
+            GodotValue::Quaternion((x, y, z, w)) => {
+                put_u32(out, VARIANT_QUATERNION);
+                self.reals(out, &[*x, *y, *z, *w]);
+            }
+            GodotValue::AABB(values) => {
+                put_u32(out, VARIANT_AABB);
+                self.reals(out, values);
+            }
+            GodotValue::Basis(values) => {
+                put_u32(out, VARIANT_BASIS);
+                self.reals(out, values);
+            }
+            GodotValue::Transform2D(values) => {
+                put_u32(out, VARIANT_TRANSFORM2D);
+                self.reals(out, values);
+            }
+            GodotValue::Transform3D(values) => {
+                put_u32(out, VARIANT_TRANSFORM3D);
+                self.reals(out, values);
+            }
+            GodotValue::Projection(values) => {
+                put_u32(out, VARIANT_PROJECTION);
+                self.reals(out, values);
+            }
+            GodotValue::Color((r, g, b, a)) => {
+                put_u32(out, VARIANT_COLOR);
+                [*r, *g, *b, *a].into_iter().for_each(|v| put_f32(out, v));
+            }
+            GodotValue::ExtResourceLink(id) => self.object(out, OBJECT_EXTERNAL_RESOURCE_INDEX, &self.ext_ids, id)?,
+            GodotValue::SubResourceLink(id) => self.object(out, OBJECT_INTERNAL_RESOURCE, &self.int_ids, id)?,
+            // Only resources can be stored as objects.
+            GodotValue::Object(_) => return None,
+            GodotValue::Array(values) => {
+                put_u32(out, VARIANT_ARRAY);
+                put_u32(out, values.len() as u32);
+                for value in values {
+                    self.variant(out, value)?;
+                }
+            }
+            GodotValue::Dictionary(entries) => {
+                put_u32(out, VARIANT_DICTIONARY);
+                put_u32(out, entries.len() as u32);
+                for (key, value) in entries {
+                    put_u32(out, VARIANT_STRING);
+                    put_unicode_string(out, &unescape_string(key));
+                    self.variant(out, value)?;
+                }
+            }
+            GodotValue::PackedByteArray(values) => {
+                put_u32(out, VARIANT_PACKED_BYTE_ARRAY);
+                put_u32(out, values.len() as u32);
+                out.extend(values);
+                out.extend(std::iter::repeat_n(0, (4 - values.len() % 4) % 4));
+            }
+            GodotValue::PackedInt32Array(values) => {
+                put_u32(out, VARIANT_PACKED_INT32_ARRAY);
+                put_u32(out, values.len() as u32);
+                values.iter().for_each(|v| put_i32(out, *v));
+            }
+            GodotValue::PackedInt64Array(values) => {
+                put_u32(out, VARIANT_PACKED_INT64_ARRAY);
+                put_u32(out, values.len() as u32);
+                values.iter().for_each(|v| out.extend(v.to_le_bytes()));
+            }
+            GodotValue::PackedFloat32Array(values) => {
+                put_u32(out, VARIANT_PACKED_FLOAT32_ARRAY);
+                put_u32(out, values.len() as u32);
+                values.iter().for_each(|v| out.extend(v.to_le_bytes()));
+            }
+            GodotValue::PackedFloat64Array(values) => {
+                put_u32(out, VARIANT_PACKED_FLOAT64_ARRAY);
+                put_u32(out, values.len() as u32);
+                values.iter().for_each(|v| out.extend(v.to_le_bytes()));
+            }
+            GodotValue::PackedStringArray(values) => {
+                put_u32(out, VARIANT_PACKED_STRING_ARRAY);
+                put_u32(out, values.len() as u32);
+                values.iter().for_each(|v| put_unicode_string(out, &unescape_string(v)));
+            }
+            GodotValue::PackedVector2Array(values) => {
+                put_u32(out, VARIANT_PACKED_VECTOR2_ARRAY);
+                put_u32(out, values.len() as u32);
+                values.iter().for_each(|(x, y)| self.reals(out, &[*x, *y]));
+            }
+            GodotValue::PackedVector3Array(values) => {
+                put_u32(out, VARIANT_PACKED_VECTOR3_ARRAY);
+                put_u32(out, values.len() as u32);
+                values.iter().for_each(|(x, y, z)| self.reals(out, &[*x, *y, *z]));
+            }
+            GodotValue::PackedVector4Array(values) => {
+                self.uses_vector4_array = true;
+                put_u32(out, VARIANT_PACKED_VECTOR4_ARRAY);
+                put_u32(out, values.len() as u32);
+                values.iter().for_each(|(x, y, z, w)| self.reals(out, &[*x, *y, *z, *w]));
+            }
+            GodotValue::PackedColorArray(values) => {
+                put_u32(out, VARIANT_PACKED_COLOR_ARRAY);
+                put_u32(out, values.len() as u32);
+                values.iter().for_each(|(r, g, b, a)| [*r, *g, *b, *a].into_iter().for_each(|v| put_f32(out, v)));
+            }
+        }
+        Some(())
+    }
+
+    fn resource(&mut self, resource_type: &str, properties: &HashMap<String, GodotValue>) -> Option<Vec<u8>> {
+        let mut out = Vec::new();
+        put_unicode_string(&mut out, &unescape_string(resource_type));
+        put_u32(&mut out, properties.len() as u32);
+        for key in property_order(properties) {
+            let id = self.string_index(key);
+            put_u32(&mut out, id);
+            self.variant(&mut out, &properties[key])?;
+        }
+        Some(out)
+    }
+}
+
+/// Writes a scene or resource as a Godot 4 binary resource (`.scn`, `.res`), the counterpart
+/// of [`parse_rsrc_file`]. Returns `None` when the file references unknown resources, lacks
+/// required attributes or holds values a binary resource can't store, like inline `Object(...)`s.
+pub fn write_rsrc_file(file: &TSCNFile) -> Option<Vec<u8>> {
+    let mut writer = Writer {
+        ext_ids: sorted_ids(&file.ext_resources),
+        int_ids: dependency_order(&file.sub_resources),
+        ..Writer::default()
+    };
+
+    let (main_type, main_properties, script_class) = match file.header._type {
+        TagType::GdScene => (
+            "PackedScene",
+            HashMap::from([("_bundled".to_string(), encode_bundled_scene(&file.nodes, &file.connections)?)]),
+            None,
+        ),
+        TagType::GdResource => (
+            attr_str(&file.header, "type")?,
+            file.nodes
+                .iter()
+                .find(|tag| tag._type == TagType::Resource)
+                .map(|tag| tag.props.clone())
+                .unwrap_or_default(),
+            attr_str(&file.header, "script_class"),
+        ),
+        _ => return None,
+    };
+
+    let mut bodies = Vec::with_capacity(writer.int_ids.len() + 1);
+    for id in writer.int_ids.clone() {
+        let tag = &file.sub_resources[&id];
+        bodies.push(writer.resource(attr_str(tag, "type")?, &tag.props)?);
+    }
+    bodies.push(writer.resource(main_type, &main_properties)?);
+
+    let mut out = Vec::new();
+    out.extend(RSRC_MAGIC);
+    put_u32(&mut out, 0);
+    put_u32(&mut out, 0);
+    if writer.uses_vector4_array {
+        [4, 3, FORMAT_VERSION].into_iter().for_each(|v| put_u32(&mut out, v));
+    } else {
+        [4, 0, FORMAT_VERSION_NO_VECTOR4_ARRAY].into_iter().for_each(|v| put_u32(&mut out, v));
+    }
+    put_unicode_string(&mut out, &unescape_string(main_type));
+    put_u64(&mut out, 0);
+    let mut flags = FORMAT_FLAG_NAMED_SCENE_IDS | FORMAT_FLAG_UIDS;
+    if script_class.is_some() {
+        flags |= FORMAT_FLAG_HAS_SCRIPT_CLASS;
+    }
+    put_u32(&mut out, flags);
+    let uid = attr_str(&file.header, "uid").and_then(text_to_id).unwrap_or(INVALID_UID);
+    put_u64(&mut out, uid as u64);
+    if let Some(script_class) = script_class {
+        put_unicode_string(&mut out, &unescape_string(script_class));
+    }
+    (0..RESERVED_FIELDS).for_each(|_| put_u32(&mut out, 0));
+
+    put_u32(&mut out, writer.strings.len() as u32);
+    writer.strings.iter().for_each(|s| put_unicode_string(&mut out, s));
+
+    put_u32(&mut out, writer.ext_ids.len() as u32);
+    for id in &writer.ext_ids {
+        let tag = &file.ext_resources[id];
+        put_unicode_string(&mut out, &unescape_string(attr_str(tag, "type")?));
+        put_unicode_string(&mut out, &unescape_string(attr_str(tag, "path")?));
+        let uid = attr_str(tag, "uid").and_then(text_to_id).unwrap_or(INVALID_UID);
+        put_u64(&mut out, uid as u64);
+    }
+
+    // The main resource comes last; its path is ignored when loading.
+    let paths = writer
+        .int_ids
+        .iter()
+        .map(|id| format!("{}{}", LOCAL_PREFIX, unescape_string(id)))
+        .chain([String::new()])
+        .collect::<Vec<_>>();
+    put_u32(&mut out, paths.len() as u32);
+    let mut offset = out.len() + paths.iter().map(|p| 4 + p.len() + 1 + 8).sum::<usize>();
+    for (path, body) in paths.iter().zip(&bodies) {
+        put_unicode_string(&mut out, path);
+        put_u64(&mut out, offset as u64);
+        offset += body.len();
+    }
+    bodies.iter().for_each(|body| out.extend(body));
+    out.extend(RSRC_MAGIC);
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::uid::text_to_id;
+    use super::*;
+
+    #[derive(Default)]
+    struct Bytes(Vec<u8>);
+
+    impl Bytes {
+        fn u32(&mut self, v: u32) -> &mut Self {
+            self.0.extend(v.to_le_bytes());
+            self
+        }
+        fn i32s(&mut self, values: &[i32]) -> &mut Self {
+            self.u32(VARIANT_PACKED_INT32_ARRAY).u32(values.len() as u32);
+            values.iter().for_each(|v| self.0.extend(v.to_le_bytes()));
+            self
+        }
+        fn u64(&mut self, v: u64) -> &mut Self {
+            self.0.extend(v.to_le_bytes());
+            self
+        }
+        fn f32(&mut self, v: f32) -> &mut Self {
+            self.0.extend(v.to_le_bytes());
+            self
+        }
+        fn string(&mut self, s: &str) -> &mut Self {
+            self.u32(s.len() as u32 + 1);
+            self.0.extend(s.as_bytes());
+            self.0.push(0);
+            self
+        }
+        fn header(&mut self, resource_type: &str, uid: &str) -> &mut Self {
+            self.0.extend(RSRC_MAGIC);
+            self.u32(0).u32(0).u32(4).u32(3).u32(FORMAT_VERSION).string(resource_type).u64(0);
+            self.u32(FORMAT_FLAG_UIDS | 1).u64(text_to_id(uid).unwrap() as u64);
+            (0..RESERVED_FIELDS).for_each(|_| {
+                self.u32(0);
+            });
+            self
+        }
+        fn strings(&mut self, strings: &[&str]) -> &mut Self {
+            self.u32(strings.len() as u32);
+            strings.iter().for_each(|s| {
+                self.string(s);
+            });
+            self
+        }
+        /// Writes the internal resource table with offsets pointing right behind it.
+        fn internal(&mut self, resources: &[(&str, Bytes)]) -> &mut Self {
+            self.u32(resources.len() as u32);
+            let table_end = self.0.len() + resources.iter().map(|(path, _)| 4 + path.len() + 1 + 8).sum::<usize>();
+            let mut offset = table_end as u64;
+            for (path, body) in resources {
+                self.string(path).u64(offset);
+                offset += body.0.len() as u64;
+            }
+            resources.iter().for_each(|(_, body)| self.0.extend(&body.0));
+            self.0.extend(RSRC_MAGIC);
+            self
+        }
+    }
+
+    #[test]
+    fn test_parse_rsrc_resource() {
+        let mut file = Bytes::default();
+        file.header("AtlasTexture", "uid://bcjbib14mot8s").strings(&["atlas", "region", "text"]);
+        file.u32(1).string("Texture2D").string("res://atlases/icons.png").u64(text_to_id("uid://bqov4kuchixhi").unwrap() as u64);
+
+        let mut sub = Bytes::default();
+        sub.string("LabelSettings").u32(1).u32(2).u32(VARIANT_STRING).string("say \"hi\"");
+        let mut main = Bytes::default();
+        main.string("AtlasTexture").u32(2);
+        main.u32(0).u32(VARIANT_OBJECT).u32(OBJECT_EXTERNAL_RESOURCE_INDEX).u32(0);
+        main.u32(1).u32(VARIANT_RECT2).f32(1.0).f32(1483.0).f32(245.5).f32(0.7);
+        file.internal(&[("local://LabelSettings_4h1rj", sub), ("res://icons.tres", main)]);
+
+        let (remain, res) = parse_rsrc_file(&file.0).unwrap();
+        assert!(remain.is_empty());
+        assert_eq!(res.header._type, TagType::GdResource);
+        assert_eq!(res.header.attrs["type"], GodotValue::String("AtlasTexture".to_string()));
+        assert_eq!(res.header.attrs["uid"], GodotValue::String("uid://bcjbib14mot8s".to_string()));
+        assert_eq!(res.header.attrs["load_steps"], GodotValue::Integer(3));
+        assert_eq!(res.ext_resources["1"].attrs["uid"], GodotValue::String("uid://bqov4kuchixhi".to_string()));
+        assert_eq!(
+            res.sub_resources["LabelSettings_4h1rj"].props["text"],
+            GodotValue::String("say \\\"hi\\\"".to_string())
+        );
+        let resource = &res.nodes[0];
+        assert_eq!(resource._type, TagType::Resource);
+        assert_eq!(resource.props["atlas"], GodotValue::ExtResourceLink("1".to_string()));
+        assert_eq!(resource.props["region"], GodotValue::Rect2((1.0, 1483.0, 245.5, 0.7)));
+    }
+
+    #[test]
+    fn test_dictionary_keys() {
+        let resource = |key: Bytes| {
+            let mut file = Bytes::default();
+            file.header("Resource", "uid://bcjbib14mot8s").strings(&["data"]).u32(0);
+            let mut main = Bytes::default();
+            main.string("Resource").u32(1).u32(0).u32(VARIANT_DICTIONARY).u32(1);
+            main.0.extend(key.0);
+            main.u32(VARIANT_STRING).string("one");
+            file.internal(&[("res://data.res", main)]);
+            file
+        };
+        let mut key = Bytes::default();
+        key.u32(VARIANT_STRING).string("1");
+        let (_, res) = parse_rsrc_file(&resource(key).0).unwrap();
+        assert_eq!(
+            res.nodes[0].props["data"],
+            GodotValue::Dictionary(vec![("1".to_string(), GodotValue::String("one".to_string()))])
+        );
+        // An integer key has no text form that keeps it apart from the string "1".
+        let mut key = Bytes::default();
+        key.u32(VARIANT_INT).u32(1);
+        assert!(parse_rsrc_file(&resource(key).0).is_err());
+    }
+
+    fn bundled_scene(node_count: u32, conn_count: u32) -> Bytes {
+        let mut file = Bytes::default();
+        file.header("PackedScene", "uid://lrpk7b420cd7").strings(&["_bundled"]);
+        file.u32(0);
+
+        let names = ["Game", "Node2D", "scores", "Label", "text", "pressed", "_on_pressed", "ui"];
+        let mut main = Bytes::default();
+        main.string("PackedScene").u32(1).u32(0).u32(VARIANT_DICTIONARY).u32(6);
+        main.u32(VARIANT_STRING).string("names").u32(VARIANT_PACKED_STRING_ARRAY).u32(names.len() as u32);
+        names.iter().for_each(|n| {
+            main.string(n);
+        });
+        main.u32(VARIANT_STRING).string("variants").u32(VARIANT_ARRAY).u32(1).u32(VARIANT_STRING).string("ПРИВ");
+        main.u32(VARIANT_STRING).string("node_count").u32(VARIANT_INT).u32(node_count);
+        main.u32(VARIANT_STRING).string("nodes").i32s(&[-1, -1, 1, 0, -1, 0, 0, 0, 0, 3, 2, -1, 1, 4, 0, 1, 7]);
+        main.u32(VARIANT_STRING).string("conn_count").u32(VARIANT_INT).u32(conn_count);
+        main.u32(VARIANT_STRING).string("conns").i32s(&[1, 0, 5, 6, 2, 0, 0]);
+        file.internal(&[("res://game.scn", main)]);
+        file
+    }
+
+    #[test]
+    fn test_parse_rsrc_scene() {
+        let file = bundled_scene(2, 1);
+        let (_, scene) = parse_rsrc_file(&file.0).unwrap();
+        assert_eq!(scene.header._type, TagType::GdScene);
+        assert!(!scene.header.attrs.contains_key("load_steps"));
+        assert_eq!(scene.nodes.len(), 2);
+        let root = &scene.nodes[0];
+        assert_eq!(root.attrs["name"], GodotValue::String("Game".to_string()));
+        assert_eq!(root.attrs["type"], GodotValue::String("Node2D".to_string()));
+        assert!(!root.attrs.contains_key("parent"));
+        let label = &scene.nodes[1];
+        assert_eq!(label.attrs["parent"], GodotValue::String(".".to_string()));
+        assert_eq!(label.attrs["groups"], GodotValue::Array(vec![GodotValue::String("ui".to_string())]));
+        assert_eq!(label.props["text"], GodotValue::String("ПРИВ".to_string()));
+        let connection = &scene.connections[0];
+        assert_eq!(connection.attrs["from"], GodotValue::String("scores".to_string()));
+        assert_eq!(connection.attrs["to"], GodotValue::String(".".to_string()));
+        assert_eq!(connection.attrs["method"], GodotValue::String("_on_pressed".to_string()));
+        assert!(!connection.attrs.contains_key("flags"));
+
+        // Counts past the data, or negative ones, are errors rather than huge allocations.
+        for (node_count, conn_count) in [(u32::MAX, 1), (2, u32::MAX), (i32::MAX as u32, 1), (2, i32::MAX as u32)] {
+            assert!(parse_rsrc_file(&bundled_scene(node_count, conn_count).0).is_err());
+        }
+    }
+
+    fn assert_same_tags(left: &[Tag], right: &[Tag]) {
+        assert_eq!(left.len(), right.len());
+        for (l, r) in left.iter().zip(right) {
+            assert_eq!(l._type, r._type);
+            assert_eq!(l.attrs, r.attrs);
+            assert_eq!(l.props, r.props);
+        }
+    }
+
+    #[test]
+    fn test_write_rsrc_scene() {
+        let input = r#"[gd_scene load_steps=5 format=3 uid="uid://lrpk7b420cd7"]
+
+[ext_resource type="PackedScene" uid="uid://bqov4kuchixhi" path="res://base.tscn" id="1"]
+[ext_resource type="Texture2D" path="res://icon.png" id="2"]
+
+[sub_resource type="Gradient" id="Gradient_b"]
+colors = PackedColorArray(0, 0, 0, 1, 1, 0.5, 0.25, 1)
+
+[sub_resource type="GradientTexture1D" id="GradientTexture1D_a"]
+gradient = SubResource("Gradient_b")
+
+[node name="Game" instance=ExtResource("1")]
+title = "say \"hi\""
+
+[node name="Sprite" type="Sprite2D" parent="." index="0" groups=["ui", "shaded"]]
+texture = ExtResource("2")
+offset = Vector2(1.5, -2)
+position = Vector3(1, 2, 3)
+metadata/big = 5000000000
+metadata/ratio = 0.7
+
+[node name="Tip" type="Label" parent="Sprite" node_paths=PackedStringArray("target")]
+target = NodePath("../..:position")
+material = SubResource("GradientTexture1D_a")
+
+[connection signal="pressed" from="Sprite/Tip" to="." method="_on_pressed" flags=3 binds=[1]]
+"#;
+        let (_, scene) = crate::tscn_tres_parser::parse_tscn_file(input).unwrap();
+        let binary = write_rsrc_file(&scene).unwrap();
+        assert_eq!(&binary[..4], RSRC_MAGIC);
+        assert_eq!(&binary[binary.len() - 4..], RSRC_MAGIC);
+
+        let (_, read) = parse_rsrc_file(&binary).unwrap();
+        assert_eq!(read.header._type, TagType::GdScene);
+        assert_eq!(read.header.attrs["uid"], scene.header.attrs["uid"]);
+        assert_eq!(read.header.attrs["load_steps"], GodotValue::Integer(5));
+        assert_eq!(read.ext_resources["1"].attrs, scene.ext_resources["1"].attrs);
+        assert_eq!(read.sub_resources["Gradient_b"].props, scene.sub_resources["Gradient_b"].props);
+        assert_eq!(
+            read.sub_resources["GradientTexture1D_a"].props,
+            scene.sub_resources["GradientTexture1D_a"].props
+        );
+        assert_same_tags(&read.nodes, &scene.nodes);
+        assert_same_tags(&read.connections, &scene.connections);
+    }
+
+    #[test]
+    fn test_write_rsrc_resource() {
+        let input = r#"[gd_resource type="Resource" script_class="Stats" load_steps=2 format=3]
+
+[ext_resource type="Script" path="res://stats.gd" id="1"]
+
+[resource]
+script = ExtResource("1")
+name = &"hero"
+levels = PackedInt64Array(1, 2, 4)
+extra = {
+"path": NodePath("/root/Game"),
+"corners": PackedVector4Array(1, 2, 3, 4)
+}
+"#;
+        let (_, resource) = crate::tscn_tres_parser::parse_tres_file(input).unwrap();
+        let binary = write_rsrc_file(&resource).unwrap();
+        let (_, read) = parse_rsrc_file(&binary).unwrap();
+        assert_eq!(read.header.attrs["type"], resource.header.attrs["type"]);
+        assert_eq!(read.header.attrs["script_class"], resource.header.attrs["script_class"]);
+        assert!(!read.header.attrs.contains_key("uid"));
+        assert_same_tags(&read.nodes, &resource.nodes);
+        assert_eq!(u32::from_le_bytes(binary[20..24].try_into().unwrap()), FORMAT_VERSION);
+    }
+
+    #[test]
+    fn test_write_rsrc_unknown_resource() {
+        let input = "[gd_resource type=\"Resource\" format=3]\n\n[resource]\nnext = SubResource(\"missing\")\n";
+        let (_, resource) = crate::tscn_tres_parser::parse_tres_file(input).unwrap();
+        assert!(write_rsrc_file(&resource).is_none());
+    }
+}
@@ -161,6 +161,9 @@ pub fn parse_tres_file(str: &str) -> IResult<&str, TSCNFile> {
 #[cfg(test)]
 mod tests {
     use godot_data::nanoserde::SerJson;
+    use godot_data::values::GodotValue;
+    use crate::data::values::parse_godot_value;
+    use crate::data::writer::write_godot_value;
     use crate::tscn_tres_parser::{parse_tres_file, parse_tscn_file};
 
     #[test]
@@ -185,7 +188,7 @@ autowrap_mode = 2
 justification_flags = 2
 clip_text = true
         "#;
-        let (input, tscn) = parse_tscn_file(input).unwrap();
+        let (_, tscn) = parse_tscn_file(input).unwrap();
         println!("{}", tscn.serialize_json());
     }
 
@@ -198,7 +201,48 @@ clip_text = true
 [resource]
 atlas = ExtResource("1_nhero")
 region = Rect2(1, 1483, 245, 245)"#;
-        let (input, tscn) = parse_tres_file(input).unwrap();
+        let (_, tscn) = parse_tres_file(input).unwrap();
         println!("{}", tscn.serialize_json());
     }
+
+    #[test]
+    fn test_parse_tscn_3d_values() {
+        let input = r#"[gd_scene format=3]
+
+[node name="World" type="Node3D"]
+transform = Transform3D(1, 0, 0, 0, 1, 0, 0, 0, 1, 0.5, -2, 1e-05)
+size = Vector2i(64, 32)
+points = PackedVector3Array(0, 0, 0, 1, 2.5, 3)
+indices = PackedInt32Array(0, 1, 2)
+targets = Array[NodePath]([NodePath(""), NodePath("../Player:position")])
+limit = inf
+"#;
+        let (_, tscn) = parse_tscn_file(input).unwrap();
+        let props = &tscn.nodes[0].props;
+        assert_eq!(
+            props["transform"],
+            GodotValue::Transform3D([1.0, 0.0, 0.0, 0.0, 1.0, 0.0, 0.0, 0.0, 1.0, 0.5, -2.0, 1e-05])
+        );
+        assert_eq!(props["size"], GodotValue::Vector2i((64, 32)));
+        assert_eq!(props["points"], GodotValue::PackedVector3Array(vec![(0.0, 0.0, 0.0), (1.0, 2.5, 3.0)]));
+        assert_eq!(props["indices"], GodotValue::PackedInt32Array(vec![0, 1, 2]));
+        assert_eq!(
+            props["targets"],
+            GodotValue::Array(vec![
+                GodotValue::NodePath(String::new()),
+                GodotValue::NodePath("../Player:position".to_string())
+            ])
+        );
+        assert_eq!(props["limit"], GodotValue::Float(f64::INFINITY));
+        for value in props.values() {
+            let written = write_godot_value(value);
+            assert_eq!(&parse_godot_value(&written).unwrap().1, value, "{}", written);
+        }
+
+        // Integral components past the exact range of doubles stay floats instead of saturating.
+        for value in [GodotValue::Vector2((1e20, 0.0)), GodotValue::Rect2((-1e300, 0.5, 2.0, 9007199254740992.0))] {
+            let written = write_godot_value(&value);
+            assert_eq!(parse_godot_value(&written).unwrap().1, value, "{}", written);
+        }
+    }
 }
\ No newline at end of file
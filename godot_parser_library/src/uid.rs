@@ -0,0 +1,59 @@
+//! Conversion between numeric resource UIDs and their `uid://` text form.
+
+pub const UID_PREFIX: &str = "uid://";
+pub const INVALID_UID: i64 = -1;
+
+// Godot encodes ids in base 34 using `a`..`y` followed by `0`..`8`.
+const CHAR_COUNT: i64 = (b'z' - b'a') as i64;
+const BASE: i64 = CHAR_COUNT + (b'9' - b'0') as i64;
+
+pub fn id_to_text(id: i64) -> Option<String> {
+    if id < 0 {
+        return None;
+    }
+    let mut id = id;
+    let mut text = Vec::new();
+    // At least one digit, like Godot: id 0 is `uid://a`.
+    loop {
+        let c = id % BASE;
+        text.push(if c < CHAR_COUNT {
+            (b'a' + c as u8) as char
+        } else {
+            (b'0' + (c - CHAR_COUNT) as u8) as char
+        });
+        id /= BASE;
+        if id == 0 {
+            break;
+        }
+    }
+    Some(format!("{}{}", UID_PREFIX, text.iter().rev().collect::<String>()))
+}
+
+pub fn text_to_id(text: &str) -> Option<i64> {
+    let digits = text.strip_prefix(UID_PREFIX)?;
+    let mut id: i64 = 0;
+    for c in digits.bytes() {
+        let value = match c {
+            b'a'..=b'y' => (c - b'a') as i64,
+            b'0'..=b'8' => (c - b'0') as i64 + CHAR_COUNT,
+            _ => return None,
+        };
+        id = id.checked_mul(BASE)?.checked_add(value)?;
+    }
+    Some(id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_uid_round_trip() {
+        let id = text_to_id("uid://bqov4kuchixhi").unwrap();
+        assert_eq!(id_to_text(id).as_deref(), Some("uid://bqov4kuchixhi"));
+        assert_eq!(id_to_text(INVALID_UID), None);
+        assert_eq!(id_to_text(0).as_deref(), Some("uid://a"));
+        assert_eq!(text_to_id("uid://a"), Some(0));
+        assert_eq!(text_to_id("uid://<invalid>"), None);
+    }
+}